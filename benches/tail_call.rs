@@ -0,0 +1,189 @@
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use wasmtime::*;
+
+criterion_main!(benches);
+criterion_group!(
+    benches,
+    bench_self_tail_recursion,
+    bench_relaxed_stack_check
+);
+
+const FACTORIAL: &str = r#"
+    (module
+        (func $factorial (export "factorial-tail") (param i64 i64) (result i64)
+            local.get 0
+            i64.const 1
+            i64.le_s
+            if (result i64)
+                local.get 1
+            else
+                local.get 0
+                i64.const 1
+                i64.sub
+                local.get 0
+                local.get 1
+                i64.mul
+                return_call $factorial
+            end)
+    )
+"#;
+
+const COUNTDOWN: &str = r#"
+    (module
+        (func $countdown (export "countdown") (param i32) (result i32)
+            local.get 0
+            i32.eqz
+            if (result i32)
+                i32.const 0
+            else
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $countdown
+            end)
+    )
+"#;
+
+/// A state machine which cycles through three states, each a separate function
+/// which tail calls the next until the counter runs out.
+const STATE_MACHINE: &str = r#"
+    (module
+        (func $a (export "state-machine") (param i32) (result i32)
+            local.get 0
+            i32.eqz
+            if (result i32)
+                i32.const 0
+            else
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $b
+            end)
+        (func $b (param i32) (result i32)
+            local.get 0
+            i32.eqz
+            if (result i32)
+                i32.const 1
+            else
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $c
+            end)
+        (func $c (param i32) (result i32)
+            local.get 0
+            i32.eqz
+            if (result i32)
+                i32.const 2
+            else
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $a
+            end)
+    )
+"#;
+
+/// Compares self-recursive tail calls compiled as tail calls against those
+/// compiled as loops with `TailCallOptions::self_recursion_loop`.
+fn bench_self_tail_recursion(c: &mut Criterion) {
+    let mut group = c.benchmark_group("self-tail-recursion");
+    for (name, self_recursion_loop) in [("tail-call", false), ("loop", true)] {
+        let mut config = Config::new();
+        let mut options = TailCallOptions::new();
+        options.self_recursion_loop(self_recursion_loop);
+        config.wasm_tail_call(true).tail_call_options(options);
+        let engine = Engine::new(&config).unwrap();
+        let module = Module::new(&engine, FACTORIAL).unwrap();
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[]).unwrap();
+        let factorial = instance
+            .get_typed_func::<(i64, i64), i64>(&mut store, "factorial-tail")
+            .unwrap();
+
+        for depth in [1_000, 100_000] {
+            group.bench_function(BenchmarkId::new(name, depth), |b| {
+                b.iter(|| factorial.call(&mut store, (depth, 1)).unwrap());
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Compares functions which only make tail calls with their stack checks
+/// relaxed by `TailCallOptions::relaxed_stack_check` against the same
+/// functions with standard stack checks.
+///
+/// Alongside the timings this prints the smallest `Config::max_wasm_stack`
+/// that each workload runs in, which should stay constant as the depth grows.
+fn bench_relaxed_stack_check(c: &mut Criterion) {
+    let workloads: [(&str, &str, fn(i64) -> Vec<Val>); 3] = [
+        (FACTORIAL, "factorial-tail", |depth| {
+            vec![Val::I64(depth), Val::I64(1)]
+        }),
+        (COUNTDOWN, "countdown", |depth| vec![Val::I32(depth as i32)]),
+        (STATE_MACHINE, "state-machine", |depth| {
+            vec![Val::I32(depth as i32)]
+        }),
+    ];
+
+    for (wat, export, params) in workloads {
+        let mut group = c.benchmark_group(format!("relaxed-stack-check/{export}"));
+        for (name, relaxed_stack_check) in [("relaxed", true), ("standard", false)] {
+            let mut config = Config::new();
+            let mut options = TailCallOptions::new();
+            options.relaxed_stack_check(relaxed_stack_check);
+            config.wasm_tail_call(true).tail_call_options(options);
+            let engine = Engine::new(&config).unwrap();
+            let module = Module::new(&engine, wat).unwrap();
+            let mut store = Store::new(&engine, ());
+            let instance = Instance::new(&mut store, &module, &[]).unwrap();
+            let func = instance.get_func(&mut store, export).unwrap();
+            let mut results = vec![Val::I32(0); func.ty(&store).results().len()];
+
+            for depth in [100, 10_000, 1_000_000] {
+                let params = params(depth);
+                let stack = min_wasm_stack(&config, &module, export, &params);
+                println!("{export}/{name}/{depth}: runs in a {stack}-byte wasm stack");
+
+                group.bench_function(BenchmarkId::new(name, depth), |b| {
+                    b.iter(|| func.call(&mut store, &params, &mut results).unwrap());
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Returns the smallest `max_wasm_stack`, to within 256 bytes, with which
+/// calling `export` with `params` doesn't overflow the stack.
+fn min_wasm_stack(config: &Config, module: &Module, export: &str, params: &[Val]) -> usize {
+    let bytes = module.serialize().unwrap();
+    let succeeds = |max_wasm_stack: usize| {
+        let mut config = config.clone();
+        config.max_wasm_stack(max_wasm_stack);
+        let engine = Engine::new(&config).unwrap();
+        let module = unsafe { Module::deserialize(&engine, &bytes).unwrap() };
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[]).unwrap();
+        let func = instance.get_func(&mut store, export).unwrap();
+        let mut results = vec![Val::I32(0); func.ty(&store).results().len()];
+        match func.call(&mut store, params, &mut results) {
+            Ok(()) => true,
+            Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::StackOverflow) => false,
+            Err(e) => panic!("{e:?}"),
+        }
+    };
+
+    let (mut lo, mut hi) = (0, 4 << 20);
+    assert!(succeeds(hi), "`{export}` overflows even a {hi}-byte stack");
+    while hi - lo > 256 {
+        let mid = lo + (hi - lo) / 2;
+        if succeeds(mid) {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    hi
+}
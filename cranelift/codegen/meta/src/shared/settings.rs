@@ -276,6 +276,73 @@ pub(crate) fn define() -> SettingGroup {
         false,
     );
 
+    settings.add_bool(
+        "tail_call_relaxed_stack_check",
+        "Treat functions which only make tail calls like leaf functions when checking the stack limit.",
+        r#"
+            A function whose only calls are tail calls tears down its own frame
+            before each call, so its callees' frames are never pushed on top of
+            it. When this is enabled, and the target's calling convention
+            supports it, such functions don't reserve room for a callee's setup
+            area in their stack limit check and skip the check entirely if they
+            otherwise need no stack, just like leaf functions.
+        "#,
+        false,
+    );
+
+    settings.add_bool(
+        "tail_call_zero_frame",
+        "Zero the frame torn down by each tail call before jumping to the callee.",
+        r#"
+            A tail call tears down the caller's frame and its callee's frame
+            then occupies the same region of the stack. When this is enabled
+            the caller's stack slots, spill slots, saved callee-saved
+            registers, and outgoing argument area are overwritten with zeros
+            after the callee-saved registers are restored but before the frame
+            is freed, so no stale data from the caller is left behind and
+            nothing is ever written below the stack pointer. The saved frame
+            pointer, return address, and incoming arguments, which the callee
+            uses, are left intact.
+
+            This is currently only implemented on x86-64 and aarch64; other
+            backends ignore it.
+        "#,
+        false,
+    );
+
+    settings.add_num(
+        "tail_call_max_stack_args_bytes",
+        "The size of outgoing stack arguments, in bytes, that still allows a relaxed stack check.",
+        r#"
+            Functions which only make tail calls but need stack space for their
+            callees' arguments reserve that space once, as part of the stack
+            limit check at function entry. With
+            `tail_call_relaxed_stack_check` enabled, such functions only have
+            their stack limit check relaxed if their stack argument region is
+            at most this many bytes; otherwise they keep a standard check.
+
+            The default is 0, meaning that any stack arguments prevent the
+            relaxation.
+        "#,
+        0,
+    );
+
+    settings.add_num(
+        "tail_call_min_frame_savings",
+        "The number of bytes of stack, at least, that relaxing a stack check must save.",
+        r#"
+            With `tail_call_relaxed_stack_check` enabled, functions which only
+            make tail calls no longer reserve room for a callee's setup area in
+            their stack limit check. When that setup area is smaller than this
+            many bytes the relaxation isn't applied and the function keeps a
+            standard stack check.
+
+            The default is 0, meaning that the relaxation is applied whenever
+            possible.
+        "#,
+        0,
+    );
+
     settings.add_bool(
         "machine_code_cfg_info",
         "Generate CFG metadata for machine code.",
@@ -1184,6 +1184,17 @@ impl ABIMachineSpec for AArch64MachineDeps {
             _ => &[],
         }
     }
+
+    fn supports_tail_call_relaxed_stack_check(call_conv: isa::CallConv) -> bool {
+        // Tail calls restore clobbers, pop the frame record with `ldp fp, lr`,
+        // and release any excess incoming argument space before branching to
+        // the callee, which then stores its own frame record right where ours
+        // was.
+        matches!(
+            call_conv,
+            isa::CallConv::SystemV | isa::CallConv::AppleAarch64 | isa::CallConv::Tail
+        )
+    }
 }
 
 impl AArch64MachineDeps {
@@ -1232,6 +1243,49 @@ impl AArch64MachineDeps {
         });
     }
 
+    /// Load the callee-saved registers from where `gen_clobber_save` stored
+    /// them, like `gen_clobber_restore` does, but without moving SP, so that
+    /// the whole frame stays allocated afterwards. Used by tail calls that
+    /// zero the frame before freeing it.
+    pub fn gen_clobber_restore_in_place(frame_layout: &FrameLayout) -> SmallVec<[Inst; 16]> {
+        let mut insts = SmallVec::new();
+        let (clobbered_int, clobbered_vec) = frame_layout.clobbered_callee_saves_by_class();
+
+        // Each pair of registers, or final odd register, of a class occupies
+        // 16 bytes, with the vector registers below the integer ones and all of
+        // them just above the fixed frame.
+        let mut off =
+            i64::from(frame_layout.fixed_frame_storage_size + frame_layout.outgoing_args_size);
+
+        for chunk in clobbered_vec.chunks(2) {
+            for (rd, slot) in chunk.iter().zip([0, 8]) {
+                let rd: Writable<Reg> = rd.map(|r| r.into());
+                debug_assert_eq!(rd.to_reg().class(), RegClass::Float);
+                insts.push(Inst::FpuLoad64 {
+                    rd,
+                    mem: AMode::SPOffset { off: off + slot },
+                    flags: MemFlags::trusted(),
+                });
+            }
+            off += 16;
+        }
+
+        for chunk in clobbered_int.chunks(2) {
+            for (rd, slot) in chunk.iter().zip([0, 8]) {
+                let rd: Writable<Reg> = rd.map(|r| r.into());
+                debug_assert_eq!(rd.to_reg().class(), RegClass::Int);
+                insts.push(Inst::ULoad64 {
+                    rd,
+                    mem: AMode::SPOffset { off: off + slot },
+                    flags: MemFlags::trusted(),
+                });
+            }
+            off += 16;
+        }
+
+        insts
+    }
+
     pub fn select_api_key(
         isa_flags: &aarch64_settings::Flags,
         call_conv: isa::CallConv,
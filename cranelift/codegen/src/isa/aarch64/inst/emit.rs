@@ -3623,10 +3623,99 @@ fn emit_return_call_common_sequence<T>(
     state: &mut EmitState,
     info: &ReturnCallInfo<T>,
 ) {
-    for inst in
-        AArch64MachineDeps::gen_clobber_restore(CallConv::Tail, &emit_info.0, state.frame_layout())
-    {
-        inst.emit(sink, emit_info, state);
+    let frame_layout = state.frame_layout();
+    let frame_size = frame_layout.fixed_frame_storage_size
+        + frame_layout.clobber_size
+        + frame_layout.outgoing_args_size;
+
+    if emit_info.0.tail_call_zero_frame() && frame_size > 0 {
+        // Restore the clobbers without popping them, so that the frame is
+        // still allocated while it's scrubbed rather than lying below SP.
+        for inst in AArch64MachineDeps::gen_clobber_restore_in_place(frame_layout) {
+            inst.emit(sink, emit_info, state);
+        }
+
+        // Zero the frame two words at a time, from SP up to its top:
+        //
+        //          add x16, sp, #0
+        //          mov x17, #frame_size
+        //          add x17, x16, x17
+        //      loop_start:
+        //          stp xzr, xzr, [x16]
+        //          add x16, x16, #16
+        //          subs xzr, x16, x17
+        //          b.ne loop_start
+        //      loop_end:
+        debug_assert_eq!(frame_size % 16, 0);
+        let cur = writable_spilltmp_reg();
+        let end = writable_tmp2_reg();
+        Inst::AluRRImm12 {
+            alu_op: ALUOp::Add,
+            size: OperandSize::Size64,
+            rd: cur,
+            rn: stack_reg(),
+            imm12: Imm12::ZERO,
+        }
+        .emit(sink, emit_info, state);
+        for inst in Inst::load_constant(end, frame_size.into()) {
+            inst.emit(sink, emit_info, state);
+        }
+        Inst::AluRRR {
+            alu_op: ALUOp::Add,
+            size: OperandSize::Size64,
+            rd: end,
+            rn: cur.to_reg(),
+            rm: end.to_reg(),
+        }
+        .emit(sink, emit_info, state);
+
+        let loop_start = sink.get_label();
+        sink.bind_label(loop_start, &mut state.ctrl_plane);
+        Inst::StoreP64 {
+            rt: zero_reg(),
+            rt2: zero_reg(),
+            mem: PairAMode::SignedOffset {
+                reg: cur.to_reg(),
+                simm7: SImm7Scaled::maybe_from_i64(0, types::I64).unwrap(),
+            },
+            flags: MemFlags::trusted(),
+        }
+        .emit(sink, emit_info, state);
+        Inst::AluRRImm12 {
+            alu_op: ALUOp::Add,
+            size: OperandSize::Size64,
+            rd: cur,
+            rn: cur.to_reg(),
+            imm12: Imm12::maybe_from_u64(16).unwrap(),
+        }
+        .emit(sink, emit_info, state);
+        Inst::AluRRR {
+            alu_op: ALUOp::SubS,
+            size: OperandSize::Size64,
+            rd: writable_zero_reg(),
+            rn: cur.to_reg(),
+            rm: end.to_reg(),
+        }
+        .emit(sink, emit_info, state);
+        let loop_end = sink.get_label();
+        Inst::CondBr {
+            taken: BranchTarget::Label(loop_start),
+            not_taken: BranchTarget::Label(loop_end),
+            kind: CondBrKind::Cond(Cond::Ne),
+        }
+        .emit(sink, emit_info, state);
+        sink.bind_label(loop_end, &mut state.ctrl_plane);
+
+        // Now free the frame.
+        for inst in AArch64MachineDeps::gen_sp_reg_adjust(i32::try_from(frame_size).unwrap()) {
+            inst.emit(sink, emit_info, state);
+        }
+    } else {
+        for inst in
+            AArch64MachineDeps::gen_clobber_restore(CallConv::Tail, &emit_info.0, frame_layout)
+        {
+            inst.emit(sink, emit_info, state);
+        }
     }
 
     let setup_area_size = state.frame_layout().setup_area_size;
@@ -93,6 +93,9 @@ impl TargetIsa for S390xBackend {
             dynamic_stackslot_offsets,
             bb_starts: emit_result.bb_offsets,
             bb_edges: emit_result.bb_edges,
+            tail_call_deopt: emit_result.tail_call_deopt,
+            tail_call_frame_savings: emit_result.tail_call_frame_savings,
+            frame_layout_summary: emit_result.frame_layout_summary,
         })
     }
 
@@ -965,6 +965,13 @@ impl ABIMachineSpec for X64ABIMachineSpec {
             _ => &[],
         }
     }
+
+    fn supports_tail_call_relaxed_stack_check(call_conv: isa::CallConv) -> bool {
+        // Tail calls pop our frame, including the saved frame pointer, and
+        // then jump to the callee, which pushes its own frame pointer right
+        // where ours was.
+        matches!(call_conv, isa::CallConv::SystemV | isa::CallConv::Tail)
+    }
 }
 
 impl From<StackAMode> for SyntheticAmode {
@@ -1840,6 +1840,9 @@ pub(crate) fn emit(
 ///
 /// * Copy the new frame's stack arguments over the top of our current frame.
 ///
+/// * Restore the callee-saved registers and, if `tail_call_zero_frame` is
+///   enabled, zero the rest of our frame before freeing it.
+///
 /// * Restore the old frame pointer.
 ///
 /// * Initialize the tail callee's stack pointer (simultaneously deallocating
@@ -1861,12 +1864,54 @@ fn emit_return_call_common_sequence<T>(
 
     let tmp = call_info.tmp.to_writable_reg();
 
-    for inst in
-        X64ABIMachineSpec::gen_clobber_restore(CallConv::Tail, &info.flags, state.frame_layout())
-    {
+    let frame_layout = state.frame_layout();
+    let frame_size = frame_layout.fixed_frame_storage_size
+        + frame_layout.clobber_size
+        + frame_layout.outgoing_args_size;
+    let mut clobber_restore =
+        X64ABIMachineSpec::gen_clobber_restore(CallConv::Tail, &info.flags, frame_layout);
+
+    // Restoring the clobbers ends with an `add rsp` that frees the frame. When
+    // zeroing the frame, hold it back so that the frame is scrubbed while it is
+    // still allocated, rather than writing below RSP.
+    let free_frame = if info.flags.tail_call_zero_frame() && frame_size > 0 {
+        clobber_restore.pop()
+    } else {
+        None
+    };
+
+    for inst in clobber_restore {
         inst.emit(sink, info, state);
     }
 
+    if let Some(free_frame) = free_frame {
+        debug_assert_eq!(frame_size % 8, 0);
+
+        // Zero the frame one word at a time, from its top down to RSP:
+        //
+        //         mov  tmp, rsp
+        //         add  tmp, frame_size
+        // .loop:
+        //         sub  tmp, 8
+        //         mov  qword ptr [tmp], 0
+        //         cmp  tmp, rsp
+        //         jne  .loop
+        Inst::gen_move(tmp, regs::rsp(), types::I64).emit(sink, info, state);
+        let frame_size = i32::try_from(frame_size)
+            .expect("`frame_size` is too large to fit in a 32-bit immediate");
+        Inst::addq_mi(tmp, frame_size).emit(sink, info, state);
+
+        let loop_start = sink.get_label();
+        sink.bind_label(loop_start, state.ctrl_plane_mut());
+        Inst::subq_mi(tmp, 8).emit(sink, info, state);
+        asm::inst::movq_mi_sxl::new(Amode::imm_reg(0, tmp.to_reg()), 0).emit(sink, info, state);
+        let tmp = Gpr::unwrap_new(tmp.to_reg());
+        asm::inst::cmpq_rm::new(tmp, Gpr::RSP).emit(sink, info, state);
+        one_way_jmp(sink, CC::NZ, loop_start);
+
+        free_frame.emit(sink, info, state);
+    }
+
     for inst in X64ABIMachineSpec::gen_epilogue_frame_restore(
         CallConv::Tail,
         &info.flags,
@@ -68,9 +68,10 @@ pub use crate::machinst::buffer::{
     OpenPatchRegion, PatchRegion,
 };
 pub use crate::machinst::{
-    CallInfo, CompiledCode, Final, MachBuffer, MachBufferFinalized, MachInst, MachInstEmit,
-    MachInstEmitState, MachLabel, RealReg, Reg, RelocDistance, TextSectionBuilder, VCodeConstant,
-    VCodeConstantData, VCodeConstants, VCodeInst, Writable,
+    CallInfo, CompiledCode, Final, FrameLayoutSummary, MachBuffer, MachBufferFinalized, MachInst,
+    MachInstEmit, MachInstEmitState, MachLabel, RealReg, Reg, RelocDistance, TailCallDeopt,
+    TailCallDeoptReason, TextSectionBuilder, VCodeConstant, VCodeConstantData, VCodeConstants,
+    VCodeInst, Writable,
 };
 
 mod alias_analysis;
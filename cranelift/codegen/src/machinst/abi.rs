@@ -609,6 +609,18 @@ pub trait ABIMachineSpec {
         let _ = callee_conv;
         &[]
     }
+
+    /// Whether functions with the given calling convention which only make
+    /// tail calls may skip reserving space for a callee's setup area in their
+    /// stack limit check, as controlled by the `tail_call_relaxed_stack_check`
+    /// setting.
+    ///
+    /// This must only return `true` if a tail call always places the callee's
+    /// setup area exactly where the caller's own setup area was.
+    fn supports_tail_call_relaxed_stack_check(call_conv: isa::CallConv) -> bool {
+        let _ = call_conv;
+        false
+    }
 }
 
 /// Out-of-line data for calls, to keep the size of `Inst` down.
@@ -2206,6 +2218,105 @@ impl<M: ABIMachineSpec> Callee<M> {
         ));
     }
 
+    /// Returns whether no callee's frame is ever pushed on top of this
+    /// function's frame, either because it makes no calls at all or because
+    /// it only makes tail calls and the `tail_call_relaxed_stack_check`
+    /// setting applies to it.
+    fn has_no_callee_frames(&self) -> bool {
+        match self.frame_layout().function_calls {
+            FunctionCalls::None => true,
+            FunctionCalls::TailOnly => self.is_tail_call_stack_check_relaxed(),
+            FunctionCalls::Regular => false,
+        }
+    }
+
+    /// Decides whether the `tail_call_relaxed_stack_check` setting applies to
+    /// this function.
+    ///
+    /// Returns `None` if the setting is disabled or this function doesn't only
+    /// make tail calls, `Some(Ok(()))` if its stack limit check is relaxed, and
+    /// otherwise why it isn't. Both `is_tail_call_stack_check_relaxed` and
+    /// `tail_call_deopt` are derived from this so that a function is never
+    /// reported as both relaxed and not.
+    fn tail_call_relaxation(&self) -> Option<Result<(), TailCallDeoptReason>> {
+        let frame_layout = self.frame_layout();
+        if frame_layout.function_calls != FunctionCalls::TailOnly
+            || !self.flags.tail_call_relaxed_stack_check()
+        {
+            return None;
+        }
+        let min_frame_savings = u32::from(self.flags.tail_call_min_frame_savings());
+        let max_stack_args_bytes = u32::from(self.flags.tail_call_max_stack_args_bytes());
+        Some(
+            if !M::supports_tail_call_relaxed_stack_check(self.call_conv) {
+                Err(TailCallDeoptReason::UnsupportedCallConv)
+            } else if frame_layout.setup_area_size < min_frame_savings {
+                Err(TailCallDeoptReason::BelowSavingsThreshold)
+            } else if self.tail_call_outgoing_args_size() > max_stack_args_bytes {
+                Err(TailCallDeoptReason::StackArgsPresent)
+            } else {
+                Ok(())
+            },
+        )
+    }
+
+    /// The stack space this function needs for its callees' arguments,
+    /// including any growth of its incoming argument area for tail calls.
+    fn tail_call_outgoing_args_size(&self) -> u32 {
+        let frame_layout = self.frame_layout();
+        (frame_layout.tail_args_size - frame_layout.incoming_args_size)
+            + frame_layout.outgoing_args_size
+    }
+
+    /// Returns whether this function only makes tail calls and has had its
+    /// stack limit check relaxed accordingly; see the
+    /// `tail_call_relaxed_stack_check` setting.
+    pub fn is_tail_call_stack_check_relaxed(&self) -> bool {
+        self.tail_call_relaxation() == Some(Ok(()))
+    }
+
+    /// Returns why this function, which only makes tail calls, still needs a
+    /// standard stack limit check despite the `tail_call_relaxed_stack_check`
+    /// setting.
+    ///
+    /// Returns `None` if the setting is disabled, if this function doesn't
+    /// only make tail calls, or if its stack limit check is relaxed.
+    pub fn tail_call_deopt(&self) -> Option<TailCallDeopt> {
+        let reason = self.tail_call_relaxation()?.err()?;
+        Some(TailCallDeopt {
+            reason,
+            outgoing_args_size: self.tail_call_outgoing_args_size(),
+            stackslots_size: self.frame_layout().fixed_frame_storage_size,
+        })
+    }
+
+    /// Returns the number of bytes of stack which this function no longer
+    /// reserves for its callees' frames in its stack limit check because it
+    /// only makes tail calls, or `None` if the `tail_call_relaxed_stack_check`
+    /// setting doesn't apply to it.
+    pub fn tail_call_frame_savings(&self) -> Option<u32> {
+        if self.is_tail_call_stack_check_relaxed() {
+            Some(self.frame_layout().setup_area_size)
+        } else {
+            None
+        }
+    }
+
+    /// Returns a summary of this function's frame layout, for introspection.
+    pub fn frame_layout_summary(&self) -> FrameLayoutSummary {
+        let frame_layout = self.frame_layout();
+        FrameLayoutSummary {
+            setup_area_size: frame_layout.setup_area_size,
+            clobber_size: frame_layout.clobber_size,
+            clobbered_callee_saves: u32::try_from(frame_layout.clobbered_callee_saves.len())
+                .unwrap(),
+            fixed_frame_storage_size: frame_layout.fixed_frame_storage_size,
+            outgoing_args_size: frame_layout.outgoing_args_size,
+            tail_calls_only: frame_layout.function_calls == FunctionCalls::TailOnly,
+            tail_call_stack_check_relaxed: self.is_tail_call_stack_check_relaxed(),
+        }
+    }
+
     /// Generate a prologue, post-regalloc.
     ///
     /// This should include any stack frame or other setup necessary to use the
@@ -2230,11 +2341,16 @@ impl<M: ABIMachineSpec> Callee<M> {
         // might need. Note that s390x may also use the outgoing args area for
         // backtrace support even in leaf functions, so that should be accounted
         // for unconditionally.
+        //
+        // Functions that only make tail calls tear down their frame before
+        // each call, so when that's supported they are treated like leaf
+        // functions here.
+        let no_callee_frames = self.has_no_callee_frames();
         let total_stacksize = (frame_layout.tail_args_size - frame_layout.incoming_args_size)
             + frame_layout.clobber_size
             + frame_layout.fixed_frame_storage_size
             + frame_layout.outgoing_args_size
-            + if frame_layout.function_calls == FunctionCalls::None {
+            + if no_callee_frames {
                 0
             } else {
                 frame_layout.setup_area_size
@@ -2242,7 +2358,7 @@ impl<M: ABIMachineSpec> Callee<M> {
 
         // Leaf functions with zero stack don't need a stack check if one's
         // specified, otherwise always insert the stack check.
-        if total_stacksize > 0 || frame_layout.function_calls != FunctionCalls::None {
+        if total_stacksize > 0 || !no_callee_frames {
             if let Some((reg, stack_limit_load)) = &self.stack_limit {
                 insts.extend(stack_limit_load.clone());
                 self.insert_stack_check(*reg, total_stacksize, &mut insts);
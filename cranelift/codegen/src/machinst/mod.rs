@@ -411,6 +411,76 @@ pub struct CompiledCodeBase<T: CompilePhase> {
     /// This info is generated only if the `machine_code_cfg_info`
     /// flag is set.
     pub bb_edges: Vec<(CodeOffset, CodeOffset)>,
+    /// Why this function, which only makes tail calls, still required a stack
+    /// limit check despite the `tail_call_relaxed_stack_check` setting.
+    ///
+    /// This is `None` if that setting is disabled, if the function makes
+    /// regular calls or no calls at all, or if the optimization fully applied.
+    pub tail_call_deopt: Option<TailCallDeopt>,
+    /// The number of bytes of stack which this function no longer reserves for
+    /// its callees' frames because it only makes tail calls, or `None` if the
+    /// `tail_call_relaxed_stack_check` setting didn't apply to it.
+    pub tail_call_frame_savings: Option<u32>,
+    /// A summary of this function's stack frame layout.
+    pub frame_layout_summary: FrameLayoutSummary,
+}
+
+/// The reason that a function which only makes tail calls couldn't have its
+/// stack limit check elided by the `tail_call_relaxed_stack_check` setting.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub enum TailCallDeoptReason {
+    /// The backend doesn't support the optimization for the function's calling
+    /// convention.
+    UnsupportedCallConv,
+    /// The function needs more stack space for arguments to the functions it
+    /// calls than the `tail_call_max_stack_args_bytes` setting allows.
+    StackArgsPresent,
+    /// Optimizing the function's frame would save fewer bytes of stack than
+    /// the `tail_call_min_frame_savings` setting requires.
+    BelowSavingsThreshold,
+}
+
+/// Details about a function which only makes tail calls that still required a
+/// stack limit check; see [`TailCallDeoptReason`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct TailCallDeopt {
+    /// Why the optimization didn't apply.
+    pub reason: TailCallDeoptReason,
+    /// The size, in bytes, of stack space needed for outgoing arguments,
+    /// including any growth of the incoming argument area for tail calls.
+    pub outgoing_args_size: u32,
+    /// The size, in bytes, of the function's stack slots and spill slots.
+    pub stackslots_size: u32,
+}
+
+/// A summary of the layout of a compiled function's stack frame, for
+/// introspection.
+///
+/// The sizes correspond to the areas described by the ABI's `FrameLayout`,
+/// from high to low stack addresses.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash)]
+#[cfg_attr(feature = "enable-serde", derive(Serialize, Deserialize))]
+pub struct FrameLayoutSummary {
+    /// The size of the setup area holding the return address and saved frame
+    /// pointer, if any.
+    pub setup_area_size: u32,
+    /// The size of the area used to save clobbered callee-saved registers.
+    pub clobber_size: u32,
+    /// The number of clobbered callee-saved registers saved in the frame.
+    pub clobbered_callee_saves: u32,
+    /// The size of the function's stack slots and spill slots.
+    pub fixed_frame_storage_size: u32,
+    /// The size of the area reserved for outgoing arguments.
+    pub outgoing_args_size: u32,
+    /// Whether the function only makes tail calls, so that every call it makes
+    /// passes its own return address on to the callee instead of pushing a
+    /// new one.
+    pub tail_calls_only: bool,
+    /// Whether the function's frame was optimized by the
+    /// `tail_call_relaxed_stack_check` setting.
+    pub tail_call_stack_check_relaxed: bool,
 }
 
 impl CompiledCodeStencil {
@@ -425,6 +495,9 @@ impl CompiledCodeStencil {
             dynamic_stackslot_offsets: self.dynamic_stackslot_offsets,
             bb_starts: self.bb_starts,
             bb_edges: self.bb_edges,
+            tail_call_deopt: self.tail_call_deopt,
+            tail_call_frame_savings: self.tail_call_frame_savings,
+            frame_layout_summary: self.frame_layout_summary,
         }
     }
 }
@@ -232,6 +232,17 @@ pub struct EmitResult {
 
     /// Stack frame size.
     pub frame_size: u32,
+
+    /// Why this function's frame wasn't fully optimized despite only making
+    /// tail calls, if applicable.
+    pub tail_call_deopt: Option<TailCallDeopt>,
+
+    /// The number of stack bytes saved by optimizing this function's frame,
+    /// if applicable.
+    pub tail_call_frame_savings: Option<u32>,
+
+    /// A summary of this function's stack frame layout.
+    pub frame_layout_summary: FrameLayoutSummary,
 }
 
 /// A builder for a VCode function body.
@@ -1124,6 +1135,9 @@ impl<I: VCodeInst> VCode<I> {
             dynamic_stackslot_offsets: self.abi.dynamic_stackslot_offsets().clone(),
             value_labels_ranges,
             frame_size,
+            tail_call_deopt: self.abi.tail_call_deopt(),
+            tail_call_frame_savings: self.abi.tail_call_frame_savings(),
+            frame_layout_summary: self.abi.frame_layout_summary(),
         }
     }
 
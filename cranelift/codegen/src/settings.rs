@@ -492,6 +492,8 @@ opt_level = "none"
 tls_model = "none"
 stack_switch_model = "none"
 libcall_call_conv = "isa_default"
+tail_call_max_stack_args_bytes = 0
+tail_call_min_frame_savings = 0
 probestack_size_log2 = 12
 probestack_strategy = "outline"
 bb_padding_log2_minus_one = 0
@@ -512,6 +514,8 @@ enable_llvm_abi_extensions = false
 enable_multi_ret_implicit_sret = false
 unwind_info = true
 preserve_frame_pointers = false
+tail_call_relaxed_stack_check = false
+tail_call_zero_frame = false
 machine_code_cfg_info = false
 enable_probestack = false
 enable_jump_tables = true
@@ -157,6 +157,52 @@ WASM_API_EXTERN void
 wasmtime_module_image_range(const wasmtime_module_t *module, void **start,
                             void **end);
 
+/**
+ * \brief Specifier for the kinds of calls a function makes, values are in
+ * #wasmtime_call_class_enum
+ */
+typedef uint8_t wasmtime_call_class_t;
+
+/**
+ * \brief The kinds of calls that a function defined in a module makes.
+ *
+ * For more details see:
+ * https://docs.wasmtime.dev/api/wasmtime/enum.CallClass.html
+ */
+enum wasmtime_call_class_enum { // CallClass
+  /// The function doesn't make any calls at all.
+  WASMTIME_CALL_CLASS_LEAF,
+  /// The function only makes tail calls.
+  WASMTIME_CALL_CLASS_TAIL_CALL_ONLY,
+  /// The function only makes regular, non-tail calls.
+  WASMTIME_CALL_CLASS_REGULAR,
+  /// The function makes both tail calls and regular calls.
+  WASMTIME_CALL_CLASS_MIXED,
+  /// The function's only calls are tail calls to itself, which were compiled
+  /// as a loop.
+  WASMTIME_CALL_CLASS_SELF_TAIL_LOOP,
+};
+
+/**
+ * \brief Returns the kinds of calls made by a function defined in this module.
+ *
+ * \param module the module
+ * \param func_index the index of the function within the module's function
+ * index space, which includes imported functions
+ * \param ret if `func_index` is a function defined in this module, this is
+ * filled in with its #wasmtime_call_class_enum value
+ *
+ * \return `true` if `ret` was filled in, or `false` if `func_index` is an
+ * imported function or out of bounds.
+ *
+ * For more details see:
+ * https://docs.wasmtime.dev/api/wasmtime/struct.Module.html#method.function_call_classes
+ */
+WASM_API_EXTERN bool
+wasmtime_module_function_call_class(const wasmtime_module_t *module,
+                                    uint32_t func_index,
+                                    wasmtime_call_class_t *ret);
+
 #ifdef __cplusplus
 } // extern "C"
 #endif
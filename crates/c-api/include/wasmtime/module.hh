@@ -6,6 +6,7 @@
 #define WASMTIME_MODULE_HH
 
 #include <memory>
+#include <optional>
 #include <string_view>
 #include <wasmtime/engine.hh>
 #include <wasmtime/module.h>
@@ -16,6 +17,21 @@
 
 namespace wasmtime {
 
+/// \brief Values returned by `Module::function_call_class`
+enum class CallClass {
+  /// The function doesn't make any calls at all.
+  Leaf = WASMTIME_CALL_CLASS_LEAF,
+  /// The function only makes tail calls.
+  TailCallOnly = WASMTIME_CALL_CLASS_TAIL_CALL_ONLY,
+  /// The function only makes regular, non-tail calls.
+  Regular = WASMTIME_CALL_CLASS_REGULAR,
+  /// The function makes both tail calls and regular calls.
+  Mixed = WASMTIME_CALL_CLASS_MIXED,
+  /// The function's only calls are tail calls to itself, which were compiled
+  /// as a loop.
+  SelfTailLoop = WASMTIME_CALL_CLASS_SELF_TAIL_LOOP,
+};
+
 /**
  * \brief Representation of a compiled WebAssembly module.
  *
@@ -162,6 +178,20 @@ public:
     return list;
   }
 
+  /**
+   * \brief Returns the kinds of calls made by the function at `func_index`.
+   *
+   * Returns `std::nullopt` if `func_index` is an imported function or out of
+   * bounds.
+   */
+  std::optional<CallClass> function_call_class(uint32_t func_index) const {
+    wasmtime_call_class_t ret;
+    if (!wasmtime_module_function_call_class(ptr.get(), func_index, &ret)) {
+      return std::nullopt;
+    }
+    return static_cast<CallClass>(ret);
+  }
+
 #ifdef WASMTIME_FEATURE_COMPILER
   /**
    * \brief Serializes this module to a list of bytes.
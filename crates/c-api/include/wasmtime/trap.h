@@ -74,9 +74,29 @@ enum wasmtime_trap_code_enum {
   /// Async-lifted export failed to produce a result by calling `task.return`
   /// before returning `STATUS_DONE` and/or after all host tasks completed.
   WASMTIME_TRAP_CODE_NO_ASYNC_RESULT,
+  /// We are suspending to a tag for which there is no active handler.
+  WASMTIME_TRAP_CODE_UNHANDLED_TAG,
+  /// Attempt to resume a continuation twice.
+  WASMTIME_TRAP_CODE_CONTINUATION_ALREADY_CONSUMED,
   /// A Pulley opcode was executed at runtime when the opcode was disabled at
   /// compile time.
   WASMTIME_TRAP_CODE_DISABLED_OPCODE,
+  /// Async event loop deadlocked; i.e. it cannot make further progress given
+  /// that all host tasks have completed and any/all host-owned stream/future
+  /// handles have been dropped.
+  WASMTIME_TRAP_CODE_ASYNC_DEADLOCK,
+  /// All of the tail-call fuel configured with `TailCallOptions::fuel` was
+  /// consumed.
+  WASMTIME_TRAP_CODE_TAIL_CALL_FUEL_EXHAUSTED,
+  /// The limit on the depth of nested regular, non-tail calls configured
+  /// with `TailCallOptions::stack_limit` was exceeded.
+  WASMTIME_TRAP_CODE_REGULAR_CALL_DEPTH_EXCEEDED,
+  /// The frame reused by a tail call was found to be corrupted by the checks
+  /// enabled with `TailCallOptions::debug_assertions`.
+  WASMTIME_TRAP_CODE_TAIL_CALL_FRAME_CORRUPTION,
+  /// More consecutive tail calls were made than permitted by
+  /// `TailCallOptions::max_chain_length`.
+  WASMTIME_TRAP_CODE_TAIL_CALL_CHAIN_TOO_LONG,
 };
 
 /**
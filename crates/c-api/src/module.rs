@@ -6,7 +6,7 @@ use crate::{
 use anyhow::Context;
 use std::ffi::CStr;
 use std::os::raw::c_char;
-use wasmtime::{Engine, Module};
+use wasmtime::{CallClass, Engine, Module};
 
 #[derive(Clone)]
 pub struct wasm_module_t {
@@ -201,6 +201,42 @@ pub extern "C" fn wasmtime_module_image_range(
     *end = range.end;
 }
 
+#[repr(u8)]
+#[derive(Clone)]
+pub enum wasmtime_call_class_t {
+    WASMTIME_CALL_CLASS_LEAF,
+    WASMTIME_CALL_CLASS_TAIL_CALL_ONLY,
+    WASMTIME_CALL_CLASS_REGULAR,
+    WASMTIME_CALL_CLASS_MIXED,
+    WASMTIME_CALL_CLASS_SELF_TAIL_LOOP,
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn wasmtime_module_function_call_class(
+    module: &wasmtime_module_t,
+    func_index: u32,
+    ret: &mut wasmtime_call_class_t,
+) -> bool {
+    use wasmtime_call_class_t::*;
+    let Some((_, class)) = module
+        .module
+        .function_call_classes()
+        .into_iter()
+        .find(|(index, _)| index.as_u32() == func_index)
+    else {
+        return false;
+    };
+    *ret = match class {
+        CallClass::Leaf => WASMTIME_CALL_CLASS_LEAF,
+        CallClass::TailCallOnly => WASMTIME_CALL_CLASS_TAIL_CALL_ONLY,
+        CallClass::Regular => WASMTIME_CALL_CLASS_REGULAR,
+        CallClass::Mixed => WASMTIME_CALL_CLASS_MIXED,
+        CallClass::SelfTailLoop => WASMTIME_CALL_CLASS_SELF_TAIL_LOOP,
+        _ => return false,
+    };
+    true
+}
+
 #[unsafe(no_mangle)]
 pub unsafe extern "C" fn wasmtime_module_deserialize(
     engine: &wasm_engine_t,
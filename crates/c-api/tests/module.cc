@@ -14,3 +14,28 @@ TEST(Module, Simple) {
   auto serialized = m.serialize().unwrap();
   Module::deserialize(engine, serialized).unwrap();
 }
+
+TEST(Module, FunctionCallClass) {
+  Engine engine;
+  Module m = Module::compile(engine, R"(
+    (module
+      (import "" "" (func))
+      (func)
+      (func $countdown (param i32)
+        local.get 0
+        i32.eqz
+        br_if 0
+        local.get 0
+        i32.const 1
+        i32.sub
+        return_call $countdown)
+      (func call 1)
+    )
+  )")
+                 .unwrap();
+  EXPECT_EQ(m.function_call_class(0), std::nullopt);
+  EXPECT_EQ(m.function_call_class(1), CallClass::Leaf);
+  EXPECT_EQ(m.function_call_class(2), CallClass::TailCallOnly);
+  EXPECT_EQ(m.function_call_class(3), CallClass::Regular);
+  EXPECT_EQ(m.function_call_class(4), std::nullopt);
+}
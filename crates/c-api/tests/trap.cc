@@ -69,6 +69,24 @@ TEST(Trap, Codes) {
   TEST_CODE(CAST_FAILURE);
   TEST_CODE(CANNOT_ENTER_COMPONENT);
   TEST_CODE(NO_ASYNC_RESULT);
+  TEST_CODE(UNHANDLED_TAG);
+  TEST_CODE(CONTINUATION_ALREADY_CONSUMED);
   TEST_CODE(DISABLED_OPCODE);
+  TEST_CODE(ASYNC_DEADLOCK);
+  TEST_CODE(TAIL_CALL_FUEL_EXHAUSTED);
+  TEST_CODE(REGULAR_CALL_DEPTH_EXCEEDED);
+  TEST_CODE(TAIL_CALL_FRAME_CORRUPTION);
+  TEST_CODE(TAIL_CALL_CHAIN_TOO_LONG);
 #undef TEST_CODE
+
+  // The codes line up with the traps they describe.
+  EXPECT_TRUE(Trap(WASMTIME_TRAP_CODE_DISABLED_OPCODE)
+                  .message()
+                  .find("pulley opcode disabled") != std::string::npos);
+  EXPECT_TRUE(Trap(WASMTIME_TRAP_CODE_TAIL_CALL_FUEL_EXHAUSTED)
+                  .message()
+                  .find("tail-call fuel") != std::string::npos);
+  EXPECT_TRUE(Trap(WASMTIME_TRAP_CODE_TAIL_CALL_CHAIN_TOO_LONG)
+                  .message()
+                  .find("tail call chain length") != std::string::npos);
 }
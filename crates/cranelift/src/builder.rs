@@ -81,6 +81,9 @@ impl CompilerBuilder for Builder {
             "wasmtime_inlining_sum_size_threshold" => {
                 self.tunables.as_mut().unwrap().inlining_sum_size_threshold = value.parse()?;
             }
+            "wasmtime_inlining_tail_call_only" => {
+                self.tunables.as_mut().unwrap().inlining_tail_call_only = value.parse()?;
+            }
             _ => {
                 self.inner.set(name, value)?;
             }
@@ -99,12 +102,21 @@ impl CompilerBuilder for Builder {
 
     fn build(&self) -> Result<Box<dyn wasmtime_environ::Compiler>> {
         let isa = self.inner.build()?;
+        // Functions whose `tail_call_hints` entry disagrees with the
+        // `tail_call_relaxed_stack_check` setting are compiled with an ISA that
+        // has the setting flipped, which is only built once such a function is
+        // compiled.
+        let flipped = (!isa.flags().tail_call_relaxed_stack_check()).to_string();
+        let tail_call_hint_flags = self
+            .inner
+            .shared_flags_with("tail_call_relaxed_stack_check", &flipped)?;
         Ok(Box::new(crate::compiler::Compiler::new(
             self.tunables
                 .as_ref()
                 .expect("set_tunables not called")
                 .clone(),
             isa,
+            tail_call_hint_flags,
             self.cache_store.clone(),
             self.linkopts.clone(),
             self.clif_dir.clone(),
@@ -1,9 +1,9 @@
 use crate::{Relocation, mach_reloc_to_reloc, mach_trap_to_trap};
 use cranelift_codegen::{
-    Final, MachBufferFinalized, MachSrcLoc, ValueLabelsRanges, ir, isa::unwind::CfaUnwindInfo,
-    isa::unwind::UnwindInfo,
+    Final, MachBufferFinalized, MachSrcLoc, TailCallDeopt, ValueLabelsRanges, ir,
+    isa::unwind::CfaUnwindInfo, isa::unwind::UnwindInfo,
 };
-use wasmtime_environ::{FilePos, InstructionAddressMap, PrimaryMap, TrapInformation};
+use wasmtime_environ::{FilePos, FrameLayout, InstructionAddressMap, PrimaryMap, TrapInformation};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 /// Metadata to translate from binary offsets back to the original
@@ -50,6 +50,14 @@ pub struct CompiledFunctionMetadata {
     pub start_srcloc: FilePos,
     /// End source location.
     pub end_srcloc: FilePos,
+    /// Why this function's frame wasn't fully optimized despite it only making
+    /// tail calls, if applicable.
+    pub tail_call_deopt: Option<TailCallDeopt>,
+    /// The number of stack bytes saved by optimizing this function's frame, if
+    /// it was optimized.
+    pub tail_call_frame_savings: Option<u32>,
+    /// The layout of this function's stack frame.
+    pub frame_layout: Option<FrameLayout>,
 }
 
 /// Compiled function: machine code body, jump table offsets, and unwind information.
@@ -159,6 +167,21 @@ impl CompiledFunction {
     pub fn set_sized_stack_slots(&mut self, slots: ir::StackSlots) {
         self.metadata.sized_stack_slots = slots;
     }
+
+    /// Set the reason this function's frame wasn't fully optimized.
+    pub fn set_tail_call_deopt(&mut self, deopt: TailCallDeopt) {
+        self.metadata.tail_call_deopt = Some(deopt);
+    }
+
+    /// Set the number of stack bytes saved by optimizing this function's frame.
+    pub fn set_tail_call_frame_savings(&mut self, savings: u32) {
+        self.metadata.tail_call_frame_savings = Some(savings);
+    }
+
+    /// Set the layout of this function's stack frame.
+    pub fn set_frame_layout(&mut self, frame_layout: FrameLayout) {
+        self.metadata.frame_layout = Some(frame_layout);
+    }
 }
 
 // Collects an iterator of `InstructionAddressMap` into a `Vec` for insertion
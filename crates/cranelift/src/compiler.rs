@@ -10,10 +10,11 @@ use cranelift_codegen::inline::InlineCommand;
 use cranelift_codegen::ir::condcodes::IntCC;
 use cranelift_codegen::ir::{self, InstBuilder, MemFlags, UserExternalName, UserFuncName, Value};
 use cranelift_codegen::isa::{
-    OwnedTargetIsa, TargetIsa,
+    self, OwnedTargetIsa, TargetIsa,
     unwind::{UnwindInfo, UnwindInfoKind},
 };
 use cranelift_codegen::print_errors::pretty_error;
+use cranelift_codegen::settings;
 use cranelift_codegen::{CompiledCode, Context, FinalizedMachCallSite};
 use cranelift_entity::PrimaryMap;
 use cranelift_frontend::FunctionBuilder;
@@ -26,15 +27,16 @@ use std::collections::HashMap;
 use std::mem;
 use std::ops::Range;
 use std::path;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, OnceLock};
 use wasmparser::{FuncValidatorAllocations, FunctionBody};
 use wasmtime_environ::obj::ELF_WASMTIME_EXCEPTIONS;
 use wasmtime_environ::{
-    AddressMapSection, BuiltinFunctionIndex, CacheStore, CompileError, CompiledFunctionBody,
-    DefinedFuncIndex, FlagValue, FuncKey, FunctionBodyData, FunctionLoc, HostCall,
-    InliningCompiler, ModuleTranslation, ModuleTypesBuilder, PtrSize, StackMapSection,
-    StaticModuleIndex, TrapEncodingBuilder, TrapSentinel, TripleExt, Tunables, VMOffsets,
-    WasmFuncType, WasmValType,
+    AddressMapSection, BuiltinFunctionIndex, CacheStore, CallClass, CallConv, CompileError,
+    CompiledFunctionBody, DefinedFuncIndex, FlagValue, FrameLayout, FuncIndex, FuncKey,
+    FunctionBodyData, FunctionLoc, HostCall, InliningCompiler, ModuleTranslation,
+    ModuleTypesBuilder, PtrSize, StackMapSection, StaticModuleIndex, TailCallDeoptInfo,
+    TailCallDeoptReason, TailCallHint, TrapEncodingBuilder, TrapSentinel, TripleExt, Tunables,
+    VMOffsets, WasmFuncType, WasmValType,
 };
 use wasmtime_unwinder::ExceptionTableBuilder;
 
@@ -67,6 +69,15 @@ struct CompilerContext {
     incremental_cache_ctx: Option<IncrementalCacheContext>,
     validator_allocations: FuncValidatorAllocations,
     abi: Option<Abi>,
+    /// Whether to finish compiling with `Compiler::tail_call_hint_isa` because
+    /// of the function's `tail_call_hints` entry or its size.
+    use_tail_call_hint_isa: bool,
+    /// Whether the function only makes tail calls but its frame isn't
+    /// optimized because its body is too large.
+    tail_call_function_too_large: bool,
+    /// The index of the Wasm function being compiled, if any, to report why
+    /// its frame wasn't optimized.
+    func_index: Option<FuncIndex>,
 }
 
 impl Default for CompilerContext {
@@ -77,6 +88,9 @@ impl Default for CompilerContext {
             incremental_cache_ctx: None,
             validator_allocations: Default::default(),
             abi: None,
+            use_tail_call_hint_isa: false,
+            tail_call_function_too_large: false,
+            func_index: None,
         }
     }
 }
@@ -87,6 +101,14 @@ pub struct Compiler {
     tunables: Tunables,
     contexts: Mutex<Vec<CompilerContext>>,
     isa: OwnedTargetIsa,
+    /// The shared flags of `isa` but with the `tail_call_relaxed_stack_check`
+    /// setting flipped, for functions with a `tail_call_hints` entry that
+    /// disagrees with it.
+    tail_call_hint_flags: settings::Flags,
+    /// The ISA built from `tail_call_hint_flags`, which is only built once a
+    /// function needs it since most modules don't have a `tail_call_hints`
+    /// section.
+    tail_call_hint_isa: OnceLock<OwnedTargetIsa>,
     linkopts: LinkOptions,
     cache_store: Option<Arc<dyn CacheStore>>,
     clif_dir: Option<path::PathBuf>,
@@ -126,6 +148,7 @@ impl Compiler {
     pub fn new(
         tunables: Tunables,
         isa: OwnedTargetIsa,
+        tail_call_hint_flags: settings::Flags,
         cache_store: Option<Arc<dyn CacheStore>>,
         linkopts: LinkOptions,
         clif_dir: Option<path::PathBuf>,
@@ -136,6 +159,8 @@ impl Compiler {
             contexts: Default::default(),
             tunables,
             isa,
+            tail_call_hint_flags,
+            tail_call_hint_isa: OnceLock::new(),
             linkopts,
             cache_store,
             clif_dir,
@@ -144,6 +169,18 @@ impl Compiler {
         }
     }
 
+    /// Returns the ISA used for functions whose `tail_call_hints` entry
+    /// disagrees with `isa`, building it the first time it's needed.
+    fn tail_call_hint_isa(&self) -> Result<&dyn TargetIsa, CompileError> {
+        if let Some(isa) = self.tail_call_hint_isa.get() {
+            return Ok(&**isa);
+        }
+        let isa = isa::Builder::from_target_isa(&*self.isa)
+            .finish(self.tail_call_hint_flags.clone())
+            .map_err(|e| CompileError::Codegen(e.to_string()))?;
+        Ok(&**self.tail_call_hint_isa.get_or_init(|| isa))
+    }
+
     /// Perform an indirect call from Cranelift-generated code to native code in
     /// Wasmtime itself.
     ///
@@ -252,6 +289,29 @@ impl wasmtime_environ::Compiler for Compiler {
         let wasm_func_ty = types[sig].unwrap_func();
 
         let mut compiler = self.function_compiler();
+        compiler.cx.func_index = Some(func_index);
+        compiler.cx.use_tail_call_hint_isa = match translation.tail_call_hints[func_index] {
+            TailCallHint::Auto => false,
+            TailCallHint::Force => !isa.flags().tail_call_relaxed_stack_check(),
+            TailCallHint::Forbid => isa.flags().tail_call_relaxed_stack_check(),
+        };
+
+        // Functions without a hint whose bodies are too large are compiled as
+        // if they were marked `forbid`, which is reported as a deopt for those
+        // that only make tail calls.
+        if let Some(max) = self.tunables.tail_call_max_optimized_body_bytes {
+            let size = input.body.range().len();
+            if translation.tail_call_hints[func_index] == TailCallHint::Auto
+                && isa.flags().tail_call_relaxed_stack_check()
+                && size > usize::try_from(max).unwrap()
+                && translation.tail_calls[def_func_index]
+                    .as_ref()
+                    .is_some_and(|t| t.class == CallClass::TailCallOnly)
+            {
+                compiler.cx.use_tail_call_hint_isa = true;
+                compiler.cx.tail_call_function_too_large = true;
+            }
+        }
 
         let context = &mut compiler.cx.codegen_context;
         context.func.signature = wasm_call_signature(isa, wasm_func_ty, &self.tunables);
@@ -262,7 +322,8 @@ impl wasmtime_environ::Compiler for Compiler {
             context.func.collect_debug_info();
         }
 
-        let mut func_env = FuncEnvironment::new(self, translation, types, wasm_func_ty);
+        let mut func_env =
+            FuncEnvironment::new(self, translation, types, wasm_func_ty, def_func_index);
 
         // The `stack_limit` global value below is the implementation of stack
         // overflow checks in Wasmtime.
@@ -340,9 +401,21 @@ impl wasmtime_environ::Compiler for Compiler {
         log::debug!("`{symbol}` translated to CLIF in {:?}", timing.total());
         log::trace!("`{symbol}` timing info\n{timing}");
 
+        // Functions whose self-recursive tail calls became a loop don't make
+        // any tail calls after all.
+        if func_env.is_self_tail_loop() {
+            compiler.cx.tail_call_function_too_large = false;
+        }
+
+        let call_conv = clif_to_env_call_conv(compiler.cx.codegen_context.func.signature.call_conv);
         Ok(CompiledFunctionBody {
             code: box_dyn_any_compiler_context(Some(compiler.cx)),
             needs_gc_heap: func_env.needs_gc_heap(),
+            tail_call_deopt: None,
+            tail_call_frame_savings: None,
+            self_tail_loop: func_env.is_self_tail_loop(),
+            call_conv: Some(call_conv),
+            frame_layout: None,
         })
     }
 
@@ -428,6 +501,11 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: box_dyn_any_compiler_context(Some(compiler.cx)),
             needs_gc_heap: false,
+            tail_call_deopt: None,
+            tail_call_frame_savings: None,
+            self_tail_loop: false,
+            call_conv: None,
+            frame_layout: None,
         })
     }
 
@@ -505,6 +583,11 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: box_dyn_any_compiler_context(Some(compiler.cx)),
             needs_gc_heap: false,
+            tail_call_deopt: None,
+            tail_call_frame_savings: None,
+            self_tail_loop: false,
+            call_conv: None,
+            frame_layout: None,
         })
     }
 
@@ -763,6 +846,11 @@ impl wasmtime_environ::Compiler for Compiler {
         Ok(CompiledFunctionBody {
             code: box_dyn_any_compiler_context(Some(compiler.cx)),
             needs_gc_heap: false,
+            tail_call_deopt: None,
+            tail_call_frame_savings: None,
+            self_tail_loop: false,
+            call_conv: None,
+            frame_layout: None,
         })
     }
 
@@ -892,6 +980,8 @@ impl InliningCompiler for Compiler {
             .unwrap()
             .take()
             .unwrap();
+        let too_large = cx.tail_call_function_too_large;
+        let func_index = cx.func_index;
         let compiler = FunctionCompiler { compiler: self, cx };
 
         let symbol = match compiler.cx.abi {
@@ -910,6 +1000,26 @@ impl InliningCompiler for Compiler {
         log::debug!("`{symbol}` compiled in {:?}", timing.total());
         log::trace!("`{symbol}` timing info\n{timing}");
 
+        func_body.tail_call_deopt = func_index.and_then(|func_index| {
+            if too_large {
+                // Report the sizes of the standard frame the function was
+                // compiled with instead.
+                let layout = compiled_func.metadata().frame_layout.unwrap_or_default();
+                Some(TailCallDeoptInfo::new(
+                    func_index,
+                    TailCallDeoptReason::FunctionTooLarge,
+                    layout.outgoing_args_size,
+                    layout.stack_slots_size,
+                ))
+            } else {
+                compiled_func
+                    .metadata()
+                    .tail_call_deopt
+                    .map(|deopt| clif_to_env_tail_call_deopt(func_index, &deopt))
+            }
+        });
+        func_body.tail_call_frame_savings = compiled_func.metadata().tail_call_frame_savings;
+        func_body.frame_layout = compiled_func.metadata().frame_layout;
         func_body.code = box_dyn_any_compiled_function(compiled_func);
         Ok(())
     }
@@ -1103,6 +1213,9 @@ impl Compiler {
             cx: saved_context
                 .map(|mut ctx| {
                     ctx.codegen_context.clear();
+                    ctx.use_tail_call_hint_isa = false;
+                    ctx.tail_call_function_too_large = false;
+                    ctx.func_index = None;
                     ctx
                 })
                 .unwrap_or_else(|| CompilerContext {
@@ -1229,7 +1342,11 @@ impl FunctionCompiler<'_> {
         symbol: &str,
     ) -> Result<CompiledFunction, CompileError> {
         let context = &mut self.cx.codegen_context;
-        let isa = &*self.compiler.isa;
+        let isa = if self.cx.use_tail_call_hint_isa {
+            self.compiler.tail_call_hint_isa()?
+        } else {
+            &*self.compiler.isa
+        };
 
         // Run compilation, but don't propagate the error just yet. This'll
         // mutate `context` and the IR contained within (optionally) but it may
@@ -1314,6 +1431,16 @@ impl FunctionCompiler<'_> {
             }
         }
 
+        if let Some(deopt) = &compiled_code.tail_call_deopt {
+            compiled_function.set_tail_call_deopt(*deopt);
+        }
+        if let Some(savings) = compiled_code.tail_call_frame_savings {
+            compiled_function.set_tail_call_frame_savings(savings);
+        }
+        compiled_function.set_frame_layout(clif_to_env_frame_layout(
+            &compiled_code.frame_layout_summary,
+        ));
+
         compiled_function
             .set_sized_stack_slots(std::mem::take(&mut context.func.sized_stack_slots));
         self.compiler.contexts.lock().unwrap().push(self.cx);
@@ -1322,6 +1449,54 @@ impl FunctionCompiler<'_> {
     }
 }
 
+/// Convert from Cranelift's description of why the frame of the function
+/// `func_index` wasn't fully optimized to Wasmtime's compiler-agnostic
+/// representation.
+fn clif_to_env_tail_call_deopt(
+    func_index: FuncIndex,
+    deopt: &cranelift_codegen::TailCallDeopt,
+) -> TailCallDeoptInfo {
+    use cranelift_codegen::TailCallDeoptReason as Clif;
+    let reason = match deopt.reason {
+        Clif::UnsupportedCallConv => TailCallDeoptReason::UnsupportedCallConv,
+        Clif::StackArgsPresent => TailCallDeoptReason::StackArgsPresent,
+        Clif::BelowSavingsThreshold => TailCallDeoptReason::BelowSavingsThreshold,
+    };
+    TailCallDeoptInfo::new(
+        func_index,
+        reason,
+        deopt.outgoing_args_size,
+        deopt.stackslots_size,
+    )
+}
+
+/// Convert from Cranelift's summary of a function's frame layout to Wasmtime's
+/// compiler-agnostic representation.
+fn clif_to_env_frame_layout(summary: &cranelift_codegen::FrameLayoutSummary) -> FrameLayout {
+    let mut layout = FrameLayout::default();
+    layout.setup_area_size = summary.setup_area_size;
+    layout.callee_saves_size = summary.clobber_size;
+    layout.callee_saved_registers = summary.clobbered_callee_saves;
+    layout.stack_slots_size = summary.fixed_frame_storage_size;
+    layout.outgoing_args_size = summary.outgoing_args_size;
+    layout.reuses_return_address = summary.tail_calls_only;
+    layout.tail_call_optimized = summary.tail_call_stack_check_relaxed;
+    layout
+}
+
+fn clif_to_env_call_conv(call_conv: isa::CallConv) -> CallConv {
+    match call_conv {
+        isa::CallConv::Fast => CallConv::Fast,
+        isa::CallConv::Cold => CallConv::Cold,
+        isa::CallConv::Tail => CallConv::Tail,
+        isa::CallConv::SystemV => CallConv::SystemV,
+        isa::CallConv::WindowsFastcall => CallConv::WindowsFastcall,
+        isa::CallConv::AppleAarch64 => CallConv::AppleAarch64,
+        isa::CallConv::Winch => CallConv::Winch,
+        isa::CallConv::Probestack => unreachable!("wasm functions don't use `probestack`"),
+    }
+}
+
 /// Convert from Cranelift's representation of a stack map to Wasmtime's
 /// compiler-agnostic representation.
 ///
@@ -1332,6 +1332,11 @@ impl ComponentCompiler for Compiler {
             Ok(CompiledFunctionBody {
                 code: super::box_dyn_any_compiler_context(Some(compiler.cx)),
                 needs_gc_heap: false,
+                tail_call_deopt: None,
+                tail_call_frame_savings: None,
+                self_tail_loop: false,
+                call_conv: None,
+                frame_layout: None,
             })
         };
 
@@ -5,7 +5,10 @@ use crate::translate::{
     FuncTranslationStacks, GlobalVariable, Heap, HeapData, StructFieldsVec, TableData, TableSize,
     TargetEnvironment,
 };
-use crate::{BuiltinFunctionSignatures, TRAP_INTERNAL_ASSERT};
+use crate::{
+    BuiltinFunctionSignatures, TRAP_INTERNAL_ASSERT, TRAP_REGULAR_CALL_DEPTH_EXCEEDED,
+    TRAP_TAIL_CALL_CHAIN_TOO_LONG, TRAP_TAIL_CALL_FRAME_CORRUPTION, TRAP_TAIL_CALL_FUEL_EXHAUSTED,
+};
 use cranelift_codegen::cursor::FuncCursor;
 use cranelift_codegen::ir::condcodes::{FloatCC, IntCC};
 use cranelift_codegen::ir::immediates::{Imm64, Offset32, V128Imm};
@@ -91,6 +94,10 @@ macro_rules! declare_function_signatures {
 }
 wasmtime_environ::foreach_builtin_function!(declare_function_signatures);
 
+/// The value stored in each function's frame and checked by its tail calls
+/// when `Tunables::tail_call_debug_assertions` is enabled.
+const TAIL_CALL_CANARY: i64 = 0x7a11_ca11_c0de_d00d;
+
 /// The `FuncEnvironment` implementation for use by the `ModuleEnvironment`.
 pub struct FuncEnvironment<'module_environment> {
     compiler: &'module_environment Compiler,
@@ -161,8 +168,28 @@ pub struct FuncEnvironment<'module_environment> {
     /// spill, and this isn't any worse than reloading each time.
     epoch_ptr_var: cranelift_frontend::Variable,
 
+    /// The number of further regular calls that may be nested, as it was on
+    /// entry to the function, when the depth of regular calls is limited.
+    /// Each regular call decrements the count in `VMStoreContext` for its
+    /// duration and then restores it to this value, as do exception handlers
+    /// since they're entered without the calls that threw returning.
+    regular_call_depth_var: cranelift_frontend::Variable,
+
+    /// The stack slot holding this function's canary, when tail calls check
+    /// its frame for corruption.
+    tail_call_canary_slot: Option<ir::StackSlot>,
+
     fuel_consumed: i64,
 
+    /// The defined function being translated, used to attribute tail calls to
+    /// it when tail-call profiling is enabled and to recognize self-recursive
+    /// tail calls.
+    def_func_index: DefinedFuncIndex,
+
+    /// Where self-recursive tail calls jump to, if they're being compiled as a
+    /// loop; see `Tunables::tail_call_self_recursion_loop`.
+    self_tail_loop: Option<SelfTailLoop>,
+
     /// A `GlobalValue` in CLIF which represents the stack limit.
     ///
     /// Typically this resides in the `stack_limit` value of `ir::Function` but
@@ -173,12 +200,25 @@ pub struct FuncEnvironment<'module_environment> {
     pub(crate) stack_limit_at_function_entry: Option<ir::GlobalValue>,
 }
 
+/// The loop that self-recursive tail calls are compiled to.
+struct SelfTailLoop {
+    /// The block at the start of the function's body that each tail call
+    /// jumps back to.
+    header: Block,
+    /// The number of locals which are the function's parameters.
+    num_params: usize,
+    /// The initial values of the function's other locals, or `None` for
+    /// non-nullable references which have no initial value.
+    local_inits: Vec<Option<ir::Value>>,
+}
+
 impl<'module_environment> FuncEnvironment<'module_environment> {
     pub fn new(
         compiler: &'module_environment Compiler,
         translation: &'module_environment ModuleTranslation<'module_environment>,
         types: &'module_environment ModuleTypesBuilder,
         wasm_func_ty: &'module_environment WasmFuncType,
+        def_func_index: DefinedFuncIndex,
     ) -> Self {
         let tunables = compiler.tunables();
         let builtin_functions = BuiltinFunctions::new(compiler);
@@ -216,11 +256,16 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             fuel_var: Variable::reserved_value(),
             epoch_deadline_var: Variable::reserved_value(),
             epoch_ptr_var: Variable::reserved_value(),
+            regular_call_depth_var: Variable::reserved_value(),
+            tail_call_canary_slot: None,
 
             // Start with at least one fuel being consumed because even empty
             // functions should consume at least some fuel.
             fuel_consumed: 1,
 
+            def_func_index,
+            self_tail_loop: None,
+
             translation,
 
             stack_limit_at_function_entry: None,
@@ -379,6 +424,12 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
             | Operator::Else
             | Operator::End => 0,
 
+            // Tail calls have their own configurable cost, which is validated
+            // to fit in an `i64`.
+            Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. } => self.tunables.tail_call_fuel_cost as i64,
+
             // everything else, just call it one operation.
             _ => 1,
         };
@@ -556,6 +607,205 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         builder.switch_to_block(continuation_block);
     }
 
+    /// Consumes one unit of tail-call fuel from `VMStoreContext`, trapping if
+    /// there is none left.
+    fn tail_call_fuel_check(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let offset = i32::from(self.offsets.ptr.vmstore_context_tail_call_fuel());
+        let fuel = builder.ins().load(
+            self.pointer_type(),
+            ir::MemFlags::trusted(),
+            vmstore_ctx,
+            offset,
+        );
+        self.trapz(builder, fuel, TRAP_TAIL_CALL_FUEL_EXHAUSTED);
+        let fuel = builder.ins().iadd_imm(fuel, -1);
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), fuel, vmstore_ctx, offset);
+    }
+
+    /// Calls into the host to count a tail call made by the function being
+    /// translated.
+    fn tail_call_profile(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let tail_call_profile = self.builtin_functions.tail_call_profile(builder.func);
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let func = builder
+            .ins()
+            .iconst(I32, i64::from(self.def_func_index.as_u32()));
+        builder.ins().call(tail_call_profile, &[vmctx, func]);
+    }
+
+    /// Calls into the host to invoke the store's tail-call hook for a
+    /// `return_call` of `callee` made by the function being translated, if
+    /// the store has one installed.
+    fn tail_call_hook(&mut self, builder: &mut FunctionBuilder<'_>, callee: u32) {
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let active = builder.ins().load(
+            self.pointer_type(),
+            ir::MemFlags::trusted(),
+            vmstore_ctx,
+            i32::from(self.offsets.ptr.vmstore_context_tail_call_hook_active()),
+        );
+        let hook_block = builder.create_block();
+        let continuation_block = builder.create_block();
+        builder
+            .ins()
+            .brif(active, hook_block, &[], continuation_block, &[]);
+        builder.seal_block(hook_block);
+
+        builder.switch_to_block(hook_block);
+        let tail_call_hook = self.builtin_functions.tail_call_hook(builder.func);
+        let vmctx = self.vmctx_val(&mut builder.cursor());
+        let caller = self.module.func_index(self.def_func_index);
+        let caller = builder.ins().iconst(I32, i64::from(caller.as_u32()));
+        let callee = builder.ins().iconst(I32, i64::from(callee));
+        builder.ins().call(tail_call_hook, &[vmctx, caller, callee]);
+        builder.ins().jump(continuation_block, &[]);
+        builder.seal_block(continuation_block);
+
+        builder.switch_to_block(continuation_block);
+    }
+
+    /// Stores `TAIL_CALL_CANARY` in a new stack slot of this function's frame
+    /// on entry to the function.
+    fn tail_call_canary_function_entry(&mut self, builder: &mut FunctionBuilder<'_>) {
+        debug_assert!(self.tail_call_canary_slot.is_none());
+        let slot = builder.func.create_sized_stack_slot(ir::StackSlotData::new(
+            ir::StackSlotKind::ExplicitSlot,
+            8,
+            3,
+        ));
+        let canary = builder.ins().iconst(I64, TAIL_CALL_CANARY);
+        builder.ins().stack_store(canary, slot, 0);
+        self.tail_call_canary_slot = Some(slot);
+    }
+
+    /// Traps if this function's canary has been overwritten since entry to
+    /// the function.
+    fn tail_call_canary_check(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let slot = self.tail_call_canary_slot.unwrap();
+        // The canary is stored outside of any alias region, so loading it from
+        // the `vmctx` region keeps the optimizer from forwarding the stored
+        // value to this load and folding the check away.
+        let addr = builder.ins().stack_addr(self.pointer_type(), slot, 0);
+        let flags = ir::MemFlags::trusted().with_alias_region(Some(ir::AliasRegion::Vmctx));
+        let canary = builder.ins().load(I64, flags, addr, 0);
+        let corrupted = builder
+            .ins()
+            .icmp_imm(IntCC::NotEqual, canary, TAIL_CALL_CANARY);
+        self.trapnz(builder, corrupted, TRAP_TAIL_CALL_FRAME_CORRUPTION);
+    }
+
+    /// Initializes `regular_call_depth_var` with the remaining depth of
+    /// regular calls on entry to the function.
+    fn regular_call_depth_function_entry(&mut self, builder: &mut FunctionBuilder<'_>) {
+        debug_assert!(self.regular_call_depth_var.is_reserved_value());
+        self.regular_call_depth_var = builder.declare_var(self.pointer_type());
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let depth = builder.ins().load(
+            self.pointer_type(),
+            ir::MemFlags::trusted(),
+            vmstore_ctx,
+            i32::from(
+                self.offsets
+                    .ptr
+                    .vmstore_context_regular_call_depth_remaining(),
+            ),
+        );
+        builder.def_var(self.regular_call_depth_var, depth);
+    }
+
+    /// Traps if no further regular calls may be nested, and otherwise
+    /// decrements the remaining depth for the duration of a regular call.
+    fn regular_call_depth_before_call(&mut self, builder: &mut FunctionBuilder<'_>) {
+        let depth = builder.use_var(self.regular_call_depth_var);
+        self.trapz(builder, depth, TRAP_REGULAR_CALL_DEPTH_EXCEEDED);
+        let depth = builder.ins().iadd_imm(depth, -1);
+        self.regular_call_depth_store(builder, depth);
+    }
+
+    /// Restores the remaining depth of regular calls to its value on entry to
+    /// the function, after a regular call or on entry to an exception handler.
+    pub(crate) fn regular_call_depth_restore(&mut self, builder: &mut FunctionBuilder<'_>) {
+        if !self.tunables.tail_call_stack_limit {
+            return;
+        }
+        let depth = builder.use_var(self.regular_call_depth_var);
+        self.regular_call_depth_store(builder, depth);
+    }
+
+    fn regular_call_depth_store(&mut self, builder: &mut FunctionBuilder<'_>, depth: ir::Value) {
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            depth,
+            vmstore_ctx,
+            i32::from(
+                self.offsets
+                    .ptr
+                    .vmstore_context_regular_call_depth_remaining(),
+            ),
+        );
+    }
+
+    /// Traps if the chain of consecutive tail calls made since the last
+    /// regular call has reached `limit`, and otherwise counts one more tail
+    /// call in the chain.
+    fn tail_call_chain_check(&mut self, builder: &mut FunctionBuilder<'_>, limit: u64) {
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let offset = i32::from(self.offsets.ptr.vmstore_context_tail_call_chain_length());
+        let length = builder.ins().load(
+            self.pointer_type(),
+            ir::MemFlags::trusted(),
+            vmstore_ctx,
+            offset,
+        );
+        // The length can't exceed the range of the pointer type, so clamp the
+        // limit to it before comparing the two as unsigned integers.
+        let max = u64::MAX >> (64 - self.pointer_type().bits());
+        let limit = limit.min(max) as i64;
+        let too_long = builder
+            .ins()
+            .icmp_imm(IntCC::UnsignedGreaterThanOrEqual, length, limit);
+        self.trapnz(builder, too_long, TRAP_TAIL_CALL_CHAIN_TOO_LONG);
+        let length = builder.ins().iadd_imm(length, 1);
+        builder
+            .ins()
+            .store(ir::MemFlags::trusted(), length, vmstore_ctx, offset);
+    }
+
+    /// Starts a new chain of tail calls, after a regular call or on entry to
+    /// an exception handler.
+    pub(crate) fn tail_call_chain_reset(&mut self, builder: &mut FunctionBuilder<'_>) {
+        if self.tunables.max_tail_call_chain_length.is_none() {
+            return;
+        }
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let zero = builder.ins().iconst(self.pointer_type(), 0);
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            zero,
+            vmstore_ctx,
+            i32::from(self.offsets.ptr.vmstore_context_tail_call_chain_length()),
+        );
+    }
+
+    /// Records whether the call about to be made, which may be to a host
+    /// function, is a tail call.
+    fn host_call_is_tail_store(&mut self, builder: &mut FunctionBuilder<'_>, is_tail: bool) {
+        let vmstore_ctx = self.get_vmstore_context_ptr(builder);
+        let is_tail = builder
+            .ins()
+            .iconst(self.pointer_type(), i64::from(is_tail));
+        builder.ins().store(
+            ir::MemFlags::trusted(),
+            is_tail,
+            vmstore_ctx,
+            i32::from(self.offsets.ptr.vmstore_context_host_call_is_tail()),
+        );
+    }
+
     fn epoch_function_entry(&mut self, builder: &mut FunctionBuilder<'_>) {
         debug_assert!(self.epoch_deadline_var.is_reserved_value());
         self.epoch_deadline_var = builder.declare_var(ir::types::I64);
@@ -1150,6 +1400,55 @@ impl<'module_environment> FuncEnvironment<'module_environment> {
         self.needs_gc_heap
     }
 
+    /// Were this function's self-recursive tail calls compiled as a loop?
+    pub fn is_self_tail_loop(&self) -> bool {
+        self.self_tail_loop.is_some()
+    }
+
+    /// Starts a loop which self-recursive tail calls jump back to, if this
+    /// function's only calls are such tail calls and they're configured to be
+    /// compiled as a loop.
+    ///
+    /// This is called once all of the function's locals have been declared
+    /// and initialized, with `local_inits` holding the initial values of the
+    /// locals after the first `num_params`. Everything translated afterwards,
+    /// including the function-entry checks in `before_translate_function`, is
+    /// inside the loop.
+    pub fn begin_self_tail_loop(
+        &mut self,
+        builder: &mut FunctionBuilder,
+        num_params: usize,
+        local_inits: Vec<Option<ir::Value>>,
+    ) {
+        debug_assert!(self.self_tail_loop.is_none());
+        if !self.tunables.tail_call_self_recursion_loop {
+            return;
+        }
+        let self_tail_recursive = self.translation.tail_calls[self.def_func_index]
+            .as_ref()
+            .is_some_and(|t| t.self_tail_recursive);
+        if !self_tail_recursive {
+            return;
+        }
+        let header = builder.create_block();
+        builder.ins().jump(header, &[]);
+        builder.switch_to_block(header);
+        self.self_tail_loop = Some(SelfTailLoop {
+            header,
+            num_params,
+            local_inits,
+        });
+    }
+
+    /// Seals the loop started by `begin_self_tail_loop`, if any, now that all
+    /// of the self-recursive tail calls jumping back to it have been
+    /// translated.
+    pub fn end_self_tail_loop(&mut self, builder: &mut FunctionBuilder) {
+        if let Some(self_tail_loop) = &self.self_tail_loop {
+            builder.seal_block(self_tail_loop.header);
+        }
+    }
+
     /// Get the number of Wasm parameters for the given function.
     pub(crate) fn num_params_for_func(&self, function_index: FuncIndex) -> usize {
         let ty = self.module.functions[function_index]
@@ -2976,6 +3275,28 @@ impl FuncEnvironment<'_> {
         sig_ref: ir::SigRef,
         call_args: &[ir::Value],
     ) -> WasmResult<()> {
+        // Only a call back into this very function with a full set of
+        // parameters can become a jump to the loop header; anything else still
+        // needs a real tail call.
+        let this_func = self.module.func_index(self.def_func_index);
+        if let Some(self_tail_loop) = self
+            .self_tail_loop
+            .as_ref()
+            .filter(|l| callee_index == this_func && call_args.len() == l.num_params)
+        {
+            // Rebind the parameters to the call's arguments and reset all other
+            // locals to their initial values, then start the function over.
+            for (i, arg) in call_args.iter().enumerate() {
+                builder.def_var(Variable::new(i), *arg);
+            }
+            for (i, init) in self_tail_loop.local_inits.iter().enumerate() {
+                if let Some(init) = init {
+                    builder.def_var(Variable::new(self_tail_loop.num_params + i), *init);
+                }
+            }
+            builder.ins().jump(self_tail_loop.header, &[]);
+            return Ok(());
+        }
         Call::new_tail(builder, self).direct_call(callee_index, sig_ref, call_args)?;
         Ok(())
     }
@@ -3473,6 +3794,55 @@ impl FuncEnvironment<'_> {
         if self.tunables.consume_fuel {
             self.fuel_before_op(op, builder, state.reachable());
         }
+        if !state.reachable() {
+            return Ok(());
+        }
+        if is_tail_call_op(op) {
+            // Each enabled kind of tail-call instrumentation runs in this
+            // order before the tail call itself.
+            if self.tunables.tail_call_fuel {
+                self.tail_call_fuel_check(builder);
+            }
+            if self.tunables.tail_call_profiling {
+                self.tail_call_profile(builder);
+            }
+            if self.tunables.tail_call_hooks {
+                if let Operator::ReturnCall { function_index } = *op {
+                    self.tail_call_hook(builder, function_index);
+                }
+            }
+            if self.tunables.tail_call_debug_assertions {
+                self.tail_call_canary_check(builder);
+            }
+            if let Some(limit) = self.tunables.max_tail_call_chain_length {
+                self.tail_call_chain_check(builder, limit);
+            }
+        }
+        if self.tunables.tail_call_stack_limit {
+            match op {
+                Operator::Call { .. }
+                | Operator::CallIndirect { .. }
+                | Operator::CallRef { .. } => self.regular_call_depth_before_call(builder),
+                _ => {}
+            }
+        }
+        if self.tunables.tail_call_host_tracking {
+            // Direct calls to functions defined in this module can't reach
+            // the host, so only calls to imports and indirect calls are
+            // instrumented.
+            let is_import =
+                |index: u32| self.module.is_imported_function(FuncIndex::from_u32(index));
+            let is_tail = match *op {
+                Operator::Call { function_index } if is_import(function_index) => Some(false),
+                Operator::CallIndirect { .. } | Operator::CallRef { .. } => Some(false),
+                Operator::ReturnCall { function_index } if is_import(function_index) => Some(true),
+                Operator::ReturnCallIndirect { .. } | Operator::ReturnCallRef { .. } => Some(true),
+                _ => None,
+            };
+            if let Some(is_tail) = is_tail {
+                self.host_call_is_tail_store(builder, is_tail);
+            }
+        }
         Ok(())
     }
 
@@ -3486,6 +3856,22 @@ impl FuncEnvironment<'_> {
         if self.tunables.consume_fuel && state.reachable() {
             self.fuel_after_op(op, builder);
         }
+        if self.tunables.tail_call_stack_limit && state.reachable() {
+            match op {
+                Operator::Call { .. }
+                | Operator::CallIndirect { .. }
+                | Operator::CallRef { .. } => self.regular_call_depth_restore(builder),
+                _ => {}
+            }
+        }
+        if self.tunables.max_tail_call_chain_length.is_some() && state.reachable() {
+            match op {
+                Operator::Call { .. }
+                | Operator::CallIndirect { .. }
+                | Operator::CallRef { .. } => self.tail_call_chain_reset(builder),
+                _ => {}
+            }
+        }
         Ok(())
     }
 
@@ -3520,6 +3906,14 @@ impl FuncEnvironment<'_> {
             self.epoch_function_entry(builder);
         }
 
+        if self.tunables.tail_call_stack_limit {
+            self.regular_call_depth_function_entry(builder);
+        }
+
+        if self.tunables.tail_call_debug_assertions {
+            self.tail_call_canary_function_entry(builder);
+        }
+
         #[cfg(feature = "wmemcheck")]
         if self.compiler.wmemcheck {
             let func_name = self.current_func_name(builder);
@@ -4128,6 +4522,16 @@ impl FuncEnvironment<'_> {
     }
 }
 
+/// Returns whether `op` is a tail call.
+fn is_tail_call_op(op: &Operator<'_>) -> bool {
+    matches!(
+        op,
+        Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. }
+    )
+}
+
 // Helper function to convert an `IndexType` to an `ir::Type`.
 //
 // Implementing From/Into trait for `IndexType` or `ir::Type` would
@@ -98,6 +98,13 @@ impl<T> IsaBuilder<T> {
             .finish(settings::Flags::new(self.shared_flags.clone()))
     }
 
+    /// Like `shared_flags`, but with the flag `name` overridden to `value`.
+    pub fn shared_flags_with(&self, name: &str, value: &str) -> Result<Flags> {
+        let mut flags = self.shared_flags.clone();
+        flags.set(name, value)?;
+        Ok(settings::Flags::new(flags))
+    }
+
     pub fn shared_flags(&self) -> Flags {
         settings::Flags::new(self.shared_flags.clone())
     }
@@ -74,6 +74,14 @@ pub const TRAP_CONTINUATION_ALREADY_CONSUMED: TrapCode =
     TrapCode::unwrap_user(Trap::ContinuationAlreadyConsumed as u8 + TRAP_OFFSET);
 pub const TRAP_CAST_FAILURE: TrapCode =
     TrapCode::unwrap_user(Trap::CastFailure as u8 + TRAP_OFFSET);
+pub const TRAP_TAIL_CALL_FUEL_EXHAUSTED: TrapCode =
+    TrapCode::unwrap_user(Trap::TailCallFuelExhausted as u8 + TRAP_OFFSET);
+pub const TRAP_REGULAR_CALL_DEPTH_EXCEEDED: TrapCode =
+    TrapCode::unwrap_user(Trap::RegularCallDepthExceeded as u8 + TRAP_OFFSET);
+pub const TRAP_TAIL_CALL_FRAME_CORRUPTION: TrapCode =
+    TrapCode::unwrap_user(Trap::TailCallFrameCorruption as u8 + TRAP_OFFSET);
+pub const TRAP_TAIL_CALL_CHAIN_TOO_LONG: TrapCode =
+    TrapCode::unwrap_user(Trap::TailCallChainTooLong as u8 + TRAP_OFFSET);
 
 /// Creates a new cranelift `Signature` with no wasm params/results for the
 /// given calling convention.
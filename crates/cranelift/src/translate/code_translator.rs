@@ -4224,6 +4224,8 @@ fn create_catch_block(
     };
     let block = block_with_params(builder, [exn_payload_wasm_ty], environ)?;
     builder.switch_to_block(block);
+    environ.regular_call_depth_restore(builder);
+    environ.tail_call_chain_reset(builder);
     let exn_ref = builder.func.dfg.block_params(block)[0];
     debug_assert!(exn_ref_ty.bits() <= exn_payload_ty.bits());
     let exn_ref = if exn_ref_ty.bits() < exn_payload_ty.bits() {
@@ -13,8 +13,10 @@ use cranelift_codegen::entity::EntityRef;
 use cranelift_codegen::ir::{self, Block, InstBuilder, ValueLabel};
 use cranelift_codegen::timing;
 use cranelift_frontend::{FunctionBuilder, FunctionBuilderContext};
-use wasmparser::{BinaryReader, FuncValidator, FunctionBody, OperatorsReader, WasmModuleResources};
-use wasmtime_environ::{TypeConvert, WasmResult};
+use wasmparser::{
+    BinaryReader, FuncValidator, FunctionBody, Operator, OperatorsReader, WasmModuleResources,
+};
+use wasmtime_environ::{OperatorReachability, TypeConvert, WasmResult};
 
 /// WebAssembly to Cranelift IR function translator.
 ///
@@ -88,8 +90,18 @@ impl FuncTranslator {
         builder.append_block_params_for_function_returns(exit_block);
         self.state.initialize(&builder.func.signature, exit_block);
 
-        parse_local_decls(&mut reader, &mut builder, num_params, environ, validator)?;
+        let mut local_inits = Vec::new();
+        parse_local_decls(
+            &mut reader,
+            &mut builder,
+            num_params,
+            &mut local_inits,
+            environ,
+            validator,
+        )?;
+        environ.begin_self_tail_loop(&mut builder, num_params, local_inits);
         parse_function_body(validator, reader, &mut builder, &mut self.state, environ)?;
+        environ.end_self_tail_loop(&mut builder);
 
         builder.finalize();
         log::trace!("translated Wasm to CLIF:\n{}", func.display());
@@ -135,11 +147,13 @@ fn declare_wasm_parameters(
 
 /// Parse the local variable declarations that precede the function body.
 ///
-/// Declare local variables, starting from `num_params`.
+/// Declare local variables, starting from `num_params`, and push their initial
+/// values onto `local_inits`.
 fn parse_local_decls(
     reader: &mut BinaryReader,
     builder: &mut FunctionBuilder,
     num_params: usize,
+    local_inits: &mut Vec<Option<ir::Value>>,
     environ: &mut FuncEnvironment<'_>,
     validator: &mut FuncValidator<impl WasmModuleResources>,
 ) -> WasmResult<()> {
@@ -152,13 +166,14 @@ fn parse_local_decls(
         let count = reader.read_var_u32()?;
         let ty = reader.read()?;
         validator.define_locals(pos, count, ty)?;
-        declare_locals(builder, count, ty, &mut next_local, environ)?;
+        declare_locals(builder, count, ty, &mut next_local, local_inits, environ)?;
     }
 
     Ok(())
 }
 
-/// Declare `count` local variables of the same type, starting from `next_local`.
+/// Declare `count` local variables of the same type, starting from `next_local`,
+/// and push their initial values onto `local_inits`.
 ///
 /// Fail if too many locals are declared in the function, or if the type is not valid for a local.
 fn declare_locals(
@@ -166,6 +181,7 @@ fn declare_locals(
     count: u32,
     wasm_type: wasmparser::ValType,
     next_local: &mut usize,
+    local_inits: &mut Vec<Option<ir::Value>>,
     environ: &mut FuncEnvironment<'_>,
 ) -> WasmResult<()> {
     // All locals are initialized to 0.
@@ -221,6 +237,7 @@ fn declare_locals(
             builder.def_var(local, init);
             builder.set_val_label(init, ValueLabel::new(*next_local));
         }
+        local_inits.push(init);
         *next_local += 1;
     }
     Ok(())
@@ -244,15 +261,24 @@ fn parse_function_body(
 
     let mut reader = OperatorsReader::new(reader);
     let mut operand_types = vec![];
+    let mut reachability = OperatorReachability::default();
 
     while !reader.eof() {
         let pos = reader.original_position();
         builder.set_srcloc(cur_srcloc(&reader.get_binary_reader()));
 
-        let op = reader.read()?;
+        let mut op = reader.read()?;
         let operand_types =
             validate_op_and_get_operand_types(validator, environ, &mut operand_types, &op, pos)?;
 
+        // Calls that the function's `CallClass` doesn't count because they're
+        // dead code, such as those in the arm of an `if` with a constant
+        // condition, are translated as traps so that the compiled function
+        // only makes the calls its classification describes.
+        if !reachability.visit(&op) && stack.reachable && is_call(&op) {
+            op = Operator::Unreachable;
+        }
+
         environ.before_translate_operator(&op, operand_types, builder, stack)?;
         translate_operator(validator, &op, operand_types, builder, stack, environ)?;
         environ.after_translate_operator(&op, operand_types, builder, stack)?;
@@ -280,6 +306,24 @@ fn parse_function_body(
     Ok(())
 }
 
+/// Whether `op` calls a function or otherwise transfers control to another
+/// frame, as counted by `wasmtime_environ::TailCallAnalysis`.
+fn is_call(op: &Operator<'_>) -> bool {
+    matches!(
+        op,
+        Operator::Call { .. }
+            | Operator::CallIndirect { .. }
+            | Operator::CallRef { .. }
+            | Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. }
+            | Operator::Resume { .. }
+            | Operator::ResumeThrow { .. }
+            | Operator::Suspend { .. }
+            | Operator::Switch { .. }
+    )
+}
+
 fn validate_op_and_get_operand_types<'a>(
     validator: &mut FuncValidator<impl WasmModuleResources>,
     environ: &mut FuncEnvironment<'_>,
@@ -20,7 +20,7 @@ pub struct InstructionAddressMap {
 /// This structure is used as a newtype wrapper around a 32-bit integer which
 /// represents an offset within a file where a wasm instruction or function is
 /// to be originally found.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct FilePos(u32);
 
 impl FilePos {
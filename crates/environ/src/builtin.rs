@@ -42,6 +42,10 @@ macro_rules! foreach_builtin_function {
             // Invoked when we reach a new epoch.
             #[cfg(target_has_atomic = "64")]
             new_epoch(vmctx: vmctx) -> u64;
+            // Invoked before a tail call when tail-call profiling is enabled.
+            tail_call_profile(vmctx: vmctx, func: u32);
+            // Invoked before a `return_call` when tail-call hooks are enabled.
+            tail_call_hook(vmctx: vmctx, from: u32, to: u32) -> bool;
             // Invoked before malloc returns.
             #[cfg(feature = "wmemcheck")]
             check_malloc(vmctx: vmctx, addr: u32, len: u32) -> bool;
@@ -3,8 +3,8 @@
 
 use crate::prelude::*;
 use crate::{
-    DefinedFuncIndex, FlagValue, FunctionLoc, ObjectKind, PrimaryMap, StaticModuleIndex, TripleExt,
-    WasmError, WasmFuncType,
+    CallConv, DefinedFuncIndex, FlagValue, FrameLayout, FunctionLoc, ObjectKind, PrimaryMap,
+    StaticModuleIndex, TailCallDeoptInfo, TripleExt, WasmError, WasmFuncType,
 };
 use crate::{Tunables, obj};
 use anyhow::Result;
@@ -173,6 +173,27 @@ pub struct CompiledFunctionBody {
     /// Whether the compiled function needs a GC heap to run; that is, whether
     /// it reads a struct field, allocates, an array, or etc...
     pub needs_gc_heap: bool,
+    /// Why this function's frame wasn't fully optimized despite it only making
+    /// tail calls, if applicable.
+    ///
+    /// This is only filled in once the function has finished compiling.
+    pub tail_call_deopt: Option<TailCallDeoptInfo>,
+    /// The number of stack bytes saved by optimizing this function's frame
+    /// because it only makes tail calls, or `None` if its frame wasn't
+    /// optimized.
+    ///
+    /// This is only filled in once the function has finished compiling.
+    pub tail_call_frame_savings: Option<u32>,
+    /// Whether this function's self-recursive tail calls were compiled as a
+    /// loop; see [`CallClass::SelfTailLoop`](crate::CallClass::SelfTailLoop).
+    pub self_tail_loop: bool,
+    /// The calling convention this function was compiled with, if it's a Wasm
+    /// function.
+    pub call_conv: Option<CallConv>,
+    /// The layout of this function's stack frame, if the compiler reports it.
+    ///
+    /// This is only filled in once the function has finished compiling.
+    pub frame_layout: Option<FrameLayout>,
 }
 
 /// An implementation of a compiler which can compile WebAssembly functions to
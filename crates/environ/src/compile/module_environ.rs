@@ -6,8 +6,9 @@ use crate::{
     ConstExpr, ConstOp, DataIndex, DefinedFuncIndex, ElemIndex, EngineOrModuleTypeIndex,
     EntityIndex, EntityType, FuncIndex, GlobalIndex, IndexType, InitMemory, MemoryIndex,
     ModuleInternedTypeIndex, ModuleTypesBuilder, PrimaryMap, SizeOverflow, StaticMemoryInitializer,
-    TableIndex, TableInitialValue, Tag, TagIndex, Tunables, TypeConvert, TypeIndex, WasmError,
-    WasmHeapTopType, WasmHeapType, WasmResult, WasmValType, WasmparserTypeConverter,
+    TableIndex, TableInitialValue, Tag, TagIndex, TailCallAnalysis, TailCallHint, Tunables,
+    TypeConvert, TypeIndex, WasmError, WasmHeapTopType, WasmHeapType, WasmResult, WasmValType,
+    WasmparserTypeConverter,
 };
 use crate::{StaticModuleIndex, prelude::*};
 use anyhow::{Result, bail};
@@ -77,6 +78,15 @@ pub struct ModuleTranslation<'data> {
     /// configuration.
     pub has_unparsed_debuginfo: bool,
 
+    /// Per-function hints from the module's `tail_call_hints` custom section,
+    /// if any; see [`TailCallHint`].
+    pub tail_call_hints: SecondaryMap<FuncIndex, TailCallHint>,
+
+    /// The [`TailCallAnalysis`] of each defined function's body, filled in
+    /// before the functions are compiled, or `None` if the body couldn't be
+    /// parsed.
+    pub tail_calls: SecondaryMap<DefinedFuncIndex, Option<TailCallAnalysis>>,
+
     /// List of data segments found in this module which should be concatenated
     /// together for the final compiled artifact.
     ///
@@ -123,6 +133,8 @@ impl<'data> ModuleTranslation<'data> {
             exported_signatures: Vec::default(),
             debuginfo: DebugInfoData::default(),
             has_unparsed_debuginfo: false,
+            tail_call_hints: SecondaryMap::default(),
+            tail_calls: SecondaryMap::default(),
             data: Vec::default(),
             data_align: None,
             total_data: 0,
@@ -726,6 +738,21 @@ and for re-adding support for interface types you can see this issue:
                     log::warn!("failed to parse name section {e:?}");
                 }
             }
+            _ if section.name() == TailCallHint::SECTION_NAME => {
+                match TailCallHint::parse_section(section.data(), section.data_offset()) {
+                    Ok(hints) => {
+                        for (func, hint) in hints {
+                            // Skip hints for functions that don't actually
+                            // exist, as with the name section.
+                            if self.result.module.functions.get(func).is_none() {
+                                continue;
+                            }
+                            self.result.tail_call_hints[func] = hint;
+                        }
+                    }
+                    Err(e) => log::warn!("failed to parse tail call hints section {e:?}"),
+                }
+            }
             _ => {
                 let name = section.name().trim_end_matches(".dwo");
                 if name.starts_with(".debug_") {
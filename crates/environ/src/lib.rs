@@ -34,6 +34,7 @@ mod ref_bits;
 mod scopevec;
 mod stack_map;
 mod stack_switching;
+mod tail_call;
 mod trap_encoding;
 mod tunables;
 mod types;
@@ -53,6 +54,7 @@ pub use crate::ref_bits::*;
 pub use crate::scopevec::ScopeVec;
 pub use crate::stack_map::*;
 pub use crate::stack_switching::*;
+pub use crate::tail_call::*;
 pub use crate::trap_encoding::*;
 pub use crate::tunables::*;
 pub use crate::types::*;
@@ -2,7 +2,10 @@
 //! with `bincode` as part of a module's compilation process.
 
 use crate::prelude::*;
-use crate::{DefinedFuncIndex, FilePos, FuncIndex, Module, ModuleInternedTypeIndex, PrimaryMap};
+use crate::{
+    CallClass, CallConv, CallEdge, DefinedFuncIndex, FilePos, FrameLayout, FuncIndex, Module,
+    ModuleInternedTypeIndex, PrimaryMap,
+};
 use core::fmt;
 use core::ops::Range;
 use core::str;
@@ -18,6 +21,40 @@ pub struct CompiledFunctionInfo {
     pub wasm_func_loc: FunctionLoc,
     /// A trampoline for array callers (e.g. `Func::new`) calling into this function (if needed).
     pub array_to_wasm_trampoline: Option<FunctionLoc>,
+    /// What's known about the calls this function makes and how its frame was
+    /// compiled, which is only recorded when the tail call proposal is
+    /// enabled.
+    pub tail_calls: Option<TailCallFunctionInfo>,
+}
+
+/// Metadata about the calls that a compiled function makes and about its
+/// frame, recorded for introspection of how its tail calls were compiled.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TailCallFunctionInfo {
+    /// The kinds of calls this function makes.
+    pub call_class: CallClass,
+    /// The number of stack bytes saved by optimizing this function's frame
+    /// because it only makes tail calls, or `None` if its frame wasn't
+    /// optimized.
+    pub tail_call_frame_savings: Option<u32>,
+    /// If this function is part of a clique of functions that only make tail
+    /// calls and that directly tail call each other, the lowest function index
+    /// in that clique. This is only reported; it doesn't change how the
+    /// function is compiled.
+    pub tail_call_clique: Option<FuncIndex>,
+    /// The calls this function makes.
+    pub call_edges: Vec<CallEdge>,
+    /// The function this function unconditionally tail calls, if it's
+    /// straight-line code ending in a `return_call`.
+    pub unconditional_tail_callee: Option<FuncIndex>,
+    /// The positions, in the original wasm file, of this function's tail
+    /// calls.
+    pub tail_call_sites: Vec<FilePos>,
+    /// The calling convention this function was compiled with.
+    pub call_conv: CallConv,
+    /// The layout of this function's stack frame, if the compiler reported
+    /// it.
+    pub frame_layout: Option<FrameLayout>,
 }
 
 /// Description of where a function is located in the text section of a
@@ -0,0 +1,989 @@
+//! Classification of Wasm functions by the kinds of calls they make.
+
+use crate::prelude::*;
+use crate::{FilePos, FuncIndex, TypeIndex, WasmError, WasmResult};
+use core::fmt;
+use serde_derive::{Deserialize, Serialize};
+use wasmparser::{FunctionBody, Operator};
+
+/// The kinds of calls that a defined Wasm function makes, as determined from
+/// its body at compile time.
+///
+/// Functions whose only calls are tail calls never return back into their own
+/// frame, which makes them candidates for frame optimizations that aren't
+/// otherwise possible.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum CallClass {
+    /// The function doesn't make any calls at all.
+    Leaf,
+    /// The function only makes tail calls, via any of `return_call`,
+    /// `return_call_indirect`, or `return_call_ref`.
+    ///
+    /// This includes tail calls within a `try_table` block: a tail call
+    /// removes the calling function's frame, along with its handlers, before
+    /// the callee runs, so exceptions thrown by the callee are only ever
+    /// caught by frames further up the stack.
+    TailCallOnly,
+    /// The function only makes regular, non-tail calls, via any of `call`,
+    /// `call_indirect`, or `call_ref`.
+    ///
+    /// The stack-switching instructions `resume`, `resume_throw`, `suspend`,
+    /// and `switch` are counted as regular calls too: control eventually
+    /// comes back to the function's frame after each of them, so its frame
+    /// must stay intact.
+    Regular,
+    /// The function makes both tail calls and regular calls.
+    Mixed,
+    /// The function's only calls are `return_call`s to itself, which were
+    /// compiled as a loop back to the start of the function rather than as
+    /// calls.
+    ///
+    /// This is only reported when self-recursive tail calls are compiled as
+    /// loops, and otherwise such functions are [`CallClass::TailCallOnly`].
+    SelfTailLoop,
+}
+
+impl CallClass {
+    /// Returns the classification for a function given whether it contains
+    /// any regular calls and whether it contains any tail calls.
+    pub fn new(has_regular_calls: bool, has_tail_calls: bool) -> CallClass {
+        match (has_regular_calls, has_tail_calls) {
+            (false, false) => CallClass::Leaf,
+            (false, true) => CallClass::TailCallOnly,
+            (true, false) => CallClass::Regular,
+            (true, true) => CallClass::Mixed,
+        }
+    }
+
+    /// Returns whether this function makes any tail calls.
+    ///
+    /// This includes [`CallClass::SelfTailLoop`] functions, even though their
+    /// tail calls are compiled as loops.
+    pub fn has_tail_calls(&self) -> bool {
+        matches!(
+            self,
+            CallClass::TailCallOnly | CallClass::Mixed | CallClass::SelfTailLoop
+        )
+    }
+
+    /// Returns whether this function only makes tail calls, either as actual
+    /// tail calls or compiled as a loop.
+    pub fn is_tail_call_only(&self) -> bool {
+        matches!(self, CallClass::TailCallOnly | CallClass::SelfTailLoop)
+    }
+
+    /// Returns whether this function makes any regular, non-tail calls.
+    pub fn has_regular_calls(&self) -> bool {
+        matches!(self, CallClass::Regular | CallClass::Mixed)
+    }
+}
+
+impl fmt::Display for CallClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CallClass::Leaf => "leaf",
+            CallClass::TailCallOnly => "tail-call-only",
+            CallClass::Regular => "regular",
+            CallClass::Mixed => "mixed",
+            CallClass::SelfTailLoop => "self-tail-loop",
+        };
+        f.write_str(s)
+    }
+}
+
+/// The calling convention, or ABI, that a defined Wasm function was compiled
+/// with.
+///
+/// Wasm functions normally use [`CallConv::Tail`], which is the only
+/// convention in which they can make tail calls, on every target. Only some
+/// targets support optimizing the frames of functions which only make tail
+/// calls in that convention, which is the reason reported as
+/// [`TailCallDeoptReason::UnsupportedCallConv`] otherwise.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum CallConv {
+    /// Cranelift's convention which is optimized for performance and isn't
+    /// ABI-stable.
+    Fast,
+    /// Cranelift's convention which is optimized for caller code size and
+    /// isn't ABI-stable.
+    Cold,
+    /// Cranelift's convention which supports tail calls. This is used for
+    /// Wasm functions compiled by Cranelift.
+    Tail,
+    /// The System V convention used on many platforms.
+    SystemV,
+    /// The Windows "fastcall" convention.
+    WindowsFastcall,
+    /// The aarch64 convention used on Apple platforms.
+    AppleAarch64,
+    /// Winch's convention, used for Wasm functions compiled by Winch.
+    Winch,
+}
+
+impl fmt::Display for CallConv {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            CallConv::Fast => "fast",
+            CallConv::Cold => "cold",
+            CallConv::Tail => "tail",
+            CallConv::SystemV => "system_v",
+            CallConv::WindowsFastcall => "windows_fastcall",
+            CallConv::AppleAarch64 => "apple_aarch64",
+            CallConv::Winch => "winch",
+        };
+        f.write_str(s)
+    }
+}
+
+/// A best-effort description of the layout of a compiled function's stack
+/// frame.
+///
+/// The areas are listed from high to low stack addresses, each including any
+/// padding the ABI requires. This is intended for debugging and tooling: the
+/// layout of frames is an implementation detail of the compiler and may change
+/// between versions.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Hash, Serialize, Deserialize)]
+pub struct FrameLayout {
+    /// The size, in bytes, of the setup area holding the return address and
+    /// the saved frame pointer, if the target keeps them on the stack.
+    pub setup_area_size: u32,
+    /// The size, in bytes, of the area where clobbered callee-saved registers
+    /// are saved.
+    pub callee_saves_size: u32,
+    /// The number of callee-saved registers saved in the frame.
+    pub callee_saved_registers: u32,
+    /// The size, in bytes, of the function's stack slots and spill slots.
+    pub stack_slots_size: u32,
+    /// The size, in bytes, of the area reserved for arguments to the
+    /// functions this function calls.
+    pub outgoing_args_size: u32,
+    /// Whether the return address slot is reused across tail calls, that is
+    /// whether every call this function makes is a tail call which passes its
+    /// own return address on to the callee instead of pushing a new one.
+    pub reuses_return_address: bool,
+    /// Whether the frame was optimized because the function only makes tail
+    /// calls; see [`CallClass::TailCallOnly`].
+    pub tail_call_optimized: bool,
+}
+
+impl FrameLayout {
+    /// Returns the total size, in bytes, of the frame.
+    pub fn total_size(&self) -> u32 {
+        self.setup_area_size
+            + self.callee_saves_size
+            + self.stack_slots_size
+            + self.outgoing_args_size
+    }
+}
+
+/// Whether a [`CallEdge`] is a tail call or a regular call.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Serialize, Deserialize)]
+pub enum CallKind {
+    /// A tail call, via any of `return_call`, `return_call_indirect`, or
+    /// `return_call_ref`.
+    Tail,
+    /// A regular call, via any of `call`, `call_indirect`, or `call_ref`.
+    Regular,
+}
+
+impl fmt::Display for CallKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallKind::Tail => f.write_str("tail"),
+            CallKind::Regular => f.write_str("regular"),
+        }
+    }
+}
+
+/// The callee of a [`CallEdge`].
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Serialize, Deserialize)]
+pub enum CallTarget {
+    /// A direct call, via `call` or `return_call`, to the function with this
+    /// index.
+    Direct(FuncIndex),
+    /// An indirect call, via `call_indirect`, `call_ref`, or their tail call
+    /// counterparts, to any function of the given type.
+    Indirect {
+        /// The type of the functions that may be called.
+        type_index: TypeIndex,
+    },
+}
+
+impl fmt::Display for CallTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallTarget::Direct(func) => write!(f, "func[{}]", func.as_u32()),
+            CallTarget::Indirect { type_index } => {
+                write!(f, "any func of type[{}]", type_index.as_u32())
+            }
+        }
+    }
+}
+
+/// An edge of a module's static call graph: a call made by a function defined
+/// in the module.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Hash, Serialize, Deserialize)]
+pub struct CallEdge {
+    /// The index of the calling function.
+    pub from: FuncIndex,
+    /// The function or functions that are called.
+    pub to: CallTarget,
+    /// Whether this is a tail call or a regular call.
+    pub kind: CallKind,
+}
+
+impl fmt::Display for CallEdge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "func[{}] -> {} ({} call)",
+            self.from.as_u32(),
+            self.to,
+            self.kind
+        )
+    }
+}
+
+/// What a defined Wasm function's body says about the calls it makes, gathered
+/// in a single pass over its operators.
+///
+/// Calls in code that can never run, as determined by
+/// [`OperatorReachability`], aren't counted, such as calls after a `br` or in
+/// the arm of an `if` whose condition is a constant, since they aren't
+/// compiled as calls either. This means that, for example, a function whose
+/// only regular call is in an `if (i32.const 0)` arm and which otherwise only
+/// makes tail calls is [`CallClass::TailCallOnly`] rather than
+/// [`CallClass::Mixed`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TailCallAnalysis {
+    /// The kinds of calls the function makes.
+    pub class: CallClass,
+    /// Whether the function makes at least one tail call and its only calls
+    /// are `return_call`s to itself, counting dead code too to keep the check
+    /// conservative.
+    ///
+    /// Such functions may have their tail calls compiled as a loop back to the
+    /// start of the function; see [`CallClass::SelfTailLoop`].
+    pub self_tail_recursive: bool,
+    /// The sorted, deduplicated calls the function makes. The
+    /// stack-switching instructions aren't included, since they don't call a
+    /// function.
+    pub call_edges: Vec<CallEdge>,
+    /// The function this function unconditionally tail calls, if it's
+    /// straight-line code ending in a `return_call`.
+    ///
+    /// This is a conservative check used to lint for tail call cycles that can
+    /// never exit: a function that branches, returns, throws, or makes any
+    /// other kind of call before its first `return_call` isn't considered to
+    /// unconditionally tail call anything, even if it actually does.
+    pub unconditional_tail_callee: Option<FuncIndex>,
+    /// The positions, in the original wasm file, of the function's tail
+    /// calls, in the order they appear.
+    pub tail_call_sites: Vec<FilePos>,
+}
+
+impl TailCallAnalysis {
+    /// Analyzes the Wasm function `body`, whose index is `func_index`.
+    ///
+    /// This is expected to be called on function bodies that have already
+    /// been validated.
+    pub fn new(body: &FunctionBody<'_>, func_index: FuncIndex) -> WasmResult<TailCallAnalysis> {
+        let mut has_regular_calls = false;
+        let mut has_tail_calls = false;
+        let mut call_edges = Vec::new();
+        let mut tail_call_sites = Vec::new();
+        let mut unconditional_tail_callee = None;
+        let mut straight_line = true;
+        let mut self_tail_calls = Some(false);
+        let mut reachability = OperatorReachability::default();
+        let mut reader = body.get_operators_reader()?;
+        while !reader.eof() {
+            let (op, offset) = reader.read_with_offset()?;
+
+            if straight_line {
+                match op {
+                    Operator::ReturnCall { function_index } => {
+                        unconditional_tail_callee = Some(FuncIndex::from_u32(function_index));
+                        straight_line = false;
+                    }
+                    Operator::If { .. }
+                    | Operator::Else
+                    | Operator::Br { .. }
+                    | Operator::BrIf { .. }
+                    | Operator::BrTable { .. }
+                    | Operator::BrOnNull { .. }
+                    | Operator::BrOnNonNull { .. }
+                    | Operator::BrOnCast { .. }
+                    | Operator::BrOnCastFail { .. }
+                    | Operator::Return
+                    | Operator::Unreachable
+                    | Operator::Try { .. }
+                    | Operator::TryTable { .. }
+                    | Operator::Throw { .. }
+                    | Operator::ThrowRef
+                    | Operator::Rethrow { .. } => straight_line = false,
+                    _ if is_call(&op) => straight_line = false,
+                    _ => {}
+                }
+            }
+            match op {
+                Operator::ReturnCall { function_index }
+                    if function_index == func_index.as_u32() =>
+                {
+                    if let Some(seen) = &mut self_tail_calls {
+                        *seen = true;
+                    }
+                }
+                _ if is_call(&op) => self_tail_calls = None,
+                _ => {}
+            }
+
+            if !reachability.visit(&op) {
+                continue;
+            }
+            let (to, kind) = match op {
+                Operator::Call { function_index } => (
+                    CallTarget::Direct(FuncIndex::from_u32(function_index)),
+                    CallKind::Regular,
+                ),
+                Operator::ReturnCall { function_index } => (
+                    CallTarget::Direct(FuncIndex::from_u32(function_index)),
+                    CallKind::Tail,
+                ),
+                Operator::CallIndirect { type_index, .. } | Operator::CallRef { type_index } => (
+                    CallTarget::Indirect {
+                        type_index: TypeIndex::from_u32(type_index),
+                    },
+                    CallKind::Regular,
+                ),
+                Operator::ReturnCallIndirect { type_index, .. }
+                | Operator::ReturnCallRef { type_index } => (
+                    CallTarget::Indirect {
+                        type_index: TypeIndex::from_u32(type_index),
+                    },
+                    CallKind::Tail,
+                ),
+                _ => {
+                    has_regular_calls |= is_call(&op);
+                    continue;
+                }
+            };
+            match kind {
+                CallKind::Tail => {
+                    has_tail_calls = true;
+                    tail_call_sites.push(FilePos::new(u32::try_from(offset).unwrap()));
+                }
+                CallKind::Regular => has_regular_calls = true,
+            }
+            call_edges.push(CallEdge {
+                from: func_index,
+                to,
+                kind,
+            });
+        }
+        call_edges.sort_unstable();
+        call_edges.dedup();
+        Ok(TailCallAnalysis {
+            class: CallClass::new(has_regular_calls, has_tail_calls),
+            self_tail_recursive: self_tail_calls == Some(true),
+            call_edges,
+            unconditional_tail_callee,
+            tail_call_sites,
+        })
+    }
+
+    /// Returns the sorted, deduplicated indices of the functions that this
+    /// function directly tail calls via `return_call`.
+    ///
+    /// Indirect tail calls (`return_call_indirect` and `return_call_ref`) are
+    /// not included, since their callees aren't statically known.
+    pub fn direct_tail_callees(&self) -> impl Iterator<Item = FuncIndex> + '_ {
+        self.call_edges.iter().filter_map(|edge| match edge {
+            CallEdge {
+                to: CallTarget::Direct(callee),
+                kind: CallKind::Tail,
+                ..
+            } => Some(*callee),
+            _ => None,
+        })
+    }
+
+    /// Returns the position, in the original wasm file, of the function's
+    /// first tail call, if it makes any.
+    pub fn first_tail_call(&self) -> Option<FilePos> {
+        self.tail_call_sites.first().copied()
+    }
+}
+
+/// Returns whether `op` is any kind of call, including tail calls and the
+/// stack-switching instructions.
+fn is_call(op: &Operator<'_>) -> bool {
+    matches!(
+        op,
+        Operator::Call { .. }
+            | Operator::CallIndirect { .. }
+            | Operator::CallRef { .. }
+            | Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. }
+            | Operator::Resume { .. }
+            | Operator::ResumeThrow { .. }
+            | Operator::Suspend { .. }
+            | Operator::Switch { .. }
+    )
+}
+
+/// Tracks whether each operator of a function body is reachable.
+///
+/// This is the single rule for which calls in a function are dead code: calls
+/// that it considers unreachable aren't counted by [`TailCallAnalysis`] and
+/// the compiler translates them as traps rather than as calls, so that the
+/// calls a compiled function makes are exactly those its classification
+/// describes.
+///
+/// Code is dead after an operator that never falls through, such as `br` or
+/// `return`, until the end of the enclosing block, and so is the arm of an
+/// `if` whose condition is an immediately preceding `i32.const`. Code after a
+/// block's `end` is conservatively considered reachable whenever the block
+/// itself was, even if it's only exited by branching past it.
+#[derive(Default)]
+pub struct OperatorReachability {
+    /// For each enclosing block, whether its start is reachable and, for
+    /// `if` blocks, whether its `else` arm is.
+    frames: Vec<(bool, bool)>,
+    /// Whether the current operator is dead code.
+    unreachable: bool,
+    /// The value of the `i32.const` that was the previous operator, if it
+    /// was one.
+    constant: Option<bool>,
+}
+
+impl OperatorReachability {
+    /// Updates the state for the next operator, `op`, and returns whether
+    /// `op` is reachable.
+    ///
+    /// This must be called on every operator of a function body, in order.
+    pub fn visit(&mut self, op: &Operator<'_>) -> bool {
+        let reachable = !self.unreachable;
+        let constant = self.constant.take();
+        match *op {
+            Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::TryTable { .. }
+            | Operator::Try { .. } => self.frames.push((reachable, false)),
+            Operator::If { .. } => {
+                self.frames
+                    .push((reachable, reachable && constant != Some(true)));
+                self.unreachable = !reachable || constant == Some(false);
+            }
+            Operator::Else => {
+                if let Some(&(_, else_reachable)) = self.frames.last() {
+                    self.unreachable = !else_reachable;
+                }
+            }
+            Operator::Catch { .. } | Operator::CatchAll => {
+                if let Some(&(head_reachable, _)) = self.frames.last() {
+                    self.unreachable = !head_reachable;
+                }
+            }
+            Operator::End | Operator::Delegate { .. } => {
+                if let Some((head_reachable, _)) = self.frames.pop() {
+                    self.unreachable = !head_reachable;
+                }
+            }
+            Operator::Unreachable
+            | Operator::Br { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return
+            | Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. }
+            | Operator::Throw { .. }
+            | Operator::ThrowRef
+            | Operator::Rethrow { .. } => self.unreachable = true,
+            Operator::I32Const { value } if reachable => self.constant = Some(value != 0),
+            _ => {}
+        }
+        reachable
+    }
+}
+
+/// The reason that a function which only makes tail calls didn't have its
+/// frame fully optimized.
+///
+/// See [`TailCallDeoptInfo`] for more information.
+#[non_exhaustive]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub enum TailCallDeoptReason {
+    /// The compiler doesn't support the optimization for the function's calling
+    /// convention on this target.
+    UnsupportedCallConv,
+    /// The function needs more stack space for arguments to the functions it
+    /// calls than `TailCallOptions::max_stack_args_bytes` allows.
+    StackArgsPresent,
+    /// Optimizing the function's frame would save less stack than the minimum
+    /// configured with `TailCallOptions::min_frame_savings`.
+    BelowSavingsThreshold,
+    /// The function's body in the Wasm binary is larger than the maximum
+    /// configured with `TailCallOptions::max_optimized_body_bytes`.
+    FunctionTooLarge,
+}
+
+impl fmt::Display for TailCallDeoptReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            TailCallDeoptReason::UnsupportedCallConv => {
+                "the optimization isn't supported for this target's calling convention"
+            }
+            TailCallDeoptReason::StackArgsPresent => "it needs stack space for outgoing arguments",
+            TailCallDeoptReason::BelowSavingsThreshold => {
+                "it would save less stack than the configured minimum"
+            }
+            TailCallDeoptReason::FunctionTooLarge => {
+                "its WebAssembly body is larger than the configured maximum"
+            }
+        };
+        f.write_str(s)
+    }
+}
+
+/// Information about a Wasm function which only makes tail calls (see
+/// [`CallClass::TailCallOnly`]) but whose frame couldn't be fully optimized
+/// during compilation.
+///
+/// This is reported by a [`Compiler`](crate::Compiler) for each such function,
+/// and the location of its first tail call is filled in afterwards.
+#[derive(Clone, PartialEq, Eq, Debug, Hash, Serialize, Deserialize)]
+pub struct TailCallDeoptInfo {
+    /// The index of the function within its module.
+    pub func_index: FuncIndex,
+    /// Why the optimization didn't apply.
+    pub reason: TailCallDeoptReason,
+    /// The size, in bytes, of stack space needed for outgoing arguments.
+    pub outgoing_args_size: u32,
+    /// The size, in bytes, of the function's stack slots and spill slots.
+    pub stackslots_size: u32,
+    /// The position, in the original wasm file, of the function's first tail
+    /// call instruction.
+    pub tail_call_offset: Option<FilePos>,
+    /// The source file, line, and column of the function's first tail call,
+    /// if the module has DWARF debug information describing it.
+    pub source_loc: Option<(String, u32, u32)>,
+}
+
+impl TailCallDeoptInfo {
+    /// Creates the information for the function `func_index`, without the
+    /// location of its first tail call.
+    pub fn new(
+        func_index: FuncIndex,
+        reason: TailCallDeoptReason,
+        outgoing_args_size: u32,
+        stackslots_size: u32,
+    ) -> TailCallDeoptInfo {
+        TailCallDeoptInfo {
+            func_index,
+            reason,
+            outgoing_args_size,
+            stackslots_size,
+            tail_call_offset: None,
+            source_loc: None,
+        }
+    }
+}
+
+impl fmt::Display for TailCallDeoptInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "function {}", self.func_index.as_u32())?;
+        if let Some((file, line, column)) = &self.source_loc {
+            write!(f, " at {file}:{line}:{column}")?;
+        }
+        write!(
+            f,
+            " only makes tail calls but its frame couldn't be optimized because {} ({} bytes \
+             of outgoing arguments, {} bytes of stack slots)",
+            self.reason, self.outgoing_args_size, self.stackslots_size,
+        )
+    }
+}
+
+/// A per-function request, read from a module's `tail_call_hints` custom
+/// section, for whether the function's frame should be optimized when it only
+/// makes tail calls.
+///
+/// The `tail_call_hints` custom section has the following binary format,
+/// where `u32` is LEB128-encoded as elsewhere in Wasm:
+///
+/// ```text
+/// tail_call_hints ::= n:u32 (func:u32 hint:byte)^n
+/// hint            ::= 0x00 => auto
+///                   | 0x01 => force
+///                   | 0x02 => forbid
+/// ```
+///
+/// `func` is an index into the module's function index space. Hints for
+/// imported functions, and for indices that don't name a function, are
+/// ignored, as is the whole section if it's malformed or if it comes before
+/// the module's function section. A later entry for the same function replaces
+/// an earlier one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
+pub enum TailCallHint {
+    /// Follow the engine's configuration, which is the default for functions
+    /// without a hint.
+    #[default]
+    Auto,
+    /// Optimize the function's frame even if the engine otherwise wouldn't,
+    /// and fail compilation if it can't be, as if the engine required
+    /// optimization for this function alone.
+    Force,
+    /// Never optimize the function's frame, leaving it with a standard stack
+    /// limit check.
+    Forbid,
+}
+
+impl TailCallHint {
+    /// The name of the custom section that hints are read from.
+    pub const SECTION_NAME: &'static str = "tail_call_hints";
+
+    /// Parses the contents of a `tail_call_hints` custom section, where
+    /// `offset` is the position of `data` within the original wasm file.
+    pub fn parse_section(data: &[u8], offset: usize) -> WasmResult<Vec<(FuncIndex, TailCallHint)>> {
+        let mut reader = wasmparser::BinaryReader::new(data, offset);
+        let count = reader.read_var_u32()?;
+        let mut hints = Vec::new();
+        for _ in 0..count {
+            let func = FuncIndex::from_u32(reader.read_var_u32()?);
+            let pos = reader.original_position();
+            let hint = match reader.read_u8()? {
+                0x00 => TailCallHint::Auto,
+                0x01 => TailCallHint::Force,
+                0x02 => TailCallHint::Forbid,
+                byte => {
+                    return Err(WasmError::InvalidWebAssembly {
+                        message: format!("invalid tail call hint 0x{byte:02x}"),
+                        offset: pos,
+                    });
+                }
+            };
+            hints.push((func, hint));
+        }
+        if !reader.eof() {
+            return Err(WasmError::InvalidWebAssembly {
+                message: "trailing bytes in tail call hints section".to_string(),
+                offset: reader.original_position(),
+            });
+        }
+        Ok(hints)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmparser::{Parser, Payload};
+
+    fn analyze(wat: &str) -> Vec<TailCallAnalysis> {
+        let wasm = wat::parse_str(wat).unwrap();
+        let mut analyses = Vec::new();
+        for payload in Parser::new(0).parse_all(&wasm) {
+            if let Payload::CodeSectionEntry(body) = payload.unwrap() {
+                let index = FuncIndex::from_u32(u32::try_from(analyses.len()).unwrap());
+                analyses.push(TailCallAnalysis::new(&body, index).unwrap());
+            }
+        }
+        analyses
+    }
+
+    fn classify(wat: &str) -> Vec<CallClass> {
+        analyze(wat).into_iter().map(|a| a.class).collect()
+    }
+
+    #[test]
+    fn stack_switching_instructions_are_regular_calls() {
+        let classes = classify(
+            r#"
+                (module
+                    (type $ft (func))
+                    (type $ct (cont $ft))
+                    (tag $yield)
+                    (func $body
+                        suspend $yield
+                        return_call $body)
+                    (func $driver (param (ref $ct))
+                        local.get 0
+                        resume $ct
+                        return_call $body)
+                    (func $resume_only (param (ref $ct))
+                        local.get 0
+                        resume $ct)
+                    (func $tail
+                        return_call $tail)
+                )
+            "#,
+        );
+        assert_eq!(
+            classes,
+            [
+                CallClass::Mixed,
+                CallClass::Mixed,
+                CallClass::Regular,
+                CallClass::TailCallOnly,
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_calls_are_not_counted() {
+        let classes = classify(
+            r#"
+                (module
+                    (func $f
+                        i32.const 0
+                        if
+                            call $f
+                        end
+                        return_call $f)
+                    (func $g
+                        i32.const 7
+                        if
+                            return_call $f
+                        else
+                            call $f
+                        end)
+                    (func $h
+                        return
+                        call $f)
+                    (func $i (param i32)
+                        local.get 0
+                        if
+                            call $f
+                        end
+                        return_call $f)
+                )
+            "#,
+        );
+        assert_eq!(
+            classes,
+            [
+                CallClass::TailCallOnly,
+                CallClass::TailCallOnly,
+                CallClass::Leaf,
+                CallClass::Mixed,
+            ]
+        );
+    }
+
+    #[test]
+    fn br_table_arms_ending_in_tail_calls() {
+        let classes = classify(
+            r#"
+                (module
+                    (func $a)
+                    (func $b)
+                    (func $c)
+                    (func $dispatch (param i32)
+                        block $default
+                            block $arm1
+                                block $arm0
+                                    local.get 0
+                                    br_table $arm0 $arm1 $default
+                                end
+                                return_call $a
+                            end
+                            return_call $b
+                        end
+                        return_call $c)
+                )
+            "#,
+        );
+        assert_eq!(classes[3], CallClass::TailCallOnly);
+    }
+
+    #[test]
+    fn parse_tail_call_hints() {
+        let hints = TailCallHint::parse_section(&[3, 0, 1, 0x80, 0x01, 2, 5, 0], 0).unwrap();
+        assert_eq!(
+            hints,
+            [
+                (FuncIndex::from_u32(0), TailCallHint::Force),
+                (FuncIndex::from_u32(128), TailCallHint::Forbid),
+                (FuncIndex::from_u32(5), TailCallHint::Auto),
+            ]
+        );
+
+        assert!(TailCallHint::parse_section(&[1, 0, 3], 0).is_err());
+        assert!(TailCallHint::parse_section(&[1, 0], 0).is_err());
+        assert!(TailCallHint::parse_section(&[0, 0], 0).is_err());
+    }
+
+    #[test]
+    fn unconditional_tail_callee() {
+        let analyses = analyze(
+            r#"
+                (module
+                    (func $spin (param i32)
+                        local.get 0
+                        i32.const 1
+                        i32.add
+                        return_call $spin)
+                    (func $countdown (param i32)
+                        local.get 0
+                        i32.eqz
+                        if
+                            return
+                        end
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $countdown)
+                    (func $block (param i32)
+                        block
+                            local.get 0
+                            drop
+                        end
+                        local.get 0
+                        return_call $spin)
+                    (func $calls (param i32)
+                        local.get 0
+                        call $countdown
+                        local.get 0
+                        return_call $spin)
+                    (func $leaf (param i32))
+                )
+            "#,
+        );
+        let callees = analyses
+            .iter()
+            .map(|a| a.unconditional_tail_callee)
+            .collect::<Vec<_>>();
+        let spin = Some(FuncIndex::from_u32(0));
+        assert_eq!(callees, [spin, None, spin, None, None]);
+    }
+
+    #[test]
+    fn self_tail_recursive() {
+        let analyses = analyze(
+            r#"
+                (module
+                    (func $loop (param i32)
+                        local.get 0
+                        if
+                            local.get 0
+                            i32.const 1
+                            i32.sub
+                            return_call $loop
+                        end)
+                    (func $dead_call (param i32)
+                        local.get 0
+                        return_call $dead_call
+                        call $loop)
+                    (func $other (param i32)
+                        local.get 0
+                        return_call $loop)
+                    (func $leaf (param i32))
+                )
+            "#,
+        );
+        let recursive = analyses
+            .iter()
+            .map(|a| a.self_tail_recursive)
+            .collect::<Vec<_>>();
+        assert_eq!(recursive, [true, false, false, false]);
+        assert_eq!(analyses[1].class, CallClass::TailCallOnly);
+    }
+
+    #[test]
+    fn call_edges() {
+        let analyses = analyze(
+            r#"
+                (module
+                    (type $t (func (param i32) (result i32)))
+                    (table 1 funcref)
+                    (func $a (type $t)
+                        local.get 0
+                        call $a
+                        call $a
+                        local.get 0
+                        i32.const 0
+                        call_indirect (type $t)
+                        drop
+                        i32.const 0
+                        if
+                            i32.const 0
+                            call $a
+                            drop
+                        end
+                        i32.const 0
+                        return_call_indirect (type $t))
+                    (func $b (type $t)
+                        local.get 0
+                        return_call $a)
+                )
+            "#,
+        );
+        let edges = analyses
+            .iter()
+            .flat_map(|a| a.call_edges.iter().copied())
+            .collect::<Vec<_>>();
+        let a = FuncIndex::from_u32(0);
+        let b = FuncIndex::from_u32(1);
+        let t = TypeIndex::from_u32(0);
+        assert_eq!(
+            edges,
+            [
+                CallEdge {
+                    from: a,
+                    to: CallTarget::Direct(a),
+                    kind: CallKind::Regular,
+                },
+                CallEdge {
+                    from: a,
+                    to: CallTarget::Indirect { type_index: t },
+                    kind: CallKind::Tail,
+                },
+                CallEdge {
+                    from: a,
+                    to: CallTarget::Indirect { type_index: t },
+                    kind: CallKind::Regular,
+                },
+                CallEdge {
+                    from: b,
+                    to: CallTarget::Direct(a),
+                    kind: CallKind::Tail,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(CallClass::TailCallOnly.to_string(), "tail-call-only");
+        let edge = CallEdge {
+            from: FuncIndex::from_u32(1),
+            to: CallTarget::Indirect {
+                type_index: TypeIndex::from_u32(2),
+            },
+            kind: CallKind::Tail,
+        };
+        assert_eq!(
+            edge.to_string(),
+            "func[1] -> any func of type[2] (tail call)"
+        );
+
+        let mut info = TailCallDeoptInfo::new(
+            FuncIndex::from_u32(3),
+            TailCallDeoptReason::StackArgsPresent,
+            32,
+            16,
+        );
+        info.source_loc = Some(("a.c".to_string(), 4, 5));
+        assert_eq!(
+            info.to_string(),
+            "function 3 at a.c:4:5 only makes tail calls but its frame couldn't be optimized \
+             because it needs stack space for outgoing arguments (32 bytes of outgoing \
+             arguments, 16 bytes of stack slots)"
+        );
+    }
+}
@@ -107,6 +107,22 @@ pub enum Trap {
     /// that all host tasks have completed and any/all host-owned stream/future
     /// handles have been dropped.
     AsyncDeadlock,
+
+    /// All of the tail-call fuel configured with `TailCallOptions::fuel` was
+    /// consumed.
+    TailCallFuelExhausted,
+
+    /// The limit on the depth of nested regular, non-tail calls configured
+    /// with `TailCallOptions::stack_limit` was exceeded.
+    RegularCallDepthExceeded,
+
+    /// The frame reused by a tail call was found to be corrupted by the checks
+    /// enabled with `TailCallOptions::debug_assertions`.
+    TailCallFrameCorruption,
+
+    /// More consecutive tail calls were made than permitted by
+    /// `TailCallOptions::max_chain_length`.
+    TailCallChainTooLong,
     // if adding a variant here be sure to update the `check!` macro below
 }
 
@@ -148,6 +164,10 @@ impl Trap {
             ContinuationAlreadyConsumed
             DisabledOpcode
             AsyncDeadlock
+            TailCallFuelExhausted
+            RegularCallDepthExceeded
+            TailCallFrameCorruption
+            TailCallChainTooLong
         }
 
         None
@@ -183,6 +203,10 @@ impl fmt::Display for Trap {
             ContinuationAlreadyConsumed => "continuation already consumed",
             DisabledOpcode => "pulley opcode disabled at compile time was executed",
             AsyncDeadlock => "deadlock detected: event loop cannot make further progress",
+            TailCallFuelExhausted => "all tail-call fuel consumed by WebAssembly",
+            RegularCallDepthExceeded => "regular call depth limit exceeded",
+            TailCallFrameCorruption => "tail call frame corruption detected",
+            TailCallChainTooLong => "tail call chain length limit exceeded",
         };
         write!(f, "wasm trap: {desc}")
     }
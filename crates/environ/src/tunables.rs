@@ -136,6 +136,72 @@ define_tunables! {
         /// The general size threshold for the sum of the caller's and callee's
         /// sizes, past which we will generally not inline calls anymore.
         pub inlining_sum_size_threshold: u32,
+
+        /// Whether to inline small callees which don't make any regular calls
+        /// (see `CallClass`) even when the intra-module inlining heuristic
+        /// would otherwise decline to.
+        pub inlining_tail_call_only: bool,
+
+        /// Whether functions which only make tail calls may have their stack
+        /// limit checks relaxed like those of leaf functions.
+        pub tail_call_relaxed_stack_check: bool,
+
+        /// The size, in bytes, of outgoing stack arguments which functions
+        /// that only make tail calls may need while still having their frames
+        /// considered optimized.
+        pub tail_call_max_stack_args_bytes: u32,
+
+        /// The number of bytes of stack, at least, that optimizing the frame of
+        /// a function which only makes tail calls must save for it to be
+        /// optimized.
+        pub tail_call_min_frame_savings: u32,
+
+        /// The size, in bytes, of the largest Wasm function body whose frame
+        /// is optimized when the function only makes tail calls, if limited.
+        pub tail_call_max_optimized_body_bytes: Option<u32>,
+
+        /// Whether compilation fails if a function which only makes tail calls
+        /// can't have its frame optimized.
+        pub tail_call_require_optimization: bool,
+
+        /// Whether functions whose only calls are tail calls to themselves
+        /// compile those calls as a loop back to the function's start.
+        pub tail_call_self_recursion_loop: bool,
+
+        /// Whether to count the tail calls made by each function at runtime.
+        pub tail_call_profiling: bool,
+
+        /// Whether to call into the host before each `return_call` so that a
+        /// store's tail-call hook can observe it.
+        pub tail_call_hooks: bool,
+
+        /// Whether tail calls consume "tail-call fuel" from the store, trapping
+        /// once it has all been consumed.
+        pub tail_call_fuel: bool,
+
+        /// Whether the depth of nested regular, non-tail calls is limited,
+        /// trapping when the store's limit is exceeded.
+        pub tail_call_stack_limit: bool,
+
+        /// Whether the frame a function reuses for its tail calls is checked
+        /// at runtime for corruption before each tail call.
+        pub tail_call_debug_assertions: bool,
+
+        /// The number of consecutive tail calls, without an intervening
+        /// regular call, after which the next tail call traps, if limited.
+        pub max_tail_call_chain_length: Option<u64>,
+
+        /// The amount of fuel consumed by each tail call when fuel is
+        /// enabled.
+        pub tail_call_fuel_cost: u64,
+
+        /// Whether calls to host functions record whether they were made as
+        /// tail calls, for `Caller::is_tail_called`.
+        pub tail_call_host_tracking: bool,
+
+        /// Whether the frame torn down by each tail call is zeroed before
+        /// jumping to the callee.
+        pub tail_call_zero_unused_frame: bool,
     }
 
     pub struct ConfigTunables {
@@ -210,6 +276,22 @@ impl Tunables {
             inlining_intra_module: IntraModuleInlining::WhenUsingGc,
             inlining_small_callee_size: 50,
             inlining_sum_size_threshold: 2000,
+            inlining_tail_call_only: false,
+            tail_call_relaxed_stack_check: true,
+            tail_call_max_stack_args_bytes: 0,
+            tail_call_min_frame_savings: 0,
+            tail_call_max_optimized_body_bytes: None,
+            tail_call_require_optimization: false,
+            tail_call_self_recursion_loop: false,
+            tail_call_profiling: false,
+            tail_call_hooks: false,
+            tail_call_fuel: false,
+            tail_call_stack_limit: false,
+            tail_call_debug_assertions: false,
+            max_tail_call_chain_length: None,
+            tail_call_fuel_cost: 1,
+            tail_call_host_tracking: false,
+            tail_call_zero_unused_frame: false,
         }
     }
 
@@ -222,6 +222,35 @@ pub trait PtrSize {
         self.vmstore_context_last_wasm_entry_fp() + self.size()
     }
 
+    /// Return the offset of the `tail_call_fuel` field of `VMStoreContext`.
+    fn vmstore_context_tail_call_fuel(&self) -> u8 {
+        self.vmstore_context_stack_chain() + self.size_of_vmstack_chain()
+    }
+
+    /// Return the offset of the `regular_call_depth_remaining` field of
+    /// `VMStoreContext`.
+    fn vmstore_context_regular_call_depth_remaining(&self) -> u8 {
+        self.vmstore_context_tail_call_fuel() + self.size()
+    }
+
+    /// Return the offset of the `tail_call_chain_length` field of
+    /// `VMStoreContext`.
+    fn vmstore_context_tail_call_chain_length(&self) -> u8 {
+        self.vmstore_context_regular_call_depth_remaining() + self.size()
+    }
+
+    /// Return the offset of the `host_call_is_tail` field of
+    /// `VMStoreContext`.
+    fn vmstore_context_host_call_is_tail(&self) -> u8 {
+        self.vmstore_context_tail_call_chain_length() + self.size()
+    }
+
+    /// Return the offset of the `tail_call_hook_active` field of
+    /// `VMStoreContext`.
+    fn vmstore_context_tail_call_hook_active(&self) -> u8 {
+        self.vmstore_context_host_call_is_tail() + self.size()
+    }
+
     // Offsets within `VMMemoryDefinition`
 
     /// The offset of the `base` field.
@@ -18,6 +18,7 @@ pub mod dummy;
 pub mod engine;
 pub mod memory;
 mod stacks;
+mod tail_calls;
 
 use self::diff_wasmtime::WasmtimeInstance;
 use self::engine::{DiffEngine, DiffInstance};
@@ -31,6 +32,7 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst};
 use std::sync::{Arc, Condvar, Mutex};
 use std::task::{Context, Poll, Waker};
 use std::time::{Duration, Instant};
+pub use tail_calls::check_tail_calls;
 use wasmtime::*;
 use wasmtime_wast::WastContext;
 
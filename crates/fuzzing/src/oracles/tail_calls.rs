@@ -0,0 +1,164 @@
+use crate::generators::ModuleConfig;
+use arbitrary::{Result, Unstructured};
+use wasmparser::{FunctionBody, Operator, Parser, Payload};
+use wasmtime::*;
+
+/// Generate a module which may make tail calls and check that Wasmtime
+/// classifies its functions consistently with their bodies, and that each of
+/// its exported functions behaves the same whether or not the frames of
+/// functions which only make tail calls are optimized.
+pub fn check_tail_calls(u: &mut Unstructured<'_>) -> Result<()> {
+    let mut module_config: ModuleConfig = u.arbitrary()?;
+    let config = &mut module_config.config;
+    config.tail_call_enabled = true;
+    config.min_funcs = config.min_funcs.max(1);
+    config.max_funcs = config.max_funcs.max(1);
+    config.max_imports = 0;
+    config.export_everything = true;
+    config.canonicalize_nans = true;
+    config.allow_start_export = false;
+
+    // Keep to proposals which are enabled by default in `Config` so that the
+    // same configuration compiles everything which is generated.
+    config.gc_enabled = false;
+    config.exceptions_enabled = false;
+    config.threads_enabled = false;
+    config.memory64_enabled = false;
+    config.custom_page_sizes_enabled = false;
+    config.wide_arithmetic_enabled = false;
+    config.relaxed_simd_enabled = false;
+    config.max_memories = config.max_memories.min(1);
+    module_config.function_references_enabled = false;
+
+    let module = module_config.generate(u, Some(1000))?;
+    let wasm = module.to_bytes();
+    crate::oracles::log_wasm(&wasm);
+
+    let mut config = Config::new();
+    config
+        .wasm_tail_call(true)
+        .wasm_simd(module_config.config.simd_enabled);
+    let engine = Engine::new(&config).unwrap();
+    let compiled = Module::new(&engine, &wasm).expect("should compile okay");
+    check_classification(&compiled, &wasm);
+
+    // Skip modules which can't be instantiated, for example because an
+    // element segment is out of bounds, since neither run gets as far as
+    // calling anything.
+    let mut store = Store::new(&engine, ());
+    if Instance::new(&mut store, &compiled, &[]).is_err() {
+        return Ok(());
+    }
+
+    // Each check compiles the module twice, so only check a few of the
+    // exported functions.
+    let funcs = compiled
+        .exports()
+        .filter_map(|export| Some((export.name().to_string(), export.ty().func()?.clone())))
+        .collect::<Vec<_>>();
+    for (name, ty) in funcs.iter().take(3) {
+        let Some(inputs) = ty
+            .params()
+            .map(|ty| arbitrary_val(u, &ty))
+            .collect::<Result<Option<Vec<_>>>>()?
+        else {
+            continue;
+        };
+        log::debug!("checking `{name}` with {inputs:?}");
+        if let Err(e) = Module::differential_tail_call_check(&config, &wasm, name, &inputs) {
+            panic!("{e:?}");
+        }
+    }
+    Ok(())
+}
+
+/// Checks that no function whose body certainly makes a regular call is
+/// classified as only making tail calls, and that no function whose body
+/// certainly makes a tail call is classified as making none.
+fn check_classification(module: &Module, wasm: &[u8]) {
+    let bodies = Parser::new(0)
+        .parse_all(wasm)
+        .filter_map(|payload| match payload.unwrap() {
+            Payload::CodeSectionEntry(body) => Some(body),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let classes = module.function_call_classes();
+    assert_eq!(classes.len(), bodies.len());
+
+    for ((index, class), body) in classes.iter().zip(&bodies) {
+        let index = index.as_u32();
+        let (regular, tail) = entry_calls(body);
+        log::debug!("function {index}: {class}, entry calls: regular={regular} tail={tail}");
+        if regular {
+            assert!(
+                class.has_regular_calls(),
+                "function {index} makes a regular call but is classified as {class}"
+            );
+        }
+        if tail {
+            assert!(
+                class.has_tail_calls(),
+                "function {index} makes a tail call but is classified as {class}"
+            );
+        }
+    }
+}
+
+/// Returns whether the straight-line code at the start of `body`, which runs
+/// every time the function is called, makes a regular call and whether it
+/// makes a tail call.
+///
+/// This stops at the first instruction which may branch, because whether the
+/// code after it is live can depend on analyses which this deliberately
+/// doesn't replicate, such as folding constant conditions. Calls found before
+/// that point are live however the function is analyzed.
+fn entry_calls(body: &FunctionBody<'_>) -> (bool, bool) {
+    let mut regular = false;
+    let mut reader = body.get_operators_reader().unwrap();
+    while !reader.eof() {
+        match reader.read().unwrap() {
+            Operator::Call { .. } | Operator::CallIndirect { .. } | Operator::CallRef { .. } => {
+                regular = true;
+            }
+            Operator::ReturnCall { .. }
+            | Operator::ReturnCallIndirect { .. }
+            | Operator::ReturnCallRef { .. } => return (regular, true),
+
+            // Entering or leaving a `block` or `loop` doesn't branch.
+            Operator::Block { .. } | Operator::Loop { .. } | Operator::End => {}
+
+            Operator::If { .. }
+            | Operator::Else
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::BrOnNull { .. }
+            | Operator::BrOnNonNull { .. }
+            | Operator::BrOnCast { .. }
+            | Operator::BrOnCastFail { .. }
+            | Operator::Return
+            | Operator::Unreachable
+            | Operator::Throw { .. }
+            | Operator::ThrowRef
+            | Operator::TryTable { .. } => break,
+
+            _ => {}
+        }
+    }
+    (regular, false)
+}
+
+/// Generates an arbitrary value of type `ty`, or `None` if it's a reference
+/// type, which `Module::differential_tail_call_check` only supports as null,
+/// without a null value.
+fn arbitrary_val(u: &mut Unstructured<'_>, ty: &ValType) -> Result<Option<Val>> {
+    Ok(Some(match ty {
+        ValType::I32 => Val::I32(u.arbitrary()?),
+        ValType::I64 => Val::I64(u.arbitrary()?),
+        ValType::F32 => Val::F32(u.arbitrary()?),
+        ValType::F64 => Val::F64(u.arbitrary()?),
+        ValType::V128 => Val::V128(u.arbitrary::<u128>()?.into()),
+        ValType::Ref(_) => return Ok(Val::default_for_ty(ty)),
+    }))
+}
@@ -35,11 +35,14 @@ use std::{
 };
 
 use call_graph::CallGraph;
+use wasmparser::WasmFeatures;
 use wasmtime_environ::{
-    BuiltinFunctionIndex, CompiledFunctionBody, CompiledFunctionInfo, CompiledModuleInfo, Compiler,
-    DefinedFuncIndex, FilePos, FinishedObject, FuncKey, FunctionBodyData, InliningCompiler,
+    BuiltinFunctionIndex, CallClass, CallConv, CompileError, CompiledFunctionBody,
+    CompiledFunctionInfo, CompiledModuleInfo, Compiler, DefinedFuncIndex, EngineOrModuleTypeIndex,
+    FilePos, FinishedObject, FrameLayout, FuncIndex, FuncKey, FunctionBodyData, InliningCompiler,
     IntraModuleInlining, ModuleEnvironment, ModuleTranslation, ModuleTypes, ModuleTypesBuilder,
-    ObjectKind, PrimaryMap, SecondaryMap, StaticModuleIndex, Tunables,
+    ObjectKind, PrimaryMap, SecondaryMap, StaticModuleIndex, TailCallAnalysis, TailCallDeoptInfo,
+    TailCallFunctionInfo, TailCallHint, Tunables, TypeIndex, WasmError, WasmValType,
 };
 #[cfg(feature = "component-model")]
 use wasmtime_environ::{FunctionLoc, component::Translator};
@@ -91,6 +94,7 @@ pub(crate) fn build_artifacts<T: FinishedObject>(
     .translate(parser, wasm)
     .context("failed to parse WebAssembly module")?;
     let functions = mem::take(&mut translation.function_body_inputs);
+    classify_tail_calls(engine, &mut translation, &functions)?;
 
     let compile_inputs = CompileInputs::for_module(&types, &translation, functions);
     let unlinked_compile_outputs = compile_inputs.compile(engine)?;
@@ -163,15 +167,14 @@ pub(crate) fn build_component_artifacts<T: FinishedObject>(
             .translate(binary)
             .context("failed to parse WebAssembly module")?;
 
-    let compile_inputs = CompileInputs::for_component(
-        engine,
-        &types,
-        &component,
-        module_translations.iter_mut().map(|(i, translation)| {
-            let functions = mem::take(&mut translation.function_body_inputs);
-            (i, &*translation, functions)
-        }),
-    );
+    let mut translations = Vec::new();
+    for (i, translation) in module_translations.iter_mut() {
+        let functions = mem::take(&mut translation.function_body_inputs);
+        classify_tail_calls(engine, translation, &functions)?;
+        let translation: &ModuleTranslation<'_> = translation;
+        translations.push((i, translation, functions));
+    }
+    let compile_inputs = CompileInputs::for_component(engine, &types, &component, translations);
     let unlinked_compile_outputs = compile_inputs.compile(&engine)?;
 
     let PreLinkOutput {
@@ -275,6 +278,25 @@ struct CompileOutput<'a> {
 
     // Only present when `self.key` is a `FuncKey::DefinedWasmFunction(..)`.
     func_body: Option<wasmparser::FunctionBody<'a>>,
+
+    // Only present when `self.key` is a `FuncKey::DefinedWasmFunction(..)`.
+    // Its class is `CallClass::SelfTailLoop` if the function's tail calls were
+    // compiled as a loop.
+    tail_calls: Option<TailCallAnalysis>,
+}
+
+impl CompileOutput<'_> {
+    /// The kinds of calls this function makes, if it's a defined Wasm
+    /// function.
+    fn call_class(&self) -> Option<CallClass> {
+        self.tail_calls.as_ref().map(|t| t.class)
+    }
+
+    /// The position of this function's first tail call, if it's a defined
+    /// Wasm function which makes any.
+    fn first_tail_call(&self) -> Option<FilePos> {
+        self.tail_calls.as_ref()?.first_tail_call()
+    }
 }
 
 /// Inputs to our inlining heuristics.
@@ -288,6 +310,7 @@ struct InlineHeuristicParams<'a> {
     callee_module: StaticModuleIndex,
     callee_def_func: DefinedFuncIndex,
     callee_needs_gc_heap: bool,
+    callee_call_class: Option<CallClass>,
 }
 
 /// The collection of things we need to compile for a Wasm module or component.
@@ -352,6 +375,7 @@ impl<'a> CompileInputs<'a> {
                     start_srcloc: FilePos::default(),
                     translation: None,
                     func_body: None,
+                    tail_calls: None,
                 })
             });
         }
@@ -377,6 +401,7 @@ impl<'a> CompileInputs<'a> {
                         start_srcloc: FilePos::default(),
                         translation: None,
                         func_body: None,
+                        tail_calls: None,
                     })
                 });
             }
@@ -455,7 +480,22 @@ impl<'a> CompileInputs<'a> {
                     let start_srcloc = FilePos::new(u32::try_from(offset).unwrap());
                     let function = compiler
                         .compile_function(translation, key, func_body_data, types, &symbol)
+                        .map_err(|e| {
+                            describe_tail_call_result_mismatch(
+                                translation,
+                                types,
+                                func_index,
+                                &func_body,
+                                e,
+                            )
+                        })
                         .with_context(|| format!("failed to compile: {symbol}"))?;
+                    let mut tail_calls = translation.tail_calls[def_func_index]
+                        .clone()
+                        .with_context(|| format!("failed to classify calls in: {symbol}"))?;
+                    if function.self_tail_loop {
+                        tail_calls.class = CallClass::SelfTailLoop;
+                    }
 
                     Ok(CompileOutput {
                         key,
@@ -464,6 +504,7 @@ impl<'a> CompileInputs<'a> {
                         start_srcloc,
                         translation: Some(translation),
                         func_body: Some(func_body),
+                        tail_calls: Some(tail_calls),
                     })
                 });
 
@@ -487,6 +528,7 @@ impl<'a> CompileInputs<'a> {
                             start_srcloc: FilePos::default(),
                             translation: None,
                             func_body: None,
+                            tail_calls: None,
                         })
                     });
                 }
@@ -516,6 +558,7 @@ impl<'a> CompileInputs<'a> {
                     start_srcloc: FilePos::default(),
                     translation: None,
                     func_body: None,
+                    tail_calls: None,
                 })
             });
         }
@@ -594,6 +637,30 @@ the use case.
             outputs.insert(output.key, output);
         }
 
+        if let Some(callback) = &engine.config().tail_call.deopt_callback {
+            for info in tail_call_deopts(&outputs) {
+                callback(info);
+            }
+        }
+        if engine.config().tail_call.report_to_log {
+            log_tail_call_classes(&outputs);
+        }
+        if engine.tunables().tail_call_require_optimization {
+            if let Some(info) = tail_call_deopts(&outputs).next() {
+                bail!("{info}");
+            }
+            if let Some(index) = unrelaxed_tail_call_only_functions(&outputs).next() {
+                bail!(
+                    "function {} only makes tail calls but its frame couldn't be optimized \
+                     because {}",
+                    index.as_u32(),
+                    regular_call_cause(engine.tunables()),
+                );
+            }
+        }
+
+        check_forced_tail_call_hints(&outputs, engine.tunables())?;
+
         Ok(UnlinkedCompileOutputs { outputs })
     }
 
@@ -745,6 +812,7 @@ the use case.
 
                         let callee_needs_gc_heap =
                             callee_output.translation.unwrap().module.needs_gc_heap;
+                        let callee_call_class = callee_output.call_class();
 
                         let (callee_module, callee_def_func) =
                             callee_key.unwrap_defined_wasm_function();
@@ -758,6 +826,7 @@ the use case.
                             callee_module,
                             callee_def_func,
                             callee_needs_gc_heap,
+                            callee_call_class,
                         }) {
                             caller_size = caller_size.saturating_add(callee_size);
                             Some(callee)
@@ -827,6 +896,7 @@ the use case.
             callee_module,
             callee_def_func,
             callee_needs_gc_heap,
+            callee_call_class,
         }: InlineHeuristicParams,
     ) -> bool {
         log::trace!(
@@ -836,7 +906,8 @@ the use case.
              \t\tneeds_gc_heap = {caller_needs_gc_heap}\n\
              \tcallee = ({callee_module:?}, {callee_def_func:?})\n\
              \t\tsize = {callee_size}\n\
-             \t\tneeds_gc_heap = {callee_needs_gc_heap}"
+             \t\tneeds_gc_heap = {callee_needs_gc_heap}\n\
+             \t\tcall_class = {callee_call_class:?}"
         );
 
         debug_assert!(
@@ -867,7 +938,26 @@ the use case.
         // function calls to small callees were presumably annotated with the
         // equivalent of `#[inline(never)]` or `#[cold]` but we don't have that
         // information anymore.
+        //
+        // The exception is small callees that don't make any regular calls,
+        // when configured to inline them: these are usually tiny helpers, and
+        // inlining them removes the call edge along with the callee's frame.
         if caller_module == callee_module {
+            if tunables.inlining_tail_call_only
+                && matches!(
+                    callee_call_class,
+                    Some(CallClass::Leaf | CallClass::TailCallOnly | CallClass::SelfTailLoop)
+                )
+                && callee_size <= tunables.inlining_small_callee_size
+            {
+                log::trace!(
+                    "  --> inlining: intra-module callee makes no regular calls and its size \
+                     is less than the small-callee size: {callee_size} <= {}",
+                    tunables.inlining_small_callee_size
+                );
+                return true;
+            }
+
             match tunables.inlining_intra_module {
                 IntraModuleInlining::Yes => {}
 
@@ -902,6 +992,366 @@ the use case.
     }
 }
 
+/// Returns information about each defined Wasm function in `outputs` whose
+/// frame wasn't fully optimized despite it only making tail calls.
+fn tail_call_deopts<'a>(
+    outputs: &'a BTreeMap<FuncKey, CompileOutput>,
+) -> impl Iterator<Item = TailCallDeoptInfo> + 'a {
+    outputs.values().filter_map(|output| {
+        let (FuncKey::DefinedWasmFunction(_, def_func_index), Some(translation)) =
+            (output.key, output.translation)
+        else {
+            return None;
+        };
+        let mut info = output.function.as_function()?.tail_call_deopt.clone()?;
+        debug_assert_eq!(
+            info.func_index,
+            translation.module.func_index(def_func_index)
+        );
+        info.tail_call_offset = output.first_tail_call();
+        info.source_loc = output
+            .first_tail_call()
+            .and_then(|pos| dwarf_source_loc(translation, pos));
+        Some(info)
+    })
+}
+
+/// Returns the index of each defined Wasm function in `outputs` which only
+/// makes tail calls according to its Wasm body, but which was neither optimized
+/// nor reported as a deopt by the compiler.
+///
+/// This happens when the compiler adds regular calls of its own to the
+/// function, such as calls into the host for tail-call instrumentation or for
+/// instructions like `memory.grow`, so that it no longer only makes tail calls
+/// once compiled. Functions marked `forbid` in their module's
+/// `tail_call_hints` section are never optimized on purpose and are skipped.
+fn unrelaxed_tail_call_only_functions<'a>(
+    outputs: &'a BTreeMap<FuncKey, CompileOutput>,
+) -> impl Iterator<Item = FuncIndex> + 'a {
+    outputs.values().filter_map(|output| {
+        let (FuncKey::DefinedWasmFunction(_, def_func_index), Some(translation)) =
+            (output.key, output.translation)
+        else {
+            return None;
+        };
+        let func_index = translation.module.func_index(def_func_index);
+        let function = output.function.as_function()?;
+        let unrelaxed = output.call_class() == Some(CallClass::TailCallOnly)
+            && translation.tail_call_hints[func_index] != TailCallHint::Forbid
+            && function.tail_call_frame_savings.is_none()
+            && function.tail_call_deopt.is_none();
+        unrelaxed.then_some(func_index)
+    })
+}
+
+/// Describes why a function which only makes tail calls in Wasm may make
+/// regular calls once compiled with `tunables`; see
+/// `unrelaxed_tail_call_only_functions` and `check_forced_tail_call_hints`.
+fn regular_call_cause(tunables: &Tunables) -> &'static str {
+    if tunables.tail_call_profiling {
+        "`TailCallOptions::profiling` adds a call into the host before each of its tail calls"
+    } else if tunables.tail_call_hooks {
+        "`TailCallOptions::hooks` adds a call into the host before each of its direct tail calls"
+    } else {
+        "its compiled code makes regular calls into the runtime, for example to implement \
+         `memory.grow` or garbage collection"
+    }
+}
+
+/// Logs the tail call classification of each defined Wasm function in
+/// `outputs`, for `TailCallOptions::report_to_log`.
+fn log_tail_call_classes(outputs: &BTreeMap<FuncKey, CompileOutput>) {
+    for output in outputs.values() {
+        let (
+            FuncKey::DefinedWasmFunction(module, def_func_index),
+            Some(translation),
+            Some(class),
+            Some(function),
+        ) = (
+            output.key,
+            output.translation,
+            output.call_class(),
+            output.function.as_function(),
+        )
+        else {
+            continue;
+        };
+        let func_index = translation.module.func_index(def_func_index);
+        let name = translation
+            .debuginfo
+            .name_section
+            .func_names
+            .get(&func_index)
+            .copied()
+            .unwrap_or("<unnamed>");
+        let module = module.as_u32();
+        let index = func_index.as_u32();
+        match (&function.tail_call_deopt, function.tail_call_frame_savings) {
+            (Some(deopt), _) => log::debug!(
+                "wasm[{module}] function {index} `{name}`: {class}, frame not optimized because {}",
+                deopt.reason
+            ),
+            (None, Some(savings)) => log::trace!(
+                "wasm[{module}] function {index} `{name}`: {class}, frame optimized saving {savings} bytes"
+            ),
+            (None, None) => {
+                log::trace!(
+                    "wasm[{module}] function {index} `{name}`: {class}, frame not optimized"
+                )
+            }
+        }
+    }
+}
+
+/// Records the `TailCallAnalysis` of each of the `functions` defined in
+/// `translation` in `ModuleTranslation::tail_calls`, so that each body is only
+/// scanned once for both compiling the functions and reporting on them.
+///
+/// Then invokes the engine's `TailCallOptions::classifier`, if any, for each
+/// function and marks those it reclassifies as making regular calls with
+/// `TailCallHint::Forbid`. Fails if the classifier makes any other change,
+/// since that could let a frame be optimized when it isn't safe to.
+///
+/// Bodies which can't be parsed are left unclassified here, and compiling them
+/// reports the error.
+fn classify_tail_calls(
+    engine: &Engine,
+    translation: &mut ModuleTranslation<'_>,
+    functions: &PrimaryMap<DefinedFuncIndex, FunctionBodyData<'_>>,
+) -> Result<()> {
+    for (def_func_index, input) in functions.iter() {
+        let func_index = translation.module.func_index(def_func_index);
+        translation.tail_calls[def_func_index] =
+            TailCallAnalysis::new(&input.body, func_index).ok();
+    }
+    let Some(classifier) = &engine.config().tail_call.classifier else {
+        return Ok(());
+    };
+    for def_func_index in functions.keys() {
+        let func_index = translation.module.func_index(def_func_index);
+        let index = func_index.as_u32();
+        let Some(class) = translation.tail_calls[def_func_index]
+            .as_ref()
+            .map(|t| t.class)
+        else {
+            continue;
+        };
+        let new_class = classifier(func_index, class);
+        if new_class == class {
+            continue;
+        }
+        if class != CallClass::TailCallOnly || !new_class.has_regular_calls() {
+            bail!(
+                "the tail-call classifier reclassified function {index} from {class} to \
+                 {new_class}, but the only permitted change is to reclassify a function which \
+                 only makes tail calls as one which makes regular calls"
+            );
+        }
+        if translation.tail_call_hints[func_index] == TailCallHint::Force {
+            bail!(
+                "the tail-call classifier reclassified function {index} as {new_class}, but \
+                 it's marked `force` in the `tail_call_hints` section"
+            );
+        }
+        translation.tail_call_hints[func_index] = TailCallHint::Forbid;
+    }
+    Ok(())
+}
+
+/// Fails if any defined Wasm function in `outputs` that's marked
+/// `TailCallHint::Force` in its module's `tail_call_hints` section didn't have
+/// its frame optimized.
+///
+/// This is the same check as `TailCallOptions::require_optimization` but
+/// scoped to individual functions, and it additionally rejects forced functions
+/// which make regular calls.
+fn check_forced_tail_call_hints(
+    outputs: &BTreeMap<FuncKey, CompileOutput>,
+    tunables: &Tunables,
+) -> Result<()> {
+    for output in outputs.values() {
+        let (FuncKey::DefinedWasmFunction(_, def_func_index), Some(translation)) =
+            (output.key, output.translation)
+        else {
+            continue;
+        };
+        let func_index = translation.module.func_index(def_func_index);
+        if translation.tail_call_hints[func_index] != TailCallHint::Force {
+            continue;
+        }
+        let Some(function) = output.function.as_function() else {
+            continue;
+        };
+        let index = func_index.as_u32();
+        match output.call_class() {
+            Some(CallClass::Regular | CallClass::Mixed) => bail!(
+                "function {index} is marked `force` in the `tail_call_hints` section but its \
+                 frame can't be optimized because it makes regular calls"
+            ),
+            Some(CallClass::TailCallOnly) => {}
+            _ => continue,
+        }
+        if let Some(deopt) = &function.tail_call_deopt {
+            let location = output
+                .first_tail_call()
+                .and_then(|pos| dwarf_source_loc(translation, pos))
+                .map(|(file, line, column)| format!(" at {file}:{line}:{column}"))
+                .unwrap_or_default();
+            bail!(
+                "function {index}{location} is marked `force` in the `tail_call_hints` section \
+                 but its frame couldn't be optimized because {} ({} bytes of outgoing \
+                 arguments, {} bytes of stack slots)",
+                deopt.reason,
+                deopt.outgoing_args_size,
+                deopt.stackslots_size,
+            );
+        }
+        if function.tail_call_frame_savings.is_none() {
+            bail!(
+                "function {index} is marked `force` in the `tail_call_hints` section but its \
+                 frame couldn't be optimized because {}",
+                regular_call_cause(tunables),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Returns the source file, line, and column of the instruction at `pos` in
+/// `translation`, if the module's DWARF debug information describes it.
+#[cfg(feature = "addr2line")]
+fn dwarf_source_loc(
+    translation: &ModuleTranslation<'_>,
+    pos: FilePos,
+) -> Option<(String, u32, u32)> {
+    use gimli::{EndianSlice, LittleEndian, Section, SectionId};
+    let debuginfo = &translation.debuginfo;
+    let dwarf = gimli::Dwarf::load(|id| -> Result<_> {
+        Ok(match id {
+            SectionId::DebugAbbrev => *debuginfo.dwarf.debug_abbrev.reader(),
+            SectionId::DebugAddr => *debuginfo.dwarf.debug_addr.reader(),
+            SectionId::DebugAranges => *debuginfo.dwarf.debug_aranges.reader(),
+            SectionId::DebugInfo => *debuginfo.dwarf.debug_info.reader(),
+            SectionId::DebugLine => *debuginfo.dwarf.debug_line.reader(),
+            SectionId::DebugLineStr => *debuginfo.dwarf.debug_line_str.reader(),
+            SectionId::DebugStr => *debuginfo.dwarf.debug_str.reader(),
+            SectionId::DebugStrOffsets => *debuginfo.dwarf.debug_str_offsets.reader(),
+            SectionId::DebugRanges => *debuginfo.debug_ranges.reader(),
+            SectionId::DebugRngLists => *debuginfo.debug_rnglists.reader(),
+            _ => EndianSlice::new(&[], LittleEndian),
+        })
+    })
+    .ok()?;
+    let context = addr2line::Context::from_dwarf(dwarf).ok()?;
+    let offset =
+        u64::from(pos.file_offset()?).checked_sub(debuginfo.wasm_file.code_section_offset)?;
+    let location = context.find_location(offset).ok()??;
+    Some((
+        location.file?.to_string(),
+        location.line?,
+        location.column.unwrap_or(0),
+    ))
+}
+
+#[cfg(not(feature = "addr2line"))]
+fn dwarf_source_loc(
+    _translation: &ModuleTranslation<'_>,
+    _pos: FilePos,
+) -> Option<(String, u32, u32)> {
+    None
+}
+
+/// Replaces a validation error reported at a tail call with a more precise
+/// error if the tail call's callee returns different results than the calling
+/// function, which tail calls require to match.
+///
+/// Other errors are returned unchanged.
+fn describe_tail_call_result_mismatch(
+    translation: &ModuleTranslation<'_>,
+    types: &ModuleTypesBuilder,
+    func_index: FuncIndex,
+    body: &wasmparser::FunctionBody<'_>,
+    error: CompileError,
+) -> CompileError {
+    let CompileError::Wasm(WasmError::InvalidWebAssembly { message, offset }) = &error else {
+        return error;
+    };
+    let module = &translation.module;
+    let func_returns = |ty: EngineOrModuleTypeIndex| {
+        types[ty.unwrap_module_type_index()]
+            .as_func()
+            .map(|f| f.returns())
+    };
+    let Some(caller_returns) = func_returns(module.functions[func_index].signature) else {
+        return error;
+    };
+
+    let Ok(mut reader) = body.get_operators_reader() else {
+        return error;
+    };
+    while !reader.eof() {
+        let Ok((op, op_offset)) = reader.read_with_offset() else {
+            break;
+        };
+        if op_offset != *offset {
+            continue;
+        }
+        let (callee, callee_returns) = match op {
+            wasmparser::Operator::ReturnCall { function_index } => {
+                let callee = FuncIndex::from_u32(function_index);
+                let Some(ty) = module.functions.get(callee) else {
+                    break;
+                };
+                (
+                    format!("function {}", callee.as_u32()),
+                    func_returns(ty.signature),
+                )
+            }
+            wasmparser::Operator::ReturnCallIndirect { type_index, .. }
+            | wasmparser::Operator::ReturnCallRef { type_index } => {
+                let Some(ty) = module.types.get(TypeIndex::from_u32(type_index)) else {
+                    break;
+                };
+                (
+                    format!("a function of type {type_index}"),
+                    func_returns(*ty),
+                )
+            }
+            _ => break,
+        };
+        let Some(callee_returns) = callee_returns else {
+            break;
+        };
+
+        // Reference types may differ as long as the callee's results are
+        // subtypes of the caller's, so only value types which can't be
+        // subtypes of each other are considered a mismatch here.
+        let may_match = caller_returns.len() == callee_returns.len()
+            && caller_returns.iter().zip(callee_returns).all(|(a, b)| {
+                a == b || matches!((a, b), (WasmValType::Ref(_), WasmValType::Ref(_)))
+            });
+        if may_match {
+            break;
+        }
+
+        let fmt_types = |tys: &[WasmValType]| {
+            let tys = tys.iter().map(|ty| ty.to_string()).collect::<Vec<_>>();
+            format!("[{}]", tys.join(", "))
+        };
+        return CompileError::Wasm(WasmError::InvalidWebAssembly {
+            message: format!(
+                "{message}: function {} returns {} but tail calls {callee} which returns {}; \
+                 the results of a tail call's callee must match the caller's results",
+                func_index.as_u32(),
+                fmt_types(caller_returns),
+                fmt_types(callee_returns),
+            ),
+            offset: *offset,
+        });
+    }
+    error
+}
+
 fn compile_required_builtins(engine: &Engine, raw_outputs: &mut Vec<CompileOutput>) -> Result<()> {
     let compiler = engine.compiler();
     let mut builtins = HashSet::new();
@@ -924,6 +1374,7 @@ fn compile_required_builtins(engine: &Engine, raw_outputs: &mut Vec<CompileOutpu
                 start_srcloc: FilePos::default(),
                 translation: None,
                 func_body: None,
+                tail_calls: None,
             })
         })
     };
@@ -969,6 +1420,15 @@ impl UnlinkedCompileOutputs<'_> {
             let index = match output.function {
                 CompiledFunction::Function(f) => {
                     needs_gc_heap |= f.needs_gc_heap;
+                    if let Some(savings) = f.tail_call_frame_savings {
+                        indices.tail_call_frame_savings.insert(output.key, savings);
+                    }
+                    if let Some(frame_layout) = f.frame_layout {
+                        indices.frame_layouts.insert(output.key, frame_layout);
+                    }
+                    if let Some(call_conv) = f.call_conv {
+                        indices.call_convs.insert(output.key, call_conv);
+                    }
                     let index = compiled_funcs.len();
                     compiled_funcs.push((output.symbol, f.code));
                     CompiledFunction::Function(index)
@@ -1004,6 +1464,10 @@ impl UnlinkedCompileOutputs<'_> {
                     .insert(output.key, output.start_srcloc);
             }
 
+            if let Some(tail_calls) = output.tail_calls {
+                indices.tail_calls.insert(output.key, tail_calls);
+            }
+
             indices.indices.insert(output.key, index);
         }
 
@@ -1036,15 +1500,89 @@ struct FunctionIndices {
     // A map of wasm functions and where they're located in the original file.
     start_srclocs: HashMap<FuncKey, FilePos>,
 
+    // A map of wasm functions to the calls they make.
+    tail_calls: HashMap<FuncKey, TailCallAnalysis>,
+
+    // A map of wasm functions whose frames were optimized to the number of
+    // stack bytes that saved.
+    tail_call_frame_savings: HashMap<FuncKey, u32>,
+
+    // A map of wasm functions to the calling conventions they were compiled
+    // with.
+    call_convs: HashMap<FuncKey, CallConv>,
+
+    // A map of functions to the layouts of their stack frames, for those
+    // whose compiler reported them.
+    frame_layouts: HashMap<FuncKey, FrameLayout>,
+
     // The index of each compiled function.
     indices: BTreeMap<FuncKey, CompiledFunction<usize>>,
 }
 
 impl FunctionIndices {
+    /// Find the cliques of tail-call-only functions within each module: the
+    /// strongly-connected components, with at least two members, of the graph
+    /// of direct tail calls between those functions.
+    ///
+    /// Returns a map from each clique member to the lowest function index in
+    /// its clique. Cliques are only recorded so they can be reported by
+    /// `Module::tail_call_cliques`; their members are still compiled as
+    /// separate functions.
+    fn tail_call_cliques(
+        &self,
+        translations: &PrimaryMap<StaticModuleIndex, ModuleTranslation<'_>>,
+    ) -> HashMap<FuncKey, FuncIndex> {
+        let is_tail_call_only = |key: &FuncKey| {
+            self.tail_calls
+                .get(key)
+                .is_some_and(|t| t.class == CallClass::TailCallOnly)
+        };
+        let mut funcs_by_module = BTreeMap::<StaticModuleIndex, Vec<DefinedFuncIndex>>::new();
+        for key in self.tail_calls.keys().filter(|key| is_tail_call_only(key)) {
+            if let FuncKey::DefinedWasmFunction(module, def_func) = *key {
+                funcs_by_module.entry(module).or_default().push(def_func);
+            }
+        }
+
+        let mut cliques = HashMap::new();
+        for (module, mut funcs) in funcs_by_module {
+            funcs.sort_unstable();
+            let module_info = &translations[module].module;
+            let components = scc::StronglyConnectedComponents::new(funcs, |def_func| {
+                self.tail_calls[&FuncKey::DefinedWasmFunction(module, def_func)]
+                    .direct_tail_callees()
+                    .filter_map(|callee| module_info.defined_func_index(callee))
+                    .filter(|callee| {
+                        is_tail_call_only(&FuncKey::DefinedWasmFunction(module, *callee))
+                    })
+                    .collect::<Vec<_>>()
+                    .into_iter()
+            });
+
+            for members in components.values() {
+                if members.len() < 2 {
+                    continue;
+                }
+                let representative = members
+                    .iter()
+                    .map(|def_func| module_info.func_index(*def_func))
+                    .min()
+                    .unwrap();
+                for def_func in members {
+                    cliques.insert(
+                        FuncKey::DefinedWasmFunction(module, *def_func),
+                        representative,
+                    );
+                }
+            }
+        }
+        cliques
+    }
+
     /// Link the compiled functions together, resolving relocations, and append
     /// them to the given ELF file.
     fn link_and_append_code<'a>(
-        self,
+        mut self,
         types: &ModuleTypesBuilder,
         mut obj: object::write::Object<'static>,
         engine: &'a Engine,
@@ -1094,6 +1632,12 @@ impl FunctionIndices {
             PrimaryMap<DefinedFuncIndex, CompiledFunctionInfo>,
         >::new();
 
+        // Tail-call metadata is only recorded when the proposal is enabled,
+        // since otherwise no function can make tail calls.
+        let record_tail_calls = engine.features().contains(WasmFeatures::TAIL_CALL);
+        let tail_call_cliques = self.tail_call_cliques(&translations);
+        let mut tail_calls = mem::take(&mut self.tail_calls);
+
         #[cfg(feature = "component-model")]
         let mut trampolines = PrimaryMap::<
             wasmtime_environ::component::TrampolineIndex,
@@ -1112,6 +1656,24 @@ impl FunctionIndices {
                     let index = index.unwrap_function();
                     let (_, wasm_func_loc) = symbol_ids_and_locs[index];
                     let start_srcloc = self.start_srclocs[key];
+                    let analysis = tail_calls.remove(key);
+                    let tail_calls = record_tail_calls.then(|| TailCallFunctionInfo {
+                        // Conservatively assume regular calls for functions
+                        // which weren't classified.
+                        call_class: analysis.as_ref().map_or(CallClass::Regular, |t| t.class),
+                        tail_call_frame_savings: self.tail_call_frame_savings.get(key).copied(),
+                        tail_call_clique: tail_call_cliques.get(key).copied(),
+                        unconditional_tail_callee: analysis
+                            .as_ref()
+                            .and_then(|t| t.unconditional_tail_callee),
+                        call_edges: analysis
+                            .as_ref()
+                            .map(|t| t.call_edges.clone())
+                            .unwrap_or_default(),
+                        tail_call_sites: analysis.map(|t| t.tail_call_sites).unwrap_or_default(),
+                        call_conv: self.call_convs.get(key).copied().unwrap_or(CallConv::Tail),
+                        frame_layout: self.frame_layouts.get(key).copied(),
+                    });
 
                     let array_to_wasm_trampoline = self
                         .indices
@@ -1127,6 +1689,7 @@ impl FunctionIndices {
                         start_srcloc,
                         wasm_func_loc,
                         array_to_wasm_trampoline,
+                        tail_calls,
                     });
                     debug_assert_eq!(def_func, def_func2);
                 }
@@ -278,6 +278,33 @@ impl<'a> CodeBuilder<'a> {
         Ok(v)
     }
 
+    /// Same as [`CodeBuilder::compile_module_serialized`] except that it also
+    /// returns a report of how each function's tail calls were compiled.
+    ///
+    /// The report is built from compilation metadata, so the serialized
+    /// bytes don't need to be loaded to produce it.
+    #[cfg(feature = "tail-call-report")]
+    pub fn compile_module_serialized_with_report(
+        &self,
+    ) -> Result<(Vec<u8>, crate::TailCallReport)> {
+        let wasm = self.get_wasm()?;
+        let dwarf_package = self.get_dwarf_package();
+        let (v, info) = super::build_artifacts(self.engine, &wasm, dwarf_package.as_deref(), &())?;
+        let (info, _types) = info.unwrap();
+        let report = crate::TailCallReport::new(
+            &info.module,
+            info.funcs.iter().filter_map(|(def_func, func)| {
+                let tail_calls = func.tail_calls.as_ref()?;
+                Some((
+                    info.module.func_index(def_func),
+                    tail_calls.call_class,
+                    tail_calls.tail_call_frame_savings,
+                ))
+            }),
+        );
+        Ok((v, report))
+    }
+
     /// Same as [`CodeBuilder::compile_module_serialized`] except that it
     /// compiles a serialized [`Component`](crate::component::Component)
     /// instead of a module.
@@ -211,7 +211,6 @@ where
     ///
     /// Iteration happens in reverse-topological order (successors are visited
     /// before predecessors in the resulting SCC DAG).
-    #[cfg(test)]
     pub fn values(&self) -> impl ExactSizeIterator<Item = &[Node]> + '_ {
         self.components
             .values()
@@ -30,6 +30,8 @@ pub use crate::runtime::code_memory::CustomCodeMemory;
 pub use wasmtime_cache::{Cache, CacheConfig};
 #[cfg(all(feature = "incremental-cache", feature = "cranelift"))]
 pub use wasmtime_environ::CacheStore;
+#[cfg(any(feature = "cranelift", feature = "winch"))]
+pub use wasmtime_environ::{TailCallDeoptInfo, TailCallDeoptReason, TailCallHint};
 
 /// Represents the module instance allocation strategy to use.
 #[derive(Clone)]
@@ -164,6 +166,7 @@ pub struct Config {
     pub(crate) macos_use_mach_ports: bool,
     pub(crate) detect_host_feature: Option<fn(&str) -> Option<bool>>,
     pub(crate) x86_float_abi_ok: Option<bool>,
+    pub(crate) tail_call: TailCallOptions,
 }
 
 /// User-provided configuration for the compiler.
@@ -273,6 +276,7 @@ impl Config {
             #[cfg(not(feature = "std"))]
             detect_host_feature: None,
             x86_float_abi_ok: None,
+            tail_call: TailCallOptions::default(),
         };
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
@@ -595,6 +599,13 @@ impl Config {
     /// to use epoch deadlines to limit the execution time of untrusted
     /// code.
     ///
+    /// The deadline is checked on entry to every function and at every loop
+    /// back-edge. Each tail call enters its callee, so even a tail-recursive
+    /// loop that never returns is interruptible, including a self-recursive
+    /// one compiled as a loop with
+    /// [`TailCallOptions::self_recursion_loop`], whose every iteration
+    /// repeats the function-entry check.
+    ///
     /// The [`Store`](crate::Store) tracks the deadline, and controls
     /// what happens when the deadline is reached during
     /// execution. Several behaviors are possible:
@@ -794,6 +805,9 @@ impl Config {
     /// usage.
     ///
     /// This is `true` by default except when the Winch compiler is enabled.
+    /// Winch does not yet support tail calls, so enabling this together with
+    /// [`Strategy::Winch`] causes [`Engine::new`](crate::Engine::new) to return
+    /// an error.
     ///
     /// [WebAssembly tail calls proposal]: https://github.com/WebAssembly/tail-call
     pub fn wasm_tail_call(&mut self, enable: bool) -> &mut Self {
@@ -801,6 +815,81 @@ impl Config {
         self
     }
 
+    /// Configures how WebAssembly tail calls are compiled and executed.
+    ///
+    /// See [`TailCallOptions`] for the available options. Calling this again
+    /// replaces all previously configured tail-call options.
+    ///
+    /// Options which are enabled or set to a value other than their default
+    /// require [`Config::wasm_tail_call`] to be enabled, otherwise creating an
+    /// [`Engine`] with this configuration will fail.
+    ///
+    /// By default all options have their default values.
+    pub fn tail_call_options(&mut self, options: TailCallOptions) -> &mut Self {
+        let tunables = &mut self.tunables;
+        tunables.tail_call_relaxed_stack_check = options.relaxed_stack_check;
+        tunables.tail_call_max_stack_args_bytes = options.max_stack_args_bytes;
+        tunables.tail_call_min_frame_savings = options.min_frame_savings;
+        tunables.tail_call_max_optimized_body_bytes = options.max_optimized_body_bytes;
+        tunables.tail_call_require_optimization = options.require_optimization;
+        tunables.tail_call_self_recursion_loop = options.self_recursion_loop;
+        tunables.tail_call_fuel = options.fuel.map(|_| true);
+        tunables.tail_call_stack_limit = options.stack_limit.map(|_| true);
+        tunables.tail_call_profiling = options.profiling;
+        tunables.tail_call_hooks = options.hooks;
+        tunables.tail_call_debug_assertions = options.debug_assertions;
+        tunables.max_tail_call_chain_length = options.max_chain_length;
+        tunables.tail_call_fuel_cost = options.fuel_cost;
+        tunables.tail_call_host_tracking = options.host_tracking;
+        tunables.tail_call_zero_unused_frame = options.zero_unused_frame;
+        self.tail_call = options;
+        self
+    }
+
+    /// Returns how well tail calls are supported by the compiler and target
+    /// that this configuration compiles for.
+    ///
+    /// This can be used to pick a strategy, or just to log which mode is in
+    /// use, before creating an [`Engine`]:
+    ///
+    /// * [`TailCallSupport::FullyOptimized`] if tail calls are enabled and the
+    ///   stack checks of functions which only make tail calls are relaxed as
+    ///   described in [`TailCallOptions::relaxed_stack_check`]. Individual
+    ///   functions may still not be optimized, which is reported through
+    ///   [`TailCallOptions::on_deopt`].
+    /// * [`TailCallSupport::SemanticsOnly`] if tail calls are enabled and
+    ///   don't grow the stack, but no stack checks are relaxed, either because
+    ///   [`TailCallOptions::relaxed_stack_check`] is disabled or because the
+    ///   relaxation isn't implemented for the target, which is currently
+    ///   the case for everything other than x86_64 and aarch64, including
+    ///   Pulley.
+    /// * [`TailCallSupport::Unsupported`] if tail calls are disabled with
+    ///   [`Config::wasm_tail_call`] or can't be used with the configured
+    ///   compiler at all, which is currently the case for
+    ///   [`Strategy::Winch`].
+    ///
+    /// This only inspects the configuration and doesn't validate it, so
+    /// creating an [`Engine`] may still fail for other reasons.
+    pub fn supports_tail_call_optimization(&self) -> TailCallSupport {
+        if !self.features().contains(WasmFeatures::TAIL_CALL)
+            || self
+                .compiler_panicking_wasm_features()
+                .contains(WasmFeatures::TAIL_CALL)
+        {
+            return TailCallSupport::Unsupported;
+        }
+        if self.tunables.tail_call_relaxed_stack_check == Some(false) {
+            return TailCallSupport::SemanticsOnly;
+        }
+        use target_lexicon::Architecture;
+        match self.compiler_target().architecture {
+            Architecture::X86_64 | Architecture::X86_64h | Architecture::Aarch64(_) => {
+                TailCallSupport::FullyOptimized
+            }
+            _ => TailCallSupport::SemanticsOnly,
+        }
+    }
+
     /// Configures whether the WebAssembly custom-page-sizes proposal will be
     /// enabled for compilation or not.
     ///
@@ -1242,6 +1331,14 @@ impl Config {
     /// to be enabled, such as `ProfilingStrategy::JitDump` requires the `jitdump`
     /// feature.
     ///
+    /// Functions whose stack checks are relaxed as described in
+    /// [`TailCallOptions::relaxed_stack_check`] only make tail calls, each of
+    /// which replaces their frame with their callee's. Samples taken while
+    /// they run are attributed to them, as usual, but the function that
+    /// called into a chain of tail calls may be missing from sampled call
+    /// stacks. To make this visible their symbols are registered with a
+    /// ` [reused frame]` suffix.
+    ///
     /// # Errors
     ///
     /// The validation of this field is deferred until the engine is being built, and thus may
@@ -1977,6 +2074,11 @@ impl Config {
     /// Configures whether or not a coredump should be generated and attached to
     /// the anyhow::Error when a trap is raised.
     ///
+    /// When [`TailCallOptions::profiling`] or [`TailCallOptions::hooks`] is
+    /// also enabled the coredump records the functions which most recently
+    /// made tail calls, see
+    /// [`WasmCoreDump::tail_call_trail`](crate::WasmCoreDump::tail_call_trail).
+    ///
     /// This option is disabled by default.
     #[cfg(feature = "coredump")]
     pub fn coredump_on_trap(&mut self, enable: bool) -> &mut Self {
@@ -2053,6 +2155,35 @@ impl Config {
         self
     }
 
+    /// Whether to inline small callees which make no regular calls into the
+    /// functions that call them, even within a single core Wasm module.
+    ///
+    /// By default Wasmtime avoids inlining calls between functions in the same
+    /// core Wasm module, since the toolchain which produced the module has most
+    /// likely already made its own inlining decisions. Tiny helper functions
+    /// which are either leaf functions or only make tail calls (see
+    /// [`CallClass::Leaf`](crate::CallClass::Leaf) and
+    /// [`CallClass::TailCallOnly`](crate::CallClass::TailCallOnly)) are cheap
+    /// to inline, though, and doing so removes a call edge along with the
+    /// callee's frame setup. When this setting is enabled such callees are
+    /// inlined regardless of the intra-module inlining heuristic.
+    ///
+    /// Only callees whose size is at most Wasmtime's small-callee threshold
+    /// are considered, which is measured in Cranelift IR instructions and is
+    /// currently 50. The usual limit on the combined size of the caller and
+    /// callee still applies.
+    ///
+    /// This only has an effect when [`Config::compiler_inlining`] is enabled,
+    /// and it is ignored when [`Config::cranelift_opt_level`] is
+    /// [`OptLevel::None`] since that level is meant to minimize compile time.
+    /// Like inlining in general it is not supported by Winch.
+    ///
+    /// By default this is `false`.
+    pub fn inline_tail_call_only(&mut self, enable: bool) -> &mut Self {
+        self.tunables.inlining_tail_call_only = Some(enable);
+        self
+    }
+
     /// Returns the set of features that the currently selected compiler backend
     /// does not support at all and may panic on.
     ///
@@ -2222,9 +2353,20 @@ impl Config {
                 if !unsupported.contains(*flag.value()) {
                     continue;
                 }
+                #[cfg(any(feature = "cranelift", feature = "winch"))]
+                let strategy = self.compiler_config.strategy;
+                #[cfg(not(any(feature = "cranelift", feature = "winch")))]
+                let strategy: Option<Strategy> = None;
                 bail!(
-                    "the wasm_{} feature is not supported on this compiler configuration",
-                    flag.name().to_lowercase()
+                    "the wasm_{} feature is not supported on this compiler configuration{}",
+                    flag.name().to_lowercase(),
+                    match strategy {
+                        Some(strategy) => format!(
+                            " (the {strategy:?} strategy targeting `{}`)",
+                            self.compiler_target()
+                        ),
+                        None => String::new(),
+                    }
                 );
             }
 
@@ -2250,6 +2392,109 @@ impl Config {
             bail!("exceptions support requires garbage collection (GC) to be enabled in the build");
         }
 
+        // Every tail-call option which is enabled or set to a value other than
+        // its default requires tail calls.
+        let tail_call_options = [
+            (
+                "relaxed_stack_check",
+                self.tunables.tail_call_relaxed_stack_check.is_some(),
+            ),
+            (
+                "max_stack_args_bytes",
+                self.tunables.tail_call_max_stack_args_bytes.is_some(),
+            ),
+            (
+                "min_frame_savings",
+                self.tunables.tail_call_min_frame_savings.is_some(),
+            ),
+            (
+                "max_optimized_body_bytes",
+                matches!(
+                    self.tunables.tail_call_max_optimized_body_bytes,
+                    Some(Some(_))
+                ),
+            ),
+            (
+                "require_optimization",
+                self.tunables.tail_call_require_optimization == Some(true),
+            ),
+            (
+                "self_recursion_loop",
+                self.tunables.tail_call_self_recursion_loop == Some(true),
+            ),
+            ("fuel", self.tail_call.fuel.is_some()),
+            ("stack_limit", self.tail_call.stack_limit.is_some()),
+            ("profiling", self.tunables.tail_call_profiling == Some(true)),
+            ("hooks", self.tunables.tail_call_hooks == Some(true)),
+            (
+                "debug_assertions",
+                self.tunables.tail_call_debug_assertions == Some(true),
+            ),
+            (
+                "max_chain_length",
+                matches!(self.tunables.max_tail_call_chain_length, Some(Some(_))),
+            ),
+            ("fuel_cost", self.tunables.tail_call_fuel_cost.is_some()),
+            (
+                "host_tracking",
+                self.tunables.tail_call_host_tracking == Some(true),
+            ),
+            (
+                "zero_unused_frame",
+                self.tunables.tail_call_zero_unused_frame == Some(true),
+            ),
+        ];
+        if !features.contains(WasmFeatures::TAIL_CALL) {
+            if let Some((name, _)) = tail_call_options.iter().find(|(_, set)| *set) {
+                bail!("TailCallOptions::{name} requires wasm_tail_call to be enabled");
+            }
+        }
+        if let Some(bytes) = self.tunables.tail_call_max_stack_args_bytes {
+            let align = match self.compiler_target().architecture {
+                target_lexicon::Architecture::S390x => 8,
+                _ => 16,
+            };
+            if bytes % align != 0 {
+                bail!("TailCallOptions::max_stack_args_bytes must be a multiple of {align}");
+            }
+            if bytes > u32::from(u8::MAX) {
+                bail!(
+                    "TailCallOptions::max_stack_args_bytes cannot exceed {}",
+                    u8::MAX
+                );
+            }
+        }
+        if let Some(bytes) = self.tunables.tail_call_min_frame_savings {
+            if bytes > u32::from(u8::MAX) {
+                bail!(
+                    "TailCallOptions::min_frame_savings cannot exceed {}",
+                    u8::MAX
+                );
+            }
+        }
+        if self.tunables.tail_call_require_optimization == Some(true)
+            && self.tunables.tail_call_relaxed_stack_check == Some(false)
+        {
+            bail!(
+                "TailCallOptions::require_optimization requires \
+                 TailCallOptions::relaxed_stack_check to be enabled"
+            );
+        }
+        if let Some(cost) = self.tunables.tail_call_fuel_cost {
+            if i64::try_from(cost).is_err() {
+                bail!("TailCallOptions::fuel_cost cannot exceed {}", i64::MAX);
+            }
+        }
+        if self.tunables.tail_call_zero_unused_frame == Some(true) {
+            use target_lexicon::Architecture;
+            match self.compiler_target().architecture {
+                Architecture::X86_64 | Architecture::X86_64h | Architecture::Aarch64(_) => {}
+                _ => bail!(
+                    "TailCallOptions::zero_unused_frame is only supported on x86_64 and aarch64"
+                ),
+            }
+        }
+
         let mut tunables = Tunables::default_for_target(&self.compiler_target())?;
 
         // If no target is explicitly specified then further refine `tunables`
@@ -2283,6 +2528,18 @@ impl Config {
         #[cfg(any(feature = "cranelift", feature = "winch"))]
         {
             tunables.winch_callable = self.compiler_config.strategy == Some(Strategy::Winch);
+
+            // Don't bother inlining tail-call-only callees when Cranelift has
+            // been asked to not optimize at all.
+            if self
+                .compiler_config
+                .settings
+                .get("opt_level")
+                .map(|s| s.as_str())
+                == Some("none")
+            {
+                tunables.inlining_tail_call_only = false;
+            }
         }
 
         tunables.collector = if features.gc_types() {
@@ -2475,6 +2732,41 @@ impl Config {
             .settings
             .insert("preserve_frame_pointers".into(), "true".into());
 
+        let tail_call_relaxed_stack_check =
+            tunables.tail_call_relaxed_stack_check && features.contains(WasmFeatures::TAIL_CALL);
+        if !self.compiler_config.ensure_setting_unset_or_given(
+            "tail_call_relaxed_stack_check",
+            &tail_call_relaxed_stack_check.to_string(),
+        ) {
+            bail!(
+                "incompatible settings requested for Cranelift and Wasmtime `tail_call_relaxed_stack_check` settings"
+            );
+        }
+        if !self.compiler_config.ensure_setting_unset_or_given(
+            "tail_call_max_stack_args_bytes",
+            &tunables.tail_call_max_stack_args_bytes.to_string(),
+        ) {
+            bail!(
+                "incompatible settings requested for Cranelift and Wasmtime `tail_call_max_stack_args_bytes` settings"
+            );
+        }
+        if !self.compiler_config.ensure_setting_unset_or_given(
+            "tail_call_min_frame_savings",
+            &tunables.tail_call_min_frame_savings.to_string(),
+        ) {
+            bail!(
+                "incompatible settings requested for Cranelift and Wasmtime `tail_call_min_frame_savings` settings"
+            );
+        }
+        if !self.compiler_config.ensure_setting_unset_or_given(
+            "tail_call_zero_frame",
+            &tunables.tail_call_zero_unused_frame.to_string(),
+        ) {
+            bail!(
+                "incompatible settings requested for Cranelift and Wasmtime `tail_call_zero_frame` settings"
+            );
+        }
+
         if !tunables.signals_based_traps {
             let mut ok = self
                 .compiler_config
@@ -3063,6 +3355,677 @@ pub enum WasmBacktraceDetails {
     Environment,
 }
 
+/// Options controlling how WebAssembly tail calls are compiled and executed,
+/// configured with [`Config::tail_call_options`].
+///
+/// Every option here only applies when tail calls are enabled with
+/// [`Config::wasm_tail_call`], and the defaults of all of them are suitable
+/// for production use: most are debugging and profiling aids which add
+/// instrumentation to compiled code when enabled.
+///
+/// # Example
+///
+/// ```
+/// use wasmtime::{Config, Engine, TailCallOptions};
+///
+/// let mut options = TailCallOptions::new();
+/// options.fuel(Some(1_000_000)).profiling(true);
+///
+/// let mut config = Config::new();
+/// config.wasm_tail_call(true).tail_call_options(options);
+/// let engine = Engine::new(&config)?;
+/// # Ok::<(), wasmtime::Error>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct TailCallOptions {
+    pub(crate) relaxed_stack_check: Option<bool>,
+    pub(crate) max_stack_args_bytes: Option<u32>,
+    pub(crate) min_frame_savings: Option<u32>,
+    pub(crate) max_optimized_body_bytes: Option<Option<u32>>,
+    pub(crate) require_optimization: Option<bool>,
+    pub(crate) self_recursion_loop: Option<bool>,
+    pub(crate) fuel: Option<u64>,
+    pub(crate) stack_limit: Option<usize>,
+    pub(crate) profiling: Option<bool>,
+    pub(crate) hooks: Option<bool>,
+    pub(crate) debug_assertions: Option<bool>,
+    pub(crate) max_chain_length: Option<Option<u64>>,
+    pub(crate) fuel_cost: Option<u64>,
+    pub(crate) host_tracking: Option<bool>,
+    pub(crate) zero_unused_frame: Option<bool>,
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) deopt_callback: Option<Arc<dyn Fn(TailCallDeoptInfo) + Send + Sync>>,
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) report_to_log: bool,
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub(crate) classifier: Option<
+        Arc<
+            dyn Fn(
+                    wasmtime_environ::FuncIndex,
+                    wasmtime_environ::CallClass,
+                ) -> wasmtime_environ::CallClass
+                + Send
+                + Sync,
+        >,
+    >,
+    pub(crate) reject_modules: bool,
+}
+
+impl TailCallOptions {
+    /// Returns a new set of options with all default settings configured.
+    pub fn new() -> TailCallOptions {
+        TailCallOptions::default()
+    }
+
+    /// Configures whether functions which only make tail calls have their
+    /// stack limit checks relaxed like those of leaf functions.
+    ///
+    /// A function whose only calls are tail calls (see
+    /// [`CallClass::TailCallOnly`](crate::CallClass::TailCallOnly)) tears down
+    /// its own frame before each call, so no callee frame is ever pushed on top
+    /// of it. When this is enabled such functions don't reserve room for a
+    /// callee's frame setup in the stack limit check made on entry, and skip
+    /// the check entirely if they otherwise use no stack, just like leaf
+    /// functions. Their frames themselves are laid out exactly as they would
+    /// be otherwise: this only changes how much stack is checked for, so
+    /// disabling it doesn't change their behavior short of a stack overflow.
+    /// It's also unaffected by host functions that call back into
+    /// WebAssembly: each re-entry starts from a regular host-to-wasm call
+    /// whose frames are unwound normally before returning to the host.
+    ///
+    /// Other tail-call settings and reports describe functions to which this
+    /// relaxation applies as having their frames "optimized", and those to
+    /// which it can't apply fully as "deoptimized".
+    ///
+    /// This relaxation is currently only implemented for x86_64 and aarch64
+    /// when using the Cranelift compiler and is otherwise ignored.
+    ///
+    /// Modules can override this setting for individual functions with a
+    /// `tail_call_hints` custom section, whose format is described by
+    /// [`TailCallHint`]. Functions marked [`TailCallHint::Forbid`] never have
+    /// their stack check relaxed and functions marked [`TailCallHint::Force`]
+    /// always do, with compilation failing if they can't, much like
+    /// [`TailCallOptions::require_optimization`] does for every function.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// This is `true` by default when tail calls are enabled.
+    pub fn relaxed_stack_check(&mut self, enable: bool) -> &mut Self {
+        self.relaxed_stack_check = Some(enable);
+        self
+    }
+
+    /// Configures the size, in bytes, of outgoing stack arguments that a
+    /// function which only makes tail calls may need while still having its
+    /// stack limit check relaxed by [`TailCallOptions::relaxed_stack_check`].
+    ///
+    /// Functions which tail call other functions with many parameters may need
+    /// to pass some arguments on the stack. That region is reserved once as
+    /// part of the stack limit check on entry to the function, so small
+    /// regions are cheap. By default any stack arguments at all keep the
+    /// function's standard stack limit check, which is reported through
+    /// [`TailCallOptions::on_deopt`] and [`Module::compile_stats`]. This
+    /// setting relaxes the check of functions with stack argument regions of
+    /// up to `bytes` bytes too.
+    ///
+    /// The value must be a multiple of the target's stack alignment (16 bytes
+    /// on most targets and 8 on s390x) and can be at most 255, otherwise
+    /// creating an [`Engine`] with this configuration will fail. This also
+    /// requires [`Config::wasm_tail_call`] to be enabled.
+    ///
+    /// By default this is `0`.
+    ///
+    /// [`Module::compile_stats`]: crate::Module::compile_stats
+    pub fn max_stack_args_bytes(&mut self, bytes: u32) -> &mut Self {
+        self.max_stack_args_bytes = Some(bytes);
+        self
+    }
+
+    /// Configures the number of bytes of stack, at least, that
+    /// [`TailCallOptions::relaxed_stack_check`] must save for a function for
+    /// its frame to be optimized.
+    ///
+    /// Optimizing a frame saves the room its stack limit check would otherwise
+    /// reserve for a callee's frame setup, which is small on most targets.
+    /// Functions for which that saving is below `bytes` keep a standard frame
+    /// instead. They're still classified as
+    /// [`CallClass::TailCallOnly`](crate::CallClass::TailCallOnly), since that
+    /// describes the calls they make, but are reported as not optimized with
+    /// [`TailCallDeoptReason::BelowSavingsThreshold`], for example through
+    /// [`TailCallOptions::on_deopt`], and so also fail compilation when
+    /// [`TailCallOptions::require_optimization`] is enabled.
+    ///
+    /// The value can be at most 255, otherwise creating an [`Engine`] with
+    /// this configuration will fail. This also requires
+    /// [`Config::wasm_tail_call`] to be enabled.
+    ///
+    /// By default this is `0`, meaning that frames are optimized whenever
+    /// possible.
+    pub fn min_frame_savings(&mut self, bytes: u32) -> &mut Self {
+        self.min_frame_savings = Some(bytes);
+        self
+    }
+
+    /// Configures the size, in bytes, of the largest WebAssembly function body
+    /// whose frame [`TailCallOptions::relaxed_stack_check`] optimizes.
+    ///
+    /// Very large functions, such as generated dispatch functions, gain little
+    /// from having their frames optimized relative to the work they do. With
+    /// `Some(bytes)`, functions which only make tail calls and whose body in
+    /// the Wasm binary is larger than `bytes` keep a standard frame instead.
+    /// This is the size of the body in the Wasm binary, not the size of its
+    /// compiled code, because the decision has to be made before the function
+    /// is compiled. Such functions are still classified as
+    /// [`CallClass::TailCallOnly`](crate::CallClass::TailCallOnly), since
+    /// that describes the calls they make, but are reported as not optimized
+    /// with [`TailCallDeoptReason::FunctionTooLarge`], for example through
+    /// [`TailCallOptions::on_deopt`], and so also fail compilation when
+    /// [`TailCallOptions::require_optimization`] is enabled. Functions marked
+    /// [`TailCallHint::Force`] in a `tail_call_hints` section are optimized
+    /// regardless of their size.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `None`, meaning that functions of any size are
+    /// optimized.
+    pub fn max_optimized_body_bytes(&mut self, bytes: Option<u32>) -> &mut Self {
+        self.max_optimized_body_bytes = Some(bytes);
+        self
+    }
+
+    /// Configures whether compiling a module fails if any function which only
+    /// makes tail calls couldn't have its frame optimized.
+    ///
+    /// By default, functions which only make tail calls but can't be handled
+    /// by [`TailCallOptions::relaxed_stack_check`], for example because they
+    /// need stack slots or too much space for outgoing stack arguments,
+    /// silently fall back to a standard frame (which can be observed with
+    /// [`TailCallOptions::on_deopt`]). When this is enabled,
+    /// [`Module::new`](crate::Module::new) and other methods which compile
+    /// WebAssembly instead return an error naming the first such function and
+    /// the [`TailCallDeoptReason`](crate::TailCallDeoptReason) the optimization
+    /// didn't apply.
+    ///
+    /// A function which only makes tail calls in WebAssembly can also end up
+    /// making regular calls once compiled, for example when
+    /// [`TailCallOptions::profiling`] or [`TailCallOptions::hooks`] add a call
+    /// into the host before its tail calls or when it uses instructions like
+    /// `memory.grow` which are implemented by calling into the runtime. Such
+    /// functions also fail to compile with this enabled.
+    ///
+    /// Note that on targets where the optimization isn't implemented at all
+    /// every function which only makes tail calls fails to compile with this
+    /// enabled.
+    ///
+    /// This requires [`Config::wasm_tail_call`] and
+    /// [`TailCallOptions::relaxed_stack_check`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn require_optimization(&mut self, enable: bool) -> &mut Self {
+        self.require_optimization = Some(enable);
+        self
+    }
+
+    /// Configures whether self-recursive tail calls are compiled as loops.
+    ///
+    /// When this is enabled, a function whose only calls are `return_call`s
+    /// to itself has each of those calls compiled as a jump back to the start
+    /// of the function, after reassigning its parameters and resetting its
+    /// locals, rather than as a tail call. This removes the overhead of the
+    /// call itself, which can be a measurable speedup for hot tail-recursive
+    /// loops. Such functions are reported as
+    /// [`CallClass::SelfTailLoop`](crate::CallClass::SelfTailLoop) rather than
+    /// [`CallClass::TailCallOnly`](crate::CallClass::TailCallOnly).
+    ///
+    /// Each iteration of the loop still consumes fuel, checks for epoch
+    /// interruption, is counted by [`TailCallOptions::fuel`] and
+    /// [`TailCallOptions::profiling`], and is observed by
+    /// [`TailCallOptions::hooks`], just like the tail call it replaces.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn self_recursion_loop(&mut self, enable: bool) -> &mut Self {
+        self.self_recursion_loop = Some(enable);
+        self
+    }
+
+    /// Configures a limit on the number of tail calls that WebAssembly may make
+    /// within a [`Store`](crate::Store), as a debugging aid for runaway tail
+    /// recursion.
+    ///
+    /// When set to `Some(n)` each `return_call`, `return_call_indirect`, and
+    /// `return_call_ref` executed consumes one unit of "tail-call fuel" from
+    /// the store, and once `n` tail calls have been made in a store the next
+    /// one traps with [`Trap::TailCallFuelExhausted`](crate::Trap::TailCallFuelExhausted). This is a
+    /// bounded way to detect an accidentally infinite tail-recursive loop
+    /// which would otherwise run forever without growing the stack.
+    ///
+    /// Each store starts with `n` units of tail-call fuel. It is never
+    /// refilled automatically, but it can be reset with
+    /// [`Store::set_tail_call_fuel`](crate::Store::set_tail_call_fuel) and the
+    /// number of tail calls made since then is available through
+    /// [`Store::tail_call_fuel_consumed`](crate::Store::tail_call_fuel_consumed).
+    ///
+    /// When `None` no tail-call fuel is consumed and no instrumentation is
+    /// added to compiled code.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `None`.
+    pub fn fuel(&mut self, fuel: Option<u64>) -> &mut Self {
+        self.fuel = fuel;
+        self
+    }
+
+    /// Configures a limit on how deeply WebAssembly may nest regular,
+    /// non-tail calls, independently of [`Config::max_wasm_stack`].
+    ///
+    /// Tail calls replace the calling function's frame rather than nesting
+    /// within it, so a tail-recursive loop can run indefinitely without
+    /// growing the stack, while regular recursion grows it with every call.
+    /// [`Config::max_wasm_stack`] has to be large enough for the deepest
+    /// legitimate regular recursion, which can hide accidentally unbounded
+    /// regular recursion until it has used all of that stack. This instead
+    /// bounds the number of regular calls (`call`, `call_indirect`, and
+    /// `call_ref`) that may be in progress at once to `limit`, and the next
+    /// regular call once that many are in progress traps with
+    /// [`Trap::RegularCallDepthExceeded`](crate::Trap::RegularCallDepthExceeded).
+    /// Tail calls don't count towards the limit, so tail-recursive loops
+    /// remain unbounded.
+    ///
+    /// The depth is counted from where the host calls into WebAssembly, so
+    /// each such call may nest up to `limit` regular calls, including calls
+    /// to host functions. The depth is tracked by instrumenting every regular
+    /// call in compiled code, which has some runtime overhead, and none is
+    /// added when this isn't configured.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default there is no limit.
+    pub fn stack_limit(&mut self, limit: usize) -> &mut Self {
+        self.stack_limit = Some(limit);
+        self
+    }
+
+    /// Configures whether the number of tail calls made by each WebAssembly
+    /// function is counted at runtime.
+    ///
+    /// When enabled each `return_call`, `return_call_indirect`, and
+    /// `return_call_ref` increments a counter for the function making the
+    /// call before the call is made. The counters are kept per instance, and
+    /// therefore per [`Store`](crate::Store), and can be read with
+    /// [`Instance::tail_call_counts`](crate::Instance::tail_call_counts). A
+    /// total across all stores is also kept per engine and can be read with
+    /// [`Engine::tail_calls_executed`]. This is intended for performance
+    /// analysis, for example to find out which transitions of a state machine
+    /// implemented with tail calls dominate a workload.
+    ///
+    /// Counting requires calling into the host at each tail call, so this adds
+    /// significant overhead to tail calls and should not be enabled in
+    /// production. Calling into the host is also a regular call, so functions
+    /// that make tail calls don't have their stack checks relaxed as described
+    /// in [`TailCallOptions::relaxed_stack_check`] when this is enabled. When
+    /// disabled no instrumentation is added to compiled code.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn profiling(&mut self, enable: bool) -> &mut Self {
+        self.profiling = Some(enable);
+        self
+    }
+
+    /// Configures whether WebAssembly's direct tail calls can be observed
+    /// with [`Store::tail_call_hook`](crate::Store::tail_call_hook).
+    ///
+    /// When enabled each `return_call` checks whether the store has a
+    /// tail-call hook installed and, if so, calls into the host to invoke it
+    /// before the tail call is made. Stores without a hook don't leave wasm,
+    /// unless [`Config::coredump_on_trap`] is also enabled, in which case each
+    /// `return_call` calls into the host to record the tail call for the
+    /// coredump. This is intended for tracing, for example to record the exact
+    /// sequence of transitions made by a state machine implemented with tail
+    /// calls.
+    ///
+    /// Only `return_call` is observed: the callees of `return_call_indirect`
+    /// and `return_call_ref` aren't known by index, so they don't invoke the
+    /// hook.
+    ///
+    /// Calling into the host is a regular call, so functions that make direct
+    /// tail calls don't have their stack checks relaxed as described in
+    /// [`TailCallOptions::relaxed_stack_check`] when this is enabled. When
+    /// disabled no instrumentation is added to compiled code and installing
+    /// a tail-call hook fails.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn hooks(&mut self, enable: bool) -> &mut Self {
+        self.hooks = Some(enable);
+        self
+    }
+
+    /// Configures whether compiled code checks the integrity of the frame
+    /// reused by each tail call before making it.
+    ///
+    /// Each tail call tears down the frame of the function making it and
+    /// builds its callee's frame in the same region of the stack, so a bug in
+    /// the compiler or runtime which clobbers a frame in a chain of tail calls
+    /// would otherwise only show up much later, if at all, since the frame is
+    /// never returned through. When this is enabled each function stores a
+    /// fixed canary value in a slot of its own frame on entry, and each
+    /// `return_call`, `return_call_indirect`, and `return_call_ref` verifies
+    /// that the slot still holds the canary before tearing the frame down and
+    /// jumping to its callee. If it doesn't the tail call traps with
+    /// [`Trap::TailCallFrameCorruption`](crate::Trap::TailCallFrameCorruption)
+    /// instead.
+    ///
+    /// This is a debugging aid: it adds a store to every function prologue
+    /// and a load and a comparison to every tail call, along with 8 bytes to
+    /// every frame, and should not be needed in production. When disabled no
+    /// instrumentation is added to compiled code.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn debug_assertions(&mut self, enable: bool) -> &mut Self {
+        self.debug_assertions = Some(enable);
+        self
+    }
+
+    /// Configures a limit on the number of consecutive tail calls that
+    /// WebAssembly may make, to keep a tail-recursive loop from running
+    /// indefinitely.
+    ///
+    /// When set to `Some(n)`, up to `n` `return_call`, `return_call_indirect`,
+    /// and `return_call_ref` instructions may execute one after the other,
+    /// and the next one traps with
+    /// [`Trap::TailCallChainTooLong`](crate::Trap::TailCallChainTooLong). The
+    /// chain is broken, and counting starts again from zero, whenever a
+    /// regular call (`call`, `call_indirect`, or `call_ref`) returns, including
+    /// calls to host functions, on entry to an exception handler, and
+    /// whenever the host calls into WebAssembly. Programs whose tail calls
+    /// form bounded chains between regular calls are therefore unaffected by
+    /// a large enough limit, while a tail loop which never returns or calls
+    /// anything else is stopped after `n` iterations.
+    ///
+    /// Unlike [`TailCallOptions::fuel`], which limits the total number of
+    /// tail calls made in a store, this only limits chains of them. Unlike
+    /// [`Config::epoch_interruption`] it's deterministic and needs no timer to
+    /// increment the epoch, but it only catches loops made of tail calls,
+    /// always traps rather than optionally yielding, and doesn't stop loops
+    /// made of branches or regular calls. Epoch interruption or
+    /// [`Config::consume_fuel`] remain the way to bound execution time in
+    /// general.
+    ///
+    /// The limit is compiled into the code, which checks it before each tail
+    /// call and resets the count after each regular call, so modules must be
+    /// compiled with the same limit they're run with. When `None` no
+    /// instrumentation is added to compiled code.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `None`.
+    pub fn max_chain_length(&mut self, length: Option<u64>) -> &mut Self {
+        self.max_chain_length = Some(length);
+        self
+    }
+
+    /// Configures the amount of fuel consumed by each tail call when
+    /// [`Config::consume_fuel`] is enabled.
+    ///
+    /// Each `return_call`, `return_call_indirect`, and `return_call_ref`
+    /// consumes `cost` units of fuel, in place of the single unit consumed by
+    /// a regular call and most other instructions. Since a tail call doesn't
+    /// grow the stack, a metering model may want to charge less for it, for
+    /// example `0` to make tail-recursive loops cost no more than loops
+    /// written with branches, or more to penalize workloads which make heavy
+    /// use of tail calls. This is unrelated to [`TailCallOptions::fuel`],
+    /// which limits the number of tail calls independently of fuel.
+    ///
+    /// The cost is compiled into the code, so modules must be compiled with
+    /// the same cost they're run with. This has no effect unless
+    /// [`Config::consume_fuel`] is enabled.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail, and `cost`
+    /// must not exceed `i64::MAX`.
+    ///
+    /// By default this is `1`, the same as a regular call.
+    pub fn fuel_cost(&mut self, cost: u64) -> &mut Self {
+        self.fuel_cost = Some(cost);
+        self
+    }
+
+    /// Configures whether host functions can tell whether they were called
+    /// from WebAssembly with a tail call.
+    ///
+    /// When enabled, compiled code records, before each call which may reach
+    /// a host function, whether that call is a tail call (`return_call`,
+    /// `return_call_indirect`, or `return_call_ref`) or a regular call
+    /// (`call`, `call_indirect`, or `call_ref`), and
+    /// [`Caller::is_tail_called`](crate::Caller::is_tail_called) reports it.
+    /// Direct calls to functions defined in the same module never reach a
+    /// host function, so aren't instrumented.
+    ///
+    /// The instrumentation is compiled into the code, so modules must be
+    /// compiled with the same setting they're run with. When disabled
+    /// [`Caller::is_tail_called`](crate::Caller::is_tail_called) always
+    /// returns `false`.
+    ///
+    /// This requires [`Config::wasm_tail_call`] to be enabled, otherwise
+    /// creating an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn host_tracking(&mut self, enable: bool) -> &mut Self {
+        self.host_tracking = Some(enable);
+        self
+    }
+
+    /// Configures whether the frame torn down by each tail call is zeroed
+    /// before jumping to the callee.
+    ///
+    /// A tail call replaces the frame of the function making it with the
+    /// frame of its callee, in the same region of the stack. Wasm itself can't
+    /// read a frame's leftover contents, but as hardening against bugs which
+    /// might expose them, when this is enabled each `return_call`,
+    /// `return_call_indirect`, and `return_call_ref` overwrites the caller's
+    /// spill slots, stack slots, saved registers, and outgoing arguments with
+    /// zeros after restoring its registers but before freeing the frame, so
+    /// no stale data from the previous logical frame is left where the
+    /// callee's frame is built. The saved frame pointer and return address,
+    /// which the callee returns through, are left intact. Any canary checked
+    /// by [`TailCallOptions::debug_assertions`] is checked before it's zeroed.
+    ///
+    /// This adds a short loop to each tail call which runs once for every 8
+    /// bytes of the function's frame on x86_64, or every 16 bytes on aarch64.
+    /// The zeroing is compiled into the code, so modules must be compiled with
+    /// the same setting they're run with.
+    ///
+    /// This is only supported when compiling for x86_64 and aarch64. It also
+    /// requires [`Config::wasm_tail_call`] to be enabled. Otherwise creating
+    /// an [`Engine`] with this configuration will fail.
+    ///
+    /// By default this is `false`.
+    pub fn zero_unused_frame(&mut self, enable: bool) -> &mut Self {
+        self.zero_unused_frame = Some(enable);
+        self
+    }
+
+    /// Registers a callback which is invoked when a function which only makes
+    /// tail calls couldn't have its frame fully optimized.
+    ///
+    /// When [`TailCallOptions::relaxed_stack_check`] is enabled the compiler
+    /// attempts to treat functions which only make tail calls like leaf
+    /// functions. This isn't always possible, for example if the function
+    /// needs stack space for stack slots or for outgoing stack arguments, in
+    /// which case it falls back to a standard frame. The provided `callback`
+    /// is invoked with a [`TailCallDeoptInfo`] describing each such function
+    /// and the reason the optimization didn't apply.
+    ///
+    /// The info also includes the position of the function's first tail call
+    /// in the original wasm file and, when the module has DWARF debug
+    /// information which is parsed because [`Config::wasm_backtrace_details`]
+    /// or [`Config::debug_info`] is enabled, the source file, line, and column
+    /// of that tail call.
+    ///
+    /// The callback is invoked synchronously on the thread calling
+    /// [`Module::new`](crate::Module::new) (or any other method which compiles
+    /// WebAssembly), after all functions have been compiled and before that
+    /// method returns. It is not invoked for modules which are loaded from the
+    /// cache or deserialized, since no compilation happens in that case.
+    ///
+    /// By default no callback is registered.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn on_deopt(
+        &mut self,
+        callback: impl Fn(TailCallDeoptInfo) + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.deopt_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Configures whether the tail call classification of each compiled
+    /// function is logged through the [`log`] crate.
+    ///
+    /// When enabled, compiling a module logs one line per defined function
+    /// with its index, its name from the module's name section if it has one,
+    /// its [`CallClass`](crate::CallClass), and whether its frame was optimized
+    /// for tail calls. Functions that only make tail calls but whose frames
+    /// couldn't be optimized are logged at the `debug` level along with the
+    /// reason, the same information passed to
+    /// [`TailCallOptions::on_deopt`]; all other functions are logged at the
+    /// `trace` level. Nothing is logged for modules which are loaded from the
+    /// cache or deserialized, since no compilation happens in that case.
+    ///
+    /// This is `false` by default.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn report_to_log(&mut self, enable: bool) -> &mut Self {
+        self.report_to_log = enable;
+        self
+    }
+
+    /// Registers a callback which can override how the functions of modules
+    /// are classified by the calls they make, for the purposes of
+    /// [`TailCallOptions::relaxed_stack_check`].
+    ///
+    /// The `classifier` is invoked during compilation with the index of each
+    /// defined function and the [`CallClass`](crate::CallClass) determined
+    /// from its body, and returns the class the function should be compiled
+    /// as. Returning the class it was given leaves the function unchanged.
+    ///
+    /// Only changes which are always safe are permitted: a function which only
+    /// makes tail calls may be reclassified as making regular calls, that is
+    /// as [`Regular`](crate::CallClass::Regular) or
+    /// [`Mixed`](crate::CallClass::Mixed), in which case it keeps a standard
+    /// frame as though it were marked [`TailCallHint::Forbid`]. Any other
+    /// change, such as claiming that a function which makes regular calls only
+    /// makes tail calls, would let its frame be optimized when that's unsafe,
+    /// and causes compilation to fail with an error, as does reclassifying a
+    /// function that's marked [`TailCallHint::Force`]. Functions keep
+    /// reporting the class determined from their body, for example through
+    /// [`Module::function_call_classes`](crate::Module::function_call_classes),
+    /// since that still describes the calls they make.
+    ///
+    /// The classifier is invoked on the thread compiling the module, possibly
+    /// more than once for the same function if the module is compiled more
+    /// than once. It isn't part of the key used by the compilation cache, so
+    /// artifacts produced with different classifiers can't be told apart by
+    /// the cache.
+    ///
+    /// By default no classifier is registered.
+    #[cfg(any(feature = "cranelift", feature = "winch"))]
+    pub fn classifier(
+        &mut self,
+        classifier: impl Fn(
+            wasmtime_environ::FuncIndex,
+            wasmtime_environ::CallClass,
+        ) -> wasmtime_environ::CallClass
+        + Send
+        + Sync
+        + 'static,
+    ) -> &mut Self {
+        self.classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Configures whether modules which make tail calls are rejected.
+    ///
+    /// When enabled, creating a [`Module`](crate::Module), including one
+    /// within a component, fails if any function it defines contains a
+    /// `return_call`, `return_call_indirect`, or `return_call_ref`
+    /// instruction. The error names the first such function. This is intended
+    /// as a policy gate for environments which mustn't run modules using tail
+    /// calls, and unlike disabling [`Config::wasm_tail_call`] it produces an
+    /// error which says why the module was rejected rather than a validation
+    /// error.
+    ///
+    /// The check is made each time a module is created, so it applies equally
+    /// to modules compiled with [`Module::new`](crate::Module::new), loaded
+    /// from the compilation cache, or loaded with
+    /// [`Module::deserialize`](crate::Module::deserialize), and it doesn't
+    /// affect the compiled artifact.
+    ///
+    /// By default this is `false`.
+    pub fn reject_modules(&mut self, reject: bool) -> &mut Self {
+        self.reject_modules = reject;
+        self
+    }
+}
+
+impl fmt::Debug for TailCallOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut f = f.debug_struct("TailCallOptions");
+        f.field("relaxed_stack_check", &self.relaxed_stack_check)
+            .field("max_stack_args_bytes", &self.max_stack_args_bytes)
+            .field("min_frame_savings", &self.min_frame_savings)
+            .field("max_optimized_body_bytes", &self.max_optimized_body_bytes)
+            .field("require_optimization", &self.require_optimization)
+            .field("self_recursion_loop", &self.self_recursion_loop)
+            .field("fuel", &self.fuel)
+            .field("stack_limit", &self.stack_limit)
+            .field("profiling", &self.profiling)
+            .field("hooks", &self.hooks)
+            .field("debug_assertions", &self.debug_assertions)
+            .field("max_chain_length", &self.max_chain_length)
+            .field("fuel_cost", &self.fuel_cost)
+            .field("host_tracking", &self.host_tracking)
+            .field("zero_unused_frame", &self.zero_unused_frame);
+        #[cfg(any(feature = "cranelift", feature = "winch"))]
+        f.field("report_to_log", &self.report_to_log);
+        f.field("reject_modules", &self.reject_modules)
+            .finish_non_exhaustive()
+    }
+}
+
+/// How well tail calls are supported by a [`Config`], as returned by
+/// [`Config::supports_tail_call_optimization`].
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TailCallSupport {
+    /// Tail calls are supported and the frames of functions which only make
+    /// tail calls are optimized.
+    FullyOptimized,
+
+    /// Tail calls are supported and don't grow the stack, but no frames are
+    /// optimized.
+    SemanticsOnly,
+
+    /// Tail calls can't be used.
+    Unsupported,
+}
+
 /// Describe the tri-state configuration of keys such as MPK or PAGEMAP_SCAN.
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
 pub enum Enabled {
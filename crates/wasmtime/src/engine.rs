@@ -6,6 +6,8 @@ pub use crate::runtime::code_memory::CustomCodeMemory;
 use crate::runtime::type_registry::TypeRegistry;
 #[cfg(feature = "runtime")]
 use crate::runtime::vm::GcRuntime;
+#[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+use crate::sync::RwLock;
 use alloc::sync::Arc;
 use core::ptr::NonNull;
 #[cfg(target_has_atomic = "64")]
@@ -16,6 +18,8 @@ use object::write::{Object, StandardSegment};
 use std::{fs::File, path::Path};
 use wasmparser::WasmFeatures;
 use wasmtime_environ::{FlagValue, ObjectKind, TripleExt, Tunables};
+#[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+use wasmtime_slab::{Id as SlabId, Slab};
 
 mod serialization;
 
@@ -62,6 +66,8 @@ struct EngineInner {
     signatures: TypeRegistry,
     #[cfg(all(feature = "runtime", target_has_atomic = "64"))]
     epoch: AtomicU64,
+    #[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+    tail_call_counters: TailCallCounters,
 
     /// One-time check of whether the compiler's settings, if present, are
     /// compatible with the native host.
@@ -140,6 +146,8 @@ impl Engine {
                 signatures: TypeRegistry::new(),
                 #[cfg(all(feature = "runtime", target_has_atomic = "64"))]
                 epoch: AtomicU64::new(0),
+                #[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+                tail_call_counters: TailCallCounters::default(),
                 compatible_with_native_host: Default::default(),
                 config,
                 tunables,
@@ -477,6 +485,10 @@ information about this check\
             | "probestack_size_log2" // probestack above asserted disabled
             | "regalloc" // shouldn't change semantics
             | "enable_incremental_compilation_cache_checks" // shouldn't change semantics
+            | "tail_call_relaxed_stack_check" // checked via tunables
+            | "tail_call_max_stack_args_bytes" // checked via tunables
+            | "tail_call_min_frame_savings" // checked via tunables
+            | "tail_call_zero_frame" // checked via tunables
             | "enable_atomics" => return Ok(()),
 
             // Everything else is unknown and needs to be added somewhere to
@@ -668,6 +680,33 @@ impl Engine {
             .compile_module_serialized()
     }
 
+    /// Same as [`Engine::precompile_module`] except that it also writes a
+    /// report of how each function's tail calls were compiled to `report`.
+    ///
+    /// The report is written as JSON in the format described by
+    /// [`TailCallReport`](crate::TailCallReport), which is the same as the
+    /// output of `wasmtime compile --emit-tail-call-report --format json`. It
+    /// describes the compiled artifact that is returned, so it can be archived
+    /// alongside that artifact and diffed across toolchain versions.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if compilation fails or if writing the report to
+    /// `report` fails.
+    #[cfg(feature = "tail-call-report")]
+    pub fn precompile_module_with_report(
+        &self,
+        bytes: &[u8],
+        report: &mut dyn std::io::Write,
+    ) -> Result<Vec<u8>> {
+        let (compiled, tail_call_report) = crate::CodeBuilder::new(self)
+            .wasm_binary_or_text(bytes, None)?
+            .compile_module_serialized_with_report()?;
+        writeln!(report, "{}", tail_call_report.to_json())
+            .context("failed to write tail-call report")?;
+        Ok(compiled)
+    }
+
     /// Same as [`Engine::precompile_module`] except for a
     /// [`Component`](crate::component::Component)
     #[cfg(feature = "component-model")]
@@ -808,12 +847,70 @@ impl Engine {
         self.inner.epoch.fetch_add(1, Ordering::Relaxed);
     }
 
+    /// Returns the total number of tail calls executed by WebAssembly code in
+    /// all stores using this engine.
+    ///
+    /// Tail calls are only counted when
+    /// [`TailCallOptions::profiling`](crate::TailCallOptions::profiling) is
+    /// enabled, otherwise this always returns zero. This is the sum of the
+    /// per-function counts reported by
+    /// [`Instance::tail_call_counts`](crate::Instance::tail_call_counts)
+    /// across every instance ever created with this engine, and it isn't
+    /// reset by
+    /// [`Instance::take_tail_call_stats`](crate::Instance::take_tail_call_stats).
+    ///
+    /// ## Atomicity
+    ///
+    /// Each instance counts its own tail calls, so executing them never
+    /// contends with other threads, and this sums those counts along with
+    /// the counts of instances which have already been dropped. No tail calls
+    /// are ever lost, but the sum isn't synchronized with execution: a read
+    /// while other threads are executing tail calls may not yet observe their
+    /// most recent ones, and successive reads never go backwards other than
+    /// when the count wraps around on overflow.
+    #[cfg(target_has_atomic = "64")]
+    pub fn tail_calls_executed(&self) -> u64 {
+        let counters = &self.inner.tail_call_counters;
+        let live = counters.live.read();
+        live.iter().fold(
+            counters.retired.load(Ordering::Relaxed),
+            |sum, (_, count)| sum.wrapping_add(count.load(Ordering::Relaxed)),
+        )
+    }
+
+    /// Registers a new counter for the tail calls executed by an instance,
+    /// which is included in [`Engine::tail_calls_executed`].
+    #[cfg(target_has_atomic = "64")]
+    pub(crate) fn new_tail_call_counter(&self) -> TailCallCounter {
+        let count = Arc::new(AtomicU64::new(0));
+        let id = self
+            .inner
+            .tail_call_counters
+            .live
+            .write()
+            .alloc(count.clone());
+        TailCallCounter {
+            engine: self.clone(),
+            id,
+            count,
+        }
+    }
+
     /// Returns a [`std::hash::Hash`] that can be used to check precompiled WebAssembly compatibility.
     ///
     /// The outputs of [`Engine::precompile_module`] and [`Engine::precompile_component`]
     /// are compatible with a different [`Engine`] instance only if the two engines use
     /// compatible [`Config`]s. If this Hash matches between two [`Engine`]s then binaries
     /// from one are guaranteed to deserialize in the other.
+    ///
+    /// This accounts for all configuration which affects compiled code, including
+    /// [`Config::wasm_tail_call`] and the options which tune how tail calls are compiled,
+    /// such as [`TailCallOptions::relaxed_stack_check`]. The exception is
+    /// [`TailCallOptions::classifier`], a callback which can only keep frames that would
+    /// otherwise be optimized, so artifacts compiled with or without it are interchangeable.
+    ///
+    /// [`TailCallOptions::relaxed_stack_check`]: crate::TailCallOptions::relaxed_stack_check
+    /// [`TailCallOptions::classifier`]: crate::TailCallOptions::classifier
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     pub fn precompile_compatibility_hash(&self) -> impl std::hash::Hash + '_ {
         crate::compile::HashedEngineCompileEnv(self)
@@ -960,6 +1057,50 @@ impl Engine {
     }
 }
 
+/// The counters behind [`Engine::tail_calls_executed`].
+#[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+#[derive(Default)]
+struct TailCallCounters {
+    /// The counter of each live instance.
+    live: RwLock<Slab<Arc<AtomicU64>>>,
+    /// The total of the counters of instances which have been dropped.
+    retired: AtomicU64,
+}
+
+/// The count of tail calls executed by a single instance, registered with its
+/// engine until it's dropped.
+#[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+pub(crate) struct TailCallCounter {
+    engine: Engine,
+    id: SlabId,
+    count: Arc<AtomicU64>,
+}
+
+#[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+impl TailCallCounter {
+    /// Counts one tail call.
+    pub(crate) fn increment(&self) {
+        // Only the instance owning this counter increments it, so this doesn't
+        // need to be an atomic read-modify-write.
+        let count = self.count.load(Ordering::Relaxed);
+        self.count.store(count.wrapping_add(1), Ordering::Relaxed);
+    }
+}
+
+#[cfg(all(feature = "runtime", target_has_atomic = "64"))]
+impl Drop for TailCallCounter {
+    fn drop(&mut self) {
+        let counters = &self.engine.inner.tail_call_counters;
+        // Retire the count while holding the lock so that a concurrent sum
+        // sees it exactly once.
+        let mut live = counters.live.write();
+        live.dealloc(self.id);
+        counters
+            .retired
+            .fetch_add(self.count.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+}
+
 /// A weak reference to an [`Engine`].
 #[derive(Clone)]
 pub struct EngineWeak {
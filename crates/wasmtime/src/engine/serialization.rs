@@ -292,6 +292,22 @@ impl Metadata<'_> {
             inlining_intra_module,
             inlining_small_callee_size,
             inlining_sum_size_threshold,
+            inlining_tail_call_only,
+            tail_call_relaxed_stack_check,
+            tail_call_max_stack_args_bytes,
+            tail_call_min_frame_savings,
+            tail_call_max_optimized_body_bytes,
+            tail_call_require_optimization,
+            tail_call_self_recursion_loop,
+            tail_call_profiling,
+            tail_call_hooks,
+            tail_call_fuel,
+            tail_call_stack_limit,
+            tail_call_debug_assertions,
+            max_tail_call_chain_length,
+            tail_call_fuel_cost,
+            tail_call_host_tracking,
+            tail_call_zero_unused_frame,
 
             // This doesn't affect compilation, it's just a runtime setting.
             memory_reservation_for_growth: _,
@@ -371,6 +387,90 @@ impl Metadata<'_> {
             other.inlining_sum_size_threshold,
             "function inlining sum-size threshold",
         )?;
+        Self::check_bool(
+            inlining_tail_call_only,
+            other.inlining_tail_call_only,
+            "function inlining of tail-call-only callees",
+        )?;
+        Self::check_bool(
+            tail_call_relaxed_stack_check,
+            other.tail_call_relaxed_stack_check,
+            "tail-call relaxed stack check",
+        )?;
+        Self::check_int(
+            tail_call_max_stack_args_bytes,
+            other.tail_call_max_stack_args_bytes,
+            "tail-call maximum stack arguments size",
+        )?;
+        Self::check_int(
+            tail_call_min_frame_savings,
+            other.tail_call_min_frame_savings,
+            "tail-call minimum frame savings",
+        )?;
+        if tail_call_max_optimized_body_bytes != other.tail_call_max_optimized_body_bytes {
+            let desc = |bytes: Option<u32>| match bytes {
+                Some(bytes) => format!("a tail-call maximum optimized body size of {bytes}"),
+                None => "no tail-call maximum optimized body size".to_string(),
+            };
+            bail!(
+                "Module was compiled with {} but {} is expected for the host",
+                desc(tail_call_max_optimized_body_bytes),
+                desc(other.tail_call_max_optimized_body_bytes),
+            );
+        }
+        Self::check_bool(
+            tail_call_require_optimization,
+            other.tail_call_require_optimization,
+            "required tail-call stack check relaxation",
+        )?;
+        Self::check_bool(
+            tail_call_self_recursion_loop,
+            other.tail_call_self_recursion_loop,
+            "self-recursive tail calls as loops",
+        )?;
+        Self::check_bool(tail_call_fuel, other.tail_call_fuel, "tail-call fuel")?;
+        Self::check_bool(
+            tail_call_profiling,
+            other.tail_call_profiling,
+            "tail-call profiling",
+        )?;
+        Self::check_bool(tail_call_hooks, other.tail_call_hooks, "tail-call hooks")?;
+        Self::check_bool(
+            tail_call_stack_limit,
+            other.tail_call_stack_limit,
+            "regular call depth limit",
+        )?;
+        Self::check_bool(
+            tail_call_debug_assertions,
+            other.tail_call_debug_assertions,
+            "tail-call debug assertions",
+        )?;
+        if max_tail_call_chain_length != other.max_tail_call_chain_length {
+            let desc = |length: Option<u64>| match length {
+                Some(length) => format!("a maximum tail call chain length of {length}"),
+                None => "no maximum tail call chain length".to_string(),
+            };
+            bail!(
+                "Module was compiled with {} but {} is expected for the host",
+                desc(max_tail_call_chain_length),
+                desc(other.max_tail_call_chain_length),
+            );
+        }
+        Self::check_int(
+            tail_call_fuel_cost,
+            other.tail_call_fuel_cost,
+            "tail call fuel cost",
+        )?;
+        Self::check_bool(
+            tail_call_host_tracking,
+            other.tail_call_host_tracking,
+            "tail-call host tracking",
+        )?;
+        Self::check_bool(
+            tail_call_zero_unused_frame,
+            other.tail_call_zero_unused_frame,
+            "zeroing of frames torn down by tail calls",
+        )?;
         Self::check_intra_module_inlining(inlining_intra_module, other.inlining_intra_module)?;
 
         Ok(())
@@ -438,7 +538,7 @@ impl Metadata<'_> {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{Cache, Config, Module, OptLevel};
+    use crate::{Cache, Config, Module, OptLevel, TailCallOptions};
     use std::{
         collections::hash_map::DefaultHasher,
         hash::{Hash, Hasher},
@@ -720,6 +820,203 @@ Caused by:
         Ok(())
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn cache_accounts_for_tail_call_options() -> Result<()> {
+        let td = TempDir::new()?;
+        let config_path = td.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            &format!(
+                "
+                    [cache]
+                    directory = '{}'
+                ",
+                td.path().join("cache").display()
+            ),
+        )?;
+        let wat = r#"
+            (module
+                (func $countdown (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $countdown
+                    end)
+            )
+        "#;
+
+        for (self_loop, class) in [
+            (false, crate::CallClass::TailCallOnly),
+            (true, crate::CallClass::SelfTailLoop),
+        ] {
+            let mut options = TailCallOptions::new();
+            options.self_recursion_loop(self_loop);
+            let mut cfg = Config::new();
+            cfg.wasm_tail_call(true)
+                .tail_call_options(options)
+                .cache(Some(Cache::from_file(Some(&config_path))?));
+            let engine = Engine::new(&cfg)?;
+            let cache_config = engine
+                .config()
+                .cache
+                .as_ref()
+                .expect("Missing cache config");
+            let module = Module::new(&engine, wat)?;
+            assert_eq!(cache_config.cache_hits(), 0);
+            assert_eq!(cache_config.cache_misses(), 1);
+            assert_eq!(module.function_call_classes()[0].1, class);
+            let module = Module::new(&engine, wat)?;
+            assert_eq!(cache_config.cache_hits(), 1);
+            assert_eq!(cache_config.cache_misses(), 1);
+            assert_eq!(module.function_call_classes()[0].1, class);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn tail_call_artifacts_require_tail_calls() -> Result<()> {
+        let mut cfg = Config::new();
+        cfg.wasm_tail_call(true);
+        let engine = Engine::new(&cfg)?;
+        let bytes = engine.precompile_module(
+            br#"
+                (module
+                    (func $f (param i32) (result i32)
+                        local.get 0
+                        return_call $f))
+            "#,
+        )?;
+
+        let mut cfg = Config::new();
+        cfg.wasm_tail_call(false);
+        let engine = Engine::new(&cfg)?;
+        match unsafe { Module::deserialize(&engine, &bytes) } {
+            Ok(_) => unreachable!(),
+            Err(e) => assert_eq!(
+                e.to_string(),
+                "Module was compiled with support for WebAssembly feature \
+                `tail_call` but it is not enabled for the host"
+            ),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn precompile_compatibility_key_accounts_for_tail_call_options() -> Result<()> {
+        fn hash_for_config(cfg: &Config) -> u64 {
+            let engine = Engine::new(cfg).expect("Config should be valid");
+            let mut hasher = DefaultHasher::new();
+            engine.precompile_compatibility_hash().hash(&mut hasher);
+            hasher.finish()
+        }
+        let wasm = br#"
+            (module
+                (func $f (param i32) (result i32)
+                    local.get 0
+                    return_call $f))
+        "#;
+
+        let mut base = Config::new();
+        base.wasm_tail_call(true);
+        let base_hash = hash_for_config(&base);
+        let bytes = Engine::new(&base)?.precompile_module(wasm)?;
+
+        let options: &[(&str, fn(&mut Config))] = &[
+            ("wasm_tail_call", |c| {
+                c.wasm_tail_call(false);
+            }),
+            ("inline_tail_call_only", |c| {
+                c.inline_tail_call_only(true);
+            }),
+        ];
+        let tail_call_options: &[(&str, fn(&mut TailCallOptions))] = &[
+            ("relaxed_stack_check", |o| {
+                o.relaxed_stack_check(false);
+            }),
+            ("max_stack_args_bytes", |o| {
+                o.max_stack_args_bytes(16);
+            }),
+            ("min_frame_savings", |o| {
+                o.min_frame_savings(16);
+            }),
+            ("max_optimized_body_bytes", |o| {
+                o.max_optimized_body_bytes(Some(1024));
+            }),
+            ("require_optimization", |o| {
+                o.require_optimization(true);
+            }),
+            ("self_recursion_loop", |o| {
+                o.self_recursion_loop(true);
+            }),
+            ("fuel", |o| {
+                o.fuel(Some(100));
+            }),
+            ("stack_limit", |o| {
+                o.stack_limit(100);
+            }),
+            ("profiling", |o| {
+                o.profiling(true);
+            }),
+            ("hooks", |o| {
+                o.hooks(true);
+            }),
+            ("debug_assertions", |o| {
+                o.debug_assertions(true);
+            }),
+            ("max_chain_length", |o| {
+                o.max_chain_length(Some(100));
+            }),
+            ("fuel_cost", |o| {
+                o.fuel_cost(2);
+            }),
+            ("host_tracking", |o| {
+                o.host_tracking(true);
+            }),
+            ("zero_unused_frame", |o| {
+                o.zero_unused_frame(true);
+            }),
+        ];
+        let check = |name: &str, cfg: &Config| -> Result<()> {
+            assert_ne!(hash_for_config(cfg), base_hash, "{name}");
+            let engine = Engine::new(cfg)?;
+            let err = match unsafe { Module::deserialize(&engine, &bytes) } {
+                Ok(_) => panic!("artifact deserialized despite a different `{name}`"),
+                Err(e) => e.to_string(),
+            };
+            assert!(err.starts_with("Module was compiled "), "{name}: {err}");
+            Ok(())
+        };
+        for (name, configure) in options {
+            let mut cfg = base.clone();
+            configure(&mut cfg);
+            check(name, &cfg)?;
+        }
+        for (name, configure) in tail_call_options {
+            if *name == "zero_unused_frame"
+                && !cfg!(any(target_arch = "x86_64", target_arch = "aarch64"))
+            {
+                continue;
+            }
+            let mut options = TailCallOptions::new();
+            configure(&mut options);
+            let mut cfg = base.clone();
+            cfg.tail_call_options(options);
+            check(name, &cfg)?;
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn precompile_compatibility_key_accounts_for_opt_level() {
         fn hash_for_config(cfg: &Config) -> u64 {
@@ -385,9 +385,13 @@ pub use compile::{CodeBuilder, CodeHint};
 mod config;
 mod engine;
 mod profiling_agent;
+#[cfg(feature = "tail-call-report")]
+mod tail_call_report;
 
 pub use crate::config::*;
 pub use crate::engine::*;
+#[cfg(feature = "tail-call-report")]
+pub use crate::tail_call_report::{TailCallReport, TailCallReportFunction};
 
 #[cfg(feature = "std")]
 mod sync_std;
@@ -71,7 +71,7 @@ pub trait ProfilingAgent: Send + Sync + 'static {
         let _ = interp;
     }
 
-    fn register_module(&self, code: &[u8], custom_name: &dyn Fn(usize) -> Option<String>) {
+    fn register_module(&self, code: &[u8], custom_name: &dyn Fn(usize, &str) -> Option<String>) {
         use object::{File, Object as _, ObjectSection, ObjectSymbol, SectionKind, SymbolKind};
 
         let image = match File::parse(code) {
@@ -104,7 +104,7 @@ pub trait ProfilingAgent: Send + Sync + 'static {
             }
             if let Ok(name) = sym.name() {
                 let owned;
-                let name = match custom_name(address as usize) {
+                let name = match custom_name(address as usize, name) {
                     Some(name) => {
                         owned = name;
                         &owned
@@ -127,5 +127,5 @@ struct NullProfilerAgent;
 
 impl ProfilingAgent for NullProfilerAgent {
     fn register_function(&self, _name: &str, _code: &[u8]) {}
-    fn register_module(&self, _code: &[u8], _custom_name: &dyn Fn(usize) -> Option<String>) {}
+    fn register_module(&self, _code: &[u8], _custom_name: &dyn Fn(usize, &str) -> Option<String>) {}
 }
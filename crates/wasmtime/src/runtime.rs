@@ -79,12 +79,15 @@ pub use exception::*;
 pub use externals::*;
 pub use func::*;
 pub use gc::*;
-pub use instance::{Instance, InstancePre};
+pub use instance::{Instance, InstancePre, TailCallStats};
 pub use instantiate::CompiledModule;
 pub use limits::*;
 pub use linker::*;
 pub use memory::*;
-pub use module::{Module, ModuleExport};
+pub use module::{
+    CallClass, CallConv, CallEdge, CallKind, CallTarget, CompileWarning, FrameLayout, Module,
+    ModuleCompileStats, ModuleExport,
+};
 pub use resources::*;
 #[cfg(all(feature = "async", feature = "call-hook"))]
 pub use store::CallHookHandler;
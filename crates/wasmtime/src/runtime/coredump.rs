@@ -2,9 +2,14 @@ use crate::hash_map::HashMap;
 use crate::prelude::*;
 use crate::{
     AsContextMut, FrameInfo, Global, HeapType, Instance, Memory, Module, StoreContextMut, Val,
-    ValType, WasmBacktrace, store::StoreOpaque,
+    ValType, WasmBacktrace,
+    store::{StoreInstanceId, StoreOpaque},
 };
 use std::fmt;
+use wasmtime_environ::FuncIndex;
+
+/// The number of tail calls recorded for [`WasmCoreDump::tail_call_trail`].
+pub(crate) const TAIL_CALL_TRAIL_LEN: usize = 16;
 
 /// Representation of a core dump of a WebAssembly module
 ///
@@ -35,6 +40,7 @@ pub struct WasmCoreDump {
     memories: Vec<Memory>,
     globals: Vec<Global>,
     backtrace: WasmBacktrace,
+    tail_call_trail: Vec<TailCallTrailEntry>,
 }
 
 impl WasmCoreDump {
@@ -45,6 +51,19 @@ impl WasmCoreDump {
 
         let mut store_globals: Vec<Global> = vec![];
         store.for_each_global(|_store, global| store_globals.push(global));
+        let tail_call_trail = store
+            .tail_call_trail()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|(instance, func_index)| TailCallTrailEntry {
+                module: store
+                    .module_for_instance(StoreInstanceId::new(store.id(), instance))
+                    .expect("tail calls are only made by real instances")
+                    .clone(),
+                instance: Instance::from_wasmtime(instance, store),
+                func_index,
+            })
+            .collect();
 
         WasmCoreDump {
             name: String::from("store_name"),
@@ -53,6 +72,7 @@ impl WasmCoreDump {
             memories: store_memories,
             globals: store_globals,
             backtrace,
+            tail_call_trail,
         }
     }
 
@@ -64,6 +84,27 @@ impl WasmCoreDump {
         self.backtrace.frames()
     }
 
+    /// The functions which most recently made tail calls before the core dump
+    /// was created, most recent first.
+    ///
+    /// This trail is only recorded when
+    /// [`TailCallOptions::profiling`](crate::TailCallOptions::profiling) or
+    /// [`TailCallOptions::hooks`](crate::TailCallOptions::hooks) is enabled,
+    /// since tail calls are otherwise never observed by the host, and it is
+    /// always empty if neither is. Profiling records every tail call while
+    /// hooks only record `return_call`.
+    ///
+    /// A tail call replaces the frame of the function making it, so those
+    /// functions are missing from [`WasmCoreDump::frames`]. Each entry of the
+    /// trail identifies the instance and module of such a function along with
+    /// its index, to help reconstruct the logical chain of calls leading to
+    /// the trap. Only the 16 most recent tail calls made in the store are
+    /// kept, which may include tail calls made before the host last called
+    /// into WebAssembly.
+    pub fn tail_call_trail(&self) -> &[TailCallTrailEntry] {
+        self.tail_call_trail.as_ref()
+    }
+
     /// All modules instantiated inside the store when the core dump was
     /// created.
     pub fn modules(&self) -> &[Module] {
@@ -333,6 +374,18 @@ impl fmt::Display for WasmCoreDump {
         writeln!(f, "backtrace:")?;
         write!(f, "{}", self.backtrace)?;
 
+        if !self.tail_call_trail.is_empty() {
+            writeln!(f, "tail call trail:")?;
+            for entry in self.tail_call_trail.iter() {
+                writeln!(
+                    f,
+                    "  {}!func[{}]",
+                    entry.module.name().unwrap_or("<module>"),
+                    entry.func_index.as_u32()
+                )?;
+            }
+        }
+
         Ok(())
     }
 }
@@ -342,3 +395,30 @@ impl fmt::Debug for WasmCoreDump {
         write!(f, "<wasm core dump>")
     }
 }
+
+/// A function which made a tail call, as recorded in
+/// [`WasmCoreDump::tail_call_trail`].
+#[derive(Clone)]
+pub struct TailCallTrailEntry {
+    instance: Instance,
+    module: Module,
+    func_index: FuncIndex,
+}
+
+impl TailCallTrailEntry {
+    /// The instance of the function which made the tail call.
+    pub fn instance(&self) -> &Instance {
+        &self.instance
+    }
+
+    /// The module of the function which made the tail call.
+    pub fn module(&self) -> &Module {
+        &self.module
+    }
+
+    /// The index of the function which made the tail call within
+    /// [`TailCallTrailEntry::module`]'s function index space.
+    pub fn func_index(&self) -> FuncIndex {
+        self.func_index
+    }
+}
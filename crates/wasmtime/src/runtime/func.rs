@@ -8,8 +8,8 @@ use crate::runtime::vm::{
 use crate::store::{AutoAssertNoGc, InstanceId, StoreId, StoreOpaque};
 use crate::type_registry::RegisteredType;
 use crate::{
-    AsContext, AsContextMut, CallHook, Engine, Extern, FuncType, Instance, ModuleExport, Ref,
-    StoreContext, StoreContextMut, Val, ValRaw, ValType,
+    AsContext, AsContextMut, CallClass, CallHook, Engine, Extern, FuncType, Instance, ModuleExport,
+    Ref, StoreContext, StoreContextMut, Val, ValRaw, ValType,
 };
 use alloc::sync::Arc;
 use core::ffi::c_void;
@@ -875,6 +875,28 @@ impl Func {
         FuncType::from_shared_type_index(store.engine(), self.type_index(store))
     }
 
+    /// Returns the kinds of calls that this function makes, if it is defined
+    /// in WebAssembly.
+    ///
+    /// This is the same classification that
+    /// [`Module::function_call_classes`](crate::Module::function_call_classes)
+    /// reports for the underlying function, which can be more convenient
+    /// when a `Func` has already been extracted from an [`Instance`]. Returns
+    /// `None` for functions defined by the host, for example with
+    /// [`Func::new`] or [`Func::wrap`], and for functions compiled without the
+    /// tail call proposal enabled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this function.
+    pub fn tail_call_class(&self, store: impl AsContext) -> Option<CallClass> {
+        let store = store.as_context().0;
+        let wasm_call = unsafe { self.vm_func_ref(store).as_ref().wasm_call? };
+        store
+            .modules()
+            .lookup_call_class(wasm_call.as_ptr() as usize)
+    }
+
     /// Does this function match the given type?
     ///
     /// That is, is this function's type a subtype of the given type?
@@ -1539,6 +1561,18 @@ pub(crate) struct EntryStoreContext {
     /// Contains value of `stack_chain` field to restore in
     /// `VMStoreContext` when exiting Wasm.
     pub stack_chain: VMStackChain,
+    /// Contains value of `regular_call_depth_remaining` field to restore in
+    /// `VMStoreContext` when exiting Wasm, since calls that trap don't
+    /// restore it themselves.
+    pub regular_call_depth_remaining: usize,
+    /// Contains value of `tail_call_chain_length` field to restore in
+    /// `VMStoreContext` when exiting Wasm, since each entry into Wasm starts
+    /// a new chain of tail calls.
+    pub tail_call_chain_length: usize,
+    /// Contains value of `host_call_is_tail` field to restore in
+    /// `VMStoreContext` when exiting Wasm, so that host functions which call
+    /// back into Wasm still see how they were themselves called.
+    pub host_call_is_tail: usize,
 
     /// We need a pointer to the runtime limits, so we can update them from
     /// `drop`/`exit_wasm`.
@@ -1631,6 +1665,17 @@ impl EntryStoreContext {
                 .last_wasm_exit_trampoline_fp
                 .get();
             let last_wasm_entry_fp = *store.0.vm_store_context().last_wasm_entry_fp.get();
+            let regular_call_depth_remaining = *store
+                .0
+                .vm_store_context()
+                .regular_call_depth_remaining
+                .get();
+            let tail_call_chain_length = mem::replace(
+                &mut *store.0.vm_store_context().tail_call_chain_length.get(),
+                0,
+            );
+            let host_call_is_tail =
+                mem::replace(&mut *store.0.vm_store_context().host_call_is_tail.get(), 0);
 
             let stack_chain = (*store.0.vm_store_context().stack_chain.get()).clone();
 
@@ -1645,6 +1690,9 @@ impl EntryStoreContext {
                 last_wasm_exit_trampoline_fp,
                 last_wasm_entry_fp,
                 stack_chain,
+                regular_call_depth_remaining,
+                tail_call_chain_length,
+                host_call_is_tail,
                 vm_store_context,
             }
         }
@@ -1666,6 +1714,10 @@ impl EntryStoreContext {
             *(*self.vm_store_context).last_wasm_exit_pc.get() = self.last_wasm_exit_pc;
             *(*self.vm_store_context).last_wasm_entry_fp.get() = self.last_wasm_entry_fp;
             *(*self.vm_store_context).stack_chain.get() = self.stack_chain.clone();
+            *(*self.vm_store_context).regular_call_depth_remaining.get() =
+                self.regular_call_depth_remaining;
+            *(*self.vm_store_context).tail_call_chain_length.get() = self.tail_call_chain_length;
+            *(*self.vm_store_context).host_call_is_tail.get() = self.host_call_is_tail;
         }
     }
 }
@@ -2194,11 +2246,50 @@ impl<T> Caller<'_, T> {
         self.store.data_mut()
     }
 
+    /// Returns whether this host function was called from WebAssembly with a
+    /// tail call.
+    ///
+    /// This returns `true` if the host function was reached with
+    /// `return_call`, `return_call_indirect`, or `return_call_ref`, in which
+    /// case it returns directly to the caller's caller, and `false` if it was
+    /// reached with a regular call or was called from the host, for example
+    /// with [`Func::call`].
+    ///
+    /// This requires [`TailCallOptions::host_tracking`](crate::TailCallOptions::host_tracking)
+    /// to be enabled, otherwise this always returns `false`.
+    pub fn is_tail_called(&self) -> bool {
+        // SAFETY: the store context is valid for the lifetime of the store,
+        // and the flag is only written by wasm on this thread and restored
+        // when exiting wasm, so it still describes this call even if this
+        // function has called back into wasm.
+        unsafe { *self.store.0.vm_store_context().host_call_is_tail.get() != 0 }
+    }
+
     /// Returns the underlying [`Engine`] this store is connected to.
     pub fn engine(&self) -> &Engine {
         self.store.engine()
     }
 
+    /// Returns the number of active WebAssembly frames on the native stack at
+    /// the point this host function was called.
+    ///
+    /// This counts all WebAssembly frames belonging to this store, including
+    /// those of any outer activations if the host has re-entered WebAssembly
+    /// in a nested fashion. Host frames are not counted.
+    ///
+    /// Frames which have been replaced by a tail call (for example via
+    /// `return_call`) are no longer on the stack and are not counted, so a
+    /// host function called from within a tail-recursive loop will observe the
+    /// same depth on every iteration.
+    ///
+    /// Note that this walks the stack to compute its result, so its cost is
+    /// proportional to the returned depth.
+    pub fn current_wasm_stack_depth(&self) -> usize {
+        crate::runtime::vm::Backtrace::new(&self.store.0)
+            .frames()
+            .len()
+    }
+
     /// Perform garbage collection.
     ///
     /// Same as [`Store::gc`](crate::Store::gc).
@@ -2232,6 +2323,23 @@ impl<T> Caller<'_, T> {
         self.store.set_fuel(fuel)
     }
 
+    /// Returns the number of tail calls made since the store's tail-call fuel
+    /// was last set.
+    ///
+    /// For more information see
+    /// [`Store::tail_call_fuel_consumed`](crate::Store::tail_call_fuel_consumed)
+    pub fn tail_call_fuel_consumed(&self) -> Result<u64> {
+        self.store.tail_call_fuel_consumed()
+    }
+
+    /// Set the amount of tail-call fuel in this store.
+    ///
+    /// For more information see
+    /// [`Store::set_tail_call_fuel`](crate::Store::set_tail_call_fuel)
+    pub fn set_tail_call_fuel(&mut self, fuel: u64) -> Result<()> {
+        self.store.set_tail_call_fuel(fuel)
+    }
+
     /// Configures this `Store` to yield while executing futures every N units of fuel.
     ///
     /// For more information see
@@ -9,10 +9,11 @@ use crate::store::{
 };
 use crate::types::matching;
 use crate::{
-    AsContextMut, Engine, Export, Extern, Func, Global, Memory, Module, ModuleExport, SharedMemory,
-    StoreContext, StoreContextMut, Table, Tag, TypedFunc,
+    AsContext, AsContextMut, Engine, Export, Extern, Func, Global, Memory, Module, ModuleExport,
+    SharedMemory, StoreContext, StoreContextMut, Table, Tag, TypedFunc,
 };
 use alloc::sync::Arc;
+use core::mem;
 use core::ptr::NonNull;
 use wasmparser::WasmFeatures;
 use wasmtime_environ::{
@@ -588,6 +589,57 @@ impl Instance {
         self.get_export(store, name)?.into_tag()
     }
 
+    /// Returns the number of tail calls made by each function defined in this
+    /// instance, as counted by [`TailCallOptions::profiling`].
+    ///
+    /// Each entry is a function index within this instance's module and the
+    /// number of `return_call`, `return_call_indirect`, and `return_call_ref`
+    /// instructions which that function has executed. Functions which haven't
+    /// made any tail calls are omitted, and entries are in function index
+    /// order. If tail-call profiling isn't enabled then this is always empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    ///
+    /// [`TailCallOptions::profiling`]: crate::TailCallOptions::profiling
+    pub fn tail_call_counts(&self, store: impl AsContext) -> Vec<(FuncIndex, u64)> {
+        let instance = &store.as_context().0[self.id];
+        let module = instance.env_module();
+        instance
+            .tail_call_counts()
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| (module.func_index(index), *count))
+            .collect()
+    }
+
+    /// Takes a snapshot of the tail calls counted for this instance by
+    /// [`TailCallOptions::profiling`] and resets its counts to zero.
+    ///
+    /// The returned [`TailCallStats`] is owned and independent of `store`, so
+    /// it can be kept and aggregated after this instance and its store have
+    /// been dropped. Since the counts are reset, taking stats again only
+    /// reports tail calls made in between. If tail-call profiling isn't
+    /// enabled then the stats are always empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `store` does not own this instance.
+    ///
+    /// [`TailCallOptions::profiling`]: crate::TailCallOptions::profiling
+    pub fn take_tail_call_stats(&self, mut store: impl AsContextMut) -> TailCallStats {
+        let mut instance = self.id.get_mut(store.as_context_mut().0);
+        let module = instance.env_module().clone();
+        let counts = mem::take(instance.as_mut().tail_call_counts_mut());
+        let counts = counts
+            .iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(index, count)| (module.func_index(index), *count))
+            .collect();
+        TailCallStats { counts }
+    }
+
     #[allow(
         dead_code,
         reason = "c-api crate does not yet support exnrefs and causes this method to be dead."
@@ -968,6 +1020,16 @@ fn pre_instantiate_raw(
     Ok(imports)
 }
 
+/// A snapshot of the tail calls made by the functions of an [`Instance`], as
+/// returned by [`Instance::take_tail_call_stats`].
+#[non_exhaustive]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TailCallStats {
+    /// The number of tail calls made by each function defined in the instance,
+    /// in the same format as [`Instance::tail_call_counts`].
+    pub counts: Vec<(FuncIndex, u64)>,
+}
+
 fn typecheck<I>(
     module: &Module,
     import_args: &[I],
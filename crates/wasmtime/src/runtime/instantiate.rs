@@ -9,8 +9,9 @@ use crate::{code_memory::CodeMemory, profiling_agent::ProfilingAgent};
 use alloc::sync::Arc;
 use core::str;
 use wasmtime_environ::{
-    CompiledFunctionInfo, CompiledModuleInfo, DefinedFuncIndex, FilePos, FuncIndex, FunctionLoc,
-    FunctionName, Metadata, Module, ModuleInternedTypeIndex, PrimaryMap,
+    CallClass, CallConv, CallEdge, CompiledFunctionInfo, CompiledModuleInfo, DefinedFuncIndex,
+    FilePos, FrameLayout, FuncIndex, FunctionLoc, FunctionName, Metadata, Module,
+    ModuleInternedTypeIndex, PrimaryMap, TailCallFunctionInfo,
 };
 
 /// A compiled wasm module, ready to be instantiated.
@@ -23,6 +24,8 @@ pub struct CompiledModule {
     /// A unique ID used to register this module with the engine.
     unique_id: CompiledModuleId,
     func_names: Vec<FunctionName>,
+    /// Whether any function in this module makes tail calls.
+    has_tail_calls: bool,
 }
 
 impl CompiledModule {
@@ -47,6 +50,11 @@ impl CompiledModule {
         info: CompiledModuleInfo,
         profiler: &dyn ProfilingAgent,
     ) -> Result<Self> {
+        let has_tail_calls = info.funcs.values().any(|f| {
+            f.tail_calls
+                .as_ref()
+                .is_some_and(|t| t.call_class.has_tail_calls())
+        });
         let mut ret = Self {
             module: Arc::new(info.module),
             funcs: info.funcs,
@@ -55,6 +63,7 @@ impl CompiledModule {
             meta: info.meta,
             unique_id: CompiledModuleId::new(),
             func_names: info.func_names,
+            has_tail_calls,
         };
         ret.register_profiling(profiler)?;
 
@@ -64,13 +73,26 @@ impl CompiledModule {
     fn register_profiling(&mut self, profiler: &dyn ProfilingAgent) -> Result<()> {
         // TODO-Bug?: "code_memory" is not exclusive for this module in the case of components,
         // so we may be registering the same code range multiple times here.
-        profiler.register_module(&self.code_memory.mmap()[..], &|addr| {
-            let (idx, _) = self.func_by_text_offset(addr)?;
-            let idx = self.module.func_index(idx);
-            let name = self.func_name(idx)?;
-            let mut demangled = String::new();
-            wasmtime_environ::demangle_function_name(&mut demangled, name).unwrap();
-            Some(demangled)
+        profiler.register_module(&self.code_memory.mmap()[..], &|addr, symbol| {
+            let (idx, offset) = self.func_by_text_offset(addr)?;
+            // Functions whose stack checks are relaxed only make tail calls,
+            // which replace their frame, so they're annotated to make that
+            // visible in profiles. Samples are still
+            // attributed to them by their code address, like any other
+            // function.
+            let reuses_frame = offset == 0 && self.func_tail_call_frame_savings(idx).is_some();
+            let mut name = String::new();
+            match self.func_name(self.module.func_index(idx)) {
+                Some(func_name) => {
+                    wasmtime_environ::demangle_function_name(&mut name, func_name).unwrap()
+                }
+                None if reuses_frame => name.push_str(symbol),
+                None => return None,
+            }
+            if reuses_frame {
+                name.push_str(" [reused frame]");
+            }
+            Some(name)
         });
         Ok(())
     }
@@ -218,6 +240,66 @@ impl CompiledModule {
         self.funcs[index].start_srcloc
     }
 
+    /// Returns the tail-call metadata recorded for the function `index`, or
+    /// `None` if the module was compiled without the tail call proposal.
+    fn func_tail_calls(&self, index: DefinedFuncIndex) -> Option<&TailCallFunctionInfo> {
+        self.funcs[index].tail_calls.as_ref()
+    }
+
+    /// Returns the kinds of calls that the function `index` makes, if they
+    /// were recorded.
+    pub fn func_call_class(&self, index: DefinedFuncIndex) -> Option<CallClass> {
+        Some(self.func_tail_calls(index)?.call_class)
+    }
+
+    /// Returns whether any function defined in this module makes tail calls.
+    pub fn has_tail_calls(&self) -> bool {
+        self.has_tail_calls
+    }
+
+    /// Returns the number of stack bytes saved by optimizing the frame of the
+    /// function `index`, or `None` if its frame wasn't optimized.
+    pub fn func_tail_call_frame_savings(&self, index: DefinedFuncIndex) -> Option<u32> {
+        self.func_tail_calls(index)?.tail_call_frame_savings
+    }
+
+    /// Returns the lowest function index in the tail-call clique that the
+    /// function `index` belongs to, or `None` if it isn't part of one.
+    pub fn func_tail_call_clique(&self, index: DefinedFuncIndex) -> Option<FuncIndex> {
+        self.func_tail_calls(index)?.tail_call_clique
+    }
+
+    /// Returns the calls that the function at `index` makes.
+    pub fn func_call_edges(&self, index: DefinedFuncIndex) -> &[CallEdge] {
+        self.func_tail_calls(index)
+            .map_or(&[], |t| t.call_edges.as_slice())
+    }
+
+    /// Returns the function that the function at `index` unconditionally tail
+    /// calls, if it's straight-line code ending in a `return_call`.
+    pub fn func_unconditional_tail_callee(&self, index: DefinedFuncIndex) -> Option<FuncIndex> {
+        self.func_tail_calls(index)?.unconditional_tail_callee
+    }
+
+    /// Returns the positions, in the original wasm file, of the tail calls made
+    /// by the function at `index`.
+    pub fn func_tail_call_sites(&self, index: DefinedFuncIndex) -> &[FilePos] {
+        self.func_tail_calls(index)
+            .map_or(&[], |t| t.tail_call_sites.as_slice())
+    }
+
+    /// Returns the layout of the stack frame of the function at `index`, if
+    /// its compiler reported one.
+    pub fn func_frame_layout(&self, index: DefinedFuncIndex) -> Option<FrameLayout> {
+        self.func_tail_calls(index)?.frame_layout
+    }
+
+    /// Returns the calling convention that the function at `index` was
+    /// compiled with, if it was recorded.
+    pub fn func_call_conv(&self, index: DefinedFuncIndex) -> Option<CallConv> {
+        Some(self.func_tail_calls(index)?.call_conv)
+    }
+
     /// Creates a new symbolication context which can be used to further
     /// symbolicate stack traces.
     ///
@@ -11,22 +11,26 @@ use crate::{
     resources::ResourcesRequired,
     types::{ExportType, ExternType, ImportType},
 };
+use alloc::collections::{BTreeMap, BTreeSet};
 use alloc::sync::Arc;
 use core::fmt;
 use core::ops::Range;
 use core::ptr::NonNull;
 #[cfg(feature = "std")]
 use std::{fs::File, path::Path};
-use wasmparser::{Parser, ValidPayload, Validator};
+use wasmparser::{Parser, ValidPayload, Validator, WasmFeatures};
 use wasmtime_environ::{
-    CompiledModuleInfo, EntityIndex, HostPtr, ModuleTypes, ObjectKind, TypeTrace, VMOffsets,
-    VMSharedTypeIndex,
+    CompiledModuleInfo, DefinedFuncIndex, EntityIndex, HostPtr, ModuleTypes, ObjectKind,
+    SecondaryMap, TypeTrace, VMOffsets, VMSharedTypeIndex,
 };
 #[cfg(feature = "gc")]
 use wasmtime_unwinder::ExceptionTable;
+#[cfg(feature = "tail-call-differential")]
+mod differential;
 mod registry;
 
 pub use registry::*;
+pub use wasmtime_environ::{CallClass, CallConv, CallEdge, CallKind, CallTarget, FrameLayout};
 
 /// A compiled WebAssembly module, ready to be instantiated.
 ///
@@ -153,6 +157,13 @@ struct ModuleInner {
     /// instantiated.
     memory_images: OnceLock<Option<ModuleMemoryImages>>,
 
+    /// The functions which directly tail call each function, for
+    /// `Module::tail_call_callers`.
+    ///
+    /// This is built from the call graph the first time it's queried.
+    tail_call_callers:
+        OnceLock<BTreeMap<wasmtime_environ::FuncIndex, Vec<wasmtime_environ::FuncIndex>>>,
+
     /// Flag indicating whether this module can be serialized or not.
     #[cfg(any(feature = "cranelift", feature = "winch"))]
     serializable: bool,
@@ -531,6 +542,10 @@ impl Module {
         let module =
             CompiledModule::from_artifacts(code.code_memory().clone(), info, engine.profiler())?;
 
+        if engine.config().tail_call.reject_modules {
+            Self::reject_tail_calls(&module)?;
+        }
+
         // Validate the module can be used with the current instance allocator.
         let offsets = VMOffsets::new(HostPtr, module.module());
         engine
@@ -544,6 +559,7 @@ impl Module {
                 engine: engine.clone(),
                 code,
                 memory_images: OnceLock::new(),
+                tail_call_callers: OnceLock::new(),
                 module,
                 #[cfg(any(feature = "cranelift", feature = "winch"))]
                 serializable,
@@ -552,6 +568,27 @@ impl Module {
         })
     }
 
+    /// Fails if any function defined in `module` makes tail calls, for
+    /// `TailCallOptions::reject_modules`.
+    fn reject_tail_calls(module: &CompiledModule) -> Result<()> {
+        let Some(index) = module.finished_functions().map(|(idx, _)| idx).find(|idx| {
+            module
+                .func_call_class(*idx)
+                .is_some_and(|c| c.has_tail_calls())
+        }) else {
+            return Ok(());
+        };
+        let index = module.module().func_index(index);
+        let func = match module.func_name(index) {
+            Some(name) => format!("function {} (`{name}`)", index.as_u32()),
+            None => format!("function {}", index.as_u32()),
+        };
+        bail!(
+            "{func} makes tail calls, and modules which make tail calls are rejected by \
+             `TailCallOptions::reject_modules`"
+        )
+    }
+
     /// Validates `binary` input data as a WebAssembly binary given the
     /// configuration in `engine`.
     ///
@@ -1066,6 +1103,571 @@ impl Module {
         })
     }
 
+    /// Returns the [`CallClass`] of each function defined in this module,
+    /// along with that function's index.
+    ///
+    /// The classification is determined at compile time by scanning each
+    /// function's body for regular calls (`call`, `call_indirect`, and
+    /// `call_ref`) and tail calls (`return_call`, `return_call_indirect`, and
+    /// `return_call_ref`). Imported functions are not included. The
+    /// classifications are recorded in the compiled artifact, so they are
+    /// also available for modules loaded with [`Module::deserialize`].
+    ///
+    /// Classifications, along with the rest of the tail-call information
+    /// reported by `Module`, are only recorded when the tail call proposal is
+    /// enabled with [`Config::wasm_tail_call`](crate::Config::wasm_tail_call).
+    /// Without it this is empty.
+    ///
+    /// Because they're part of the artifact, classifications are also reused
+    /// whenever the compilation cache reuses an artifact, rather than being
+    /// recomputed. The cache is keyed on all of the tail call options which
+    /// influence classification or codegen, such as
+    /// [`TailCallOptions::self_recursion_loop`](crate::TailCallOptions::self_recursion_loop)
+    /// and
+    /// [`TailCallOptions::min_frame_savings`](crate::TailCallOptions::min_frame_savings),
+    /// so changing any of them causes a module to be recompiled and
+    /// reclassified. The exception is
+    /// [`TailCallOptions::classifier`](crate::TailCallOptions::classifier),
+    /// which isn't part of the cache key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (func $countdown (param i32) (result i32)
+    ///             local.get 0
+    ///             i32.eqz
+    ///             if (result i32)
+    ///                 i32.const 0
+    ///             else
+    ///                 local.get 0
+    ///                 i32.const 1
+    ///                 i32.sub
+    ///                 return_call $countdown
+    ///             end)
+    ///     )
+    /// "#)?;
+    /// let classes = module.function_call_classes();
+    /// assert_eq!(classes[0].1, CallClass::TailCallOnly);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn function_call_classes(&self) -> Vec<(wasmtime_environ::FuncIndex, CallClass)> {
+        let module = self.compiled_module();
+        module
+            .finished_functions()
+            .filter_map(|(idx, _)| {
+                Some((
+                    module.module().func_index(idx),
+                    module.func_call_class(idx)?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Returns whether every function defined in this module either makes no
+    /// calls or only makes tail calls, that is whether each one's
+    /// [`CallClass`] is [`Leaf`](CallClass::Leaf),
+    /// [`TailCallOnly`](CallClass::TailCallOnly), or
+    /// [`SelfTailLoop`](CallClass::SelfTailLoop).
+    ///
+    /// None of the functions of such a module ever has its frame on the stack
+    /// while another of them runs, so they only ever use a bounded amount of
+    /// native stack however long a chain of tail calls between them is. This
+    /// can be used, for example, to give instances of the module smaller
+    /// stacks. Note that this only describes the module's own functions:
+    /// functions it tail calls which are imported, or which come from a table
+    /// shared with other modules, may use any amount of stack.
+    ///
+    /// This is `true` for a module which doesn't define any functions, and
+    /// `false` for one compiled without the tail call proposal. See
+    /// [`Module::function_call_classes`] for the classification of each
+    /// function.
+    pub fn is_tail_call_pure(&self) -> bool {
+        let module = self.compiled_module();
+        module.finished_functions().all(|(idx, _)| {
+            module
+                .func_call_class(idx)
+                .is_some_and(|class| class == CallClass::Leaf || class.is_tail_call_only())
+        })
+    }
+
+    /// Returns, for each function defined in this module, that function's
+    /// index and the number of bytes of stack saved by optimizing its frame
+    /// for tail calls.
+    ///
+    /// The savings are `None` for functions whose frames were not optimized,
+    /// either because they don't only make tail calls or because the
+    /// optimization didn't apply to them; see
+    /// [`TailCallOptions::relaxed_stack_check`]. Imported functions are not
+    /// included. Like [`Module::function_call_classes`], these values are
+    /// recorded in the compiled artifact and are summarized by
+    /// [`Module::compile_stats`].
+    ///
+    /// [`TailCallOptions::relaxed_stack_check`]: crate::TailCallOptions::relaxed_stack_check
+    pub fn function_tail_call_frame_savings(
+        &self,
+    ) -> Vec<(wasmtime_environ::FuncIndex, Option<u32>)> {
+        let module = self.compiled_module();
+        module
+            .finished_functions()
+            .map(|(idx, _)| {
+                (
+                    module.module().func_index(idx),
+                    module.func_tail_call_frame_savings(idx),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns whether the frame of the function defined in this module at
+    /// `index` was optimized because it only makes tail calls; see
+    /// [`TailCallOptions::relaxed_stack_check`].
+    ///
+    /// This is `false` for functions which make regular calls, such as
+    /// [`CallClass::Mixed`] functions, for functions the optimization didn't
+    /// apply to, and for imported functions and indices out of bounds. It's
+    /// the negative counterpart of
+    /// [`TailCallOptions::require_optimization`], which makes compilation
+    /// fail if the optimization doesn't apply, and is equivalent to checking
+    /// whether [`Module::function_tail_call_frame_savings`] reports savings
+    /// for `index`.
+    ///
+    /// [`TailCallOptions::relaxed_stack_check`]: crate::TailCallOptions::relaxed_stack_check
+    /// [`TailCallOptions::require_optimization`]: crate::TailCallOptions::require_optimization
+    pub fn tail_call_optimized(&self, index: wasmtime_environ::FuncIndex) -> bool {
+        let module = self.compiled_module();
+        if module.module().functions.get(index).is_none() {
+            return false;
+        }
+        match module.module().defined_func_index(index) {
+            Some(index) => module.func_tail_call_frame_savings(index).is_some(),
+            None => false,
+        }
+    }
+
+    /// Returns the calling convention that the function `index`, defined in
+    /// this module, was compiled with, or `None` if `index` is imported or
+    /// doesn't name a function, or if the module was compiled without the tail
+    /// call proposal.
+    ///
+    /// Functions compiled by Cranelift use [`CallConv::Tail`] on every target,
+    /// and functions compiled by Winch use [`CallConv::Winch`]. Whether the
+    /// frames of functions which only make tail calls can be optimized depends
+    /// on the target's support for this convention; see
+    /// [`TailCallOptions::relaxed_stack_check`]. Like
+    /// [`Module::function_call_classes`], this is recorded in the compiled
+    /// artifact.
+    ///
+    /// [`TailCallOptions::relaxed_stack_check`]: crate::TailCallOptions::relaxed_stack_check
+    pub fn function_call_conv(&self, index: wasmtime_environ::FuncIndex) -> Option<CallConv> {
+        let module = self.compiled_module();
+        module.module().functions.get(index)?;
+        let index = module.module().defined_func_index(index)?;
+        module.func_call_conv(index)
+    }
+
+    /// Returns a description of the layout of the stack frame of the function
+    /// defined in this module at `index`.
+    ///
+    /// The [`FrameLayout`] describes the size of each area of the frame,
+    /// including how many callee-saved registers it saves, whether the frame
+    /// was optimized because the function only makes tail calls (see
+    /// [`TailCallOptions::relaxed_stack_check`]), and whether the function's
+    /// return address is reused across the tail calls it makes. This is a
+    /// read-only, best-effort view intended for debugging and tooling.
+    ///
+    /// Returns `None` for imported functions, for indices out of bounds, for
+    /// modules compiled without the tail call proposal, and for functions
+    /// compiled by a compiler which doesn't report frame layouts, which is
+    /// currently the case for Winch. Like
+    /// [`Module::function_call_classes`], frame layouts are recorded in the
+    /// compiled artifact.
+    ///
+    /// [`TailCallOptions::relaxed_stack_check`]: crate::TailCallOptions::relaxed_stack_check
+    pub fn function_frame_layout(&self, index: wasmtime_environ::FuncIndex) -> Option<FrameLayout> {
+        let module = self.compiled_module();
+        module.module().functions.get(index)?;
+        let index = module.module().defined_func_index(index)?;
+        module.func_frame_layout(index)
+    }
+
+    /// Returns the number of bytes of native stack needed by the frames of
+    /// the function exported as `export` and all the functions it may call,
+    /// if that's statically bounded.
+    ///
+    /// This is only known when the export and every function reachable from
+    /// it through the [call graph](Module::call_graph) either makes no calls
+    /// or only makes direct tail calls to functions defined in this module,
+    /// as with [`Module::is_tail_call_pure`]. Each tail call replaces its
+    /// caller's frame, so however long the chain of tail calls, at most one
+    /// of those frames is on the stack at a time and the bound is the size of
+    /// the largest one, as reported by [`Module::function_frame_layout`].
+    ///
+    /// Returns `None` if `export` isn't an exported function, if any reachable
+    /// function makes a regular call, which may recurse without bound, or an
+    /// indirect call, whose callee isn't known, or if any reachable function
+    /// is imported or has no known frame layout.
+    ///
+    /// This is a best-effort estimate intended for capacity planning, for
+    /// example when choosing the size of the stacks of the pooling allocator.
+    /// It doesn't include the stack used by Wasmtime and the host to call
+    /// into WebAssembly, which also counts against
+    /// [`Config::max_wasm_stack`](crate::Config::max_wasm_stack).
+    pub fn min_stack_bytes(&self, export: &str) -> Option<u64> {
+        let module = self.compiled_module();
+        let env_module = module.module();
+        let EntityIndex::Function(entry) = *env_module.exports.get(export)? else {
+            return None;
+        };
+        let mut max = 0;
+        let mut seen = BTreeSet::new();
+        let mut worklist = vec![entry];
+        while let Some(func) = worklist.pop() {
+            if !seen.insert(func) {
+                continue;
+            }
+            let index = env_module.defined_func_index(func)?;
+            let class = module.func_call_class(index)?;
+            if class != CallClass::Leaf && !class.is_tail_call_only() {
+                return None;
+            }
+            let layout = module.func_frame_layout(index)?;
+            max = max.max(u64::from(layout.total_size()));
+            for edge in module.func_call_edges(index) {
+                match edge.to {
+                    CallTarget::Direct(callee) => worklist.push(callee),
+                    CallTarget::Indirect { .. } => return None,
+                }
+            }
+        }
+        Some(max)
+    }
+
+    /// Returns the length of the longest chain of consecutive tail calls which
+    /// may be made by a call to the function exported as `export`, if that's
+    /// statically bounded.
+    ///
+    /// A chain is a sequence of tail calls without an intervening regular
+    /// call, which is what's counted by
+    /// [`TailCallOptions::max_chain_length`](crate::TailCallOptions::max_chain_length),
+    /// so a limit of at least the returned length is never reached by this
+    /// export. Chains may start at the export itself or at any function it
+    /// reaches through the [call graph](Module::call_graph) with a regular
+    /// call, since the regular call starts a new chain. A function which makes
+    /// no tail calls has a chain of length 0, and a pipeline of functions each
+    /// tail calling the next has a chain of one less than the number of
+    /// functions.
+    ///
+    /// Returns `None` if `export` isn't an exported function, if any function
+    /// reachable from it can reach itself through tail calls alone, such as
+    /// the states of a state machine, in which case chains are unbounded, or
+    /// if any reachable function makes an indirect call, whose callee isn't
+    /// known. Calls to imported functions are assumed not to call back into
+    /// this module, and tail calls to them end a chain.
+    pub fn max_static_tail_chain(&self, export: &str) -> Option<usize> {
+        let module = self.compiled_module();
+        let env_module = module.module();
+        let EntityIndex::Function(entry) = *env_module.exports.get(export)? else {
+            return None;
+        };
+
+        // Find every function reachable from `export`, along with the
+        // functions each of them tail calls.
+        let mut tail_calls =
+            BTreeMap::<wasmtime_environ::FuncIndex, Vec<wasmtime_environ::FuncIndex>>::new();
+        let mut worklist = vec![entry];
+        while let Some(func) = worklist.pop() {
+            if tail_calls.contains_key(&func) {
+                continue;
+            }
+            let callees = tail_calls.entry(func).or_default();
+            let Some(index) = env_module.defined_func_index(func) else {
+                continue;
+            };
+            for edge in module.func_call_edges(index) {
+                let CallTarget::Direct(callee) = edge.to else {
+                    return None;
+                };
+                if edge.kind == CallKind::Tail {
+                    callees.push(callee);
+                }
+                worklist.push(callee);
+            }
+        }
+
+        // Find the longest chain starting at each function with a depth-first
+        // search of the tail calls. Reaching a function which is still being
+        // visited means there's a cycle of tail calls.
+        let mut longest = BTreeMap::new();
+        let mut visiting = BTreeSet::new();
+        for &start in tail_calls.keys() {
+            if longest.contains_key(&start) {
+                continue;
+            }
+            visiting.insert(start);
+            let mut stack = vec![(start, 0)];
+            while let Some(&(func, i)) = stack.last() {
+                let callees = &tail_calls[&func];
+                match callees.get(i) {
+                    Some(&callee) => {
+                        stack.last_mut().unwrap().1 += 1;
+                        if visiting.contains(&callee) {
+                            return None;
+                        }
+                        if !longest.contains_key(&callee) {
+                            visiting.insert(callee);
+                            stack.push((callee, 0));
+                        }
+                    }
+                    None => {
+                        let len = callees
+                            .iter()
+                            .map(|callee| longest[callee] + 1)
+                            .max()
+                            .unwrap_or(0);
+                        longest.insert(func, len);
+                        visiting.remove(&func);
+                        stack.pop();
+                    }
+                }
+            }
+        }
+        longest.into_values().max()
+    }
+
+    /// Returns the cliques of mutually tail-recursive functions defined in this
+    /// module.
+    ///
+    /// A clique is a group of at least two functions that only make tail calls
+    /// (see [`CallClass::TailCallOnly`]) and that all reach each other through
+    /// direct `return_call`s, such as the states of a state machine. Each
+    /// clique is returned as its members' indices in ascending order, and the
+    /// cliques are ordered by their lowest member. Tail calls through
+    /// `return_call_indirect` and `return_call_ref` are not considered.
+    ///
+    /// This only reports cliques; their functions are still compiled
+    /// separately and each tail call between them is a regular tail call.
+    /// Like [`Module::function_call_classes`], cliques are recorded in the
+    /// compiled artifact.
+    pub fn tail_call_cliques(&self) -> Vec<Vec<wasmtime_environ::FuncIndex>> {
+        let module = self.compiled_module();
+        let mut cliques =
+            BTreeMap::<wasmtime_environ::FuncIndex, Vec<wasmtime_environ::FuncIndex>>::new();
+        for (idx, _) in module.finished_functions() {
+            if let Some(representative) = module.func_tail_call_clique(idx) {
+                cliques
+                    .entry(representative)
+                    .or_default()
+                    .push(module.module().func_index(idx));
+            }
+        }
+        cliques.into_values().collect()
+    }
+
+    /// Returns this module's static call graph: the calls made by each
+    /// function defined in it, with tail calls distinguished from regular
+    /// calls.
+    ///
+    /// Each function's calls are deduplicated, and functions are listed in
+    /// ascending index order. Calls in code that can never run aren't
+    /// included, as with [`Module::function_call_classes`], and indirect calls
+    /// are described by the type of the functions they may call. Self-recursive
+    /// tail calls are included even when they were compiled as loops (see
+    /// [`CallClass::SelfTailLoop`]). Like [`Module::function_call_classes`],
+    /// the call graph is recorded in the compiled artifact.
+    pub fn call_graph(&self) -> Vec<CallEdge> {
+        let module = self.compiled_module();
+        module
+            .finished_functions()
+            .flat_map(|(idx, _)| module.func_call_edges(idx).iter().copied())
+            .collect()
+    }
+
+    /// Returns the functions defined in this module which tail call `target`
+    /// directly, through `return_call`.
+    ///
+    /// This is the reverse of the tail call edges in [`Module::call_graph`]:
+    /// callers are listed once each, in ascending index order, and include
+    /// `target` itself if it's tail recursive. Tail calls through
+    /// `return_call_indirect` and `return_call_ref` aren't included since
+    /// they don't name the function they call. `target` may be an imported
+    /// function.
+    pub fn tail_call_callers(
+        &self,
+        target: wasmtime_environ::FuncIndex,
+    ) -> Vec<wasmtime_environ::FuncIndex> {
+        let callers = self.inner.tail_call_callers.get_or_init(|| {
+            let mut callers = BTreeMap::<_, Vec<_>>::new();
+            for edge in self.call_graph() {
+                if let (CallKind::Tail, CallTarget::Direct(to)) = (edge.kind, edge.to) {
+                    callers.entry(to).or_default().push(edge.from);
+                }
+            }
+            callers
+        });
+        callers.get(&target).cloned().unwrap_or_default()
+    }
+
+    /// Returns a report of how each function defined in this module was
+    /// classified and whether its frame was optimized for tail calls.
+    ///
+    /// This is the same report that
+    /// [`Engine::precompile_module_with_report`] produces, built from the
+    /// information recorded in the compiled artifact, so it's also available
+    /// for modules that were deserialized.
+    #[cfg(feature = "tail-call-report")]
+    pub fn tail_call_report(&self) -> crate::TailCallReport {
+        let module = self.compiled_module();
+        crate::TailCallReport::new(
+            module.module(),
+            module.finished_functions().filter_map(|(idx, _)| {
+                Some((
+                    module.module().func_index(idx),
+                    module.func_call_class(idx)?,
+                    module.func_tail_call_frame_savings(idx),
+                ))
+            }),
+        )
+    }
+
+    /// Returns statistics about how the functions in this module were
+    /// compiled.
+    ///
+    /// See [`ModuleCompileStats`] for the information available. These
+    /// statistics are recorded in the compiled artifact, so they are also
+    /// available for modules created with [`Module::deserialize`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use wasmtime::*;
+    /// # fn main() -> anyhow::Result<()> {
+    /// let engine = Engine::default();
+    /// let module = Module::new(&engine, r#"
+    ///     (module
+    ///         (func $countdown (param i32) (result i32)
+    ///             local.get 0
+    ///             i32.eqz
+    ///             if (result i32)
+    ///                 i32.const 0
+    ///             else
+    ///                 local.get 0
+    ///                 i32.const 1
+    ///                 i32.sub
+    ///                 return_call $countdown
+    ///             end)
+    ///     )
+    /// "#)?;
+    /// let stats = module.compile_stats();
+    /// assert_eq!(stats.tail_call_only_functions, 1);
+    /// println!("saved {} bytes of stack", stats.estimated_stack_bytes_saved);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn compile_stats(&self) -> ModuleCompileStats {
+        let module = self.compiled_module();
+        let mut stats = ModuleCompileStats::default();
+        for (idx, _) in module.finished_functions() {
+            if module
+                .func_call_class(idx)
+                .is_some_and(|class| class.is_tail_call_only())
+            {
+                stats.tail_call_only_functions += 1;
+            }
+            if let Some(savings) = module.func_tail_call_frame_savings(idx) {
+                stats.optimized_frames += 1;
+                stats.estimated_stack_bytes_saved += u64::from(savings);
+            }
+        }
+        stats
+    }
+
+    /// Returns advisory diagnostics about how this module was compiled.
+    ///
+    /// These never prevent a module from being compiled or instantiated, but
+    /// may point at a misconfiguration; see [`CompileWarning`] for the
+    /// diagnostics reported. Like [`Module::compile_stats`], they're derived
+    /// from information recorded in the compiled artifact and from the
+    /// configuration of [`Module::engine`], so they're also available for
+    /// modules created with [`Module::deserialize`].
+    pub fn compile_warnings(&self) -> Vec<CompileWarning> {
+        let mut warnings = Vec::new();
+        let tail_calls_requested = self
+            .engine()
+            .config()
+            .enabled_features
+            .contains(WasmFeatures::TAIL_CALL);
+        if tail_calls_requested
+            && self
+                .call_graph()
+                .iter()
+                .all(|edge| edge.kind != CallKind::Tail)
+        {
+            warnings.push(CompileWarning::UnusedTailCallFlag);
+        }
+        warnings.extend(
+            self.unconditional_tail_loops()
+                .into_iter()
+                .map(|func_index| CompileWarning::UnconditionalTailLoop { func_index }),
+        );
+        warnings
+    }
+
+    /// Returns, in ascending order, the defined functions which are part of a
+    /// cycle of unconditional tail calls.
+    ///
+    /// Following the unconditional tail callee of each function forms a graph
+    /// where every function has at most one successor, so each walk ends
+    /// either at a function without one or in a cycle.
+    fn unconditional_tail_loops(&self) -> Vec<wasmtime_environ::FuncIndex> {
+        #[derive(Clone, Copy, Default, PartialEq)]
+        enum State {
+            #[default]
+            Unvisited,
+            OnPath,
+            Done,
+        }
+
+        let module = self.compiled_module();
+        let env_module = module.module();
+        let mut states = SecondaryMap::<DefinedFuncIndex, State>::new();
+        let mut loops = Vec::new();
+        let mut path = Vec::new();
+        for (start, _) in module.finished_functions() {
+            let mut current = Some(start);
+            while let Some(index) = current {
+                match states[index] {
+                    State::Unvisited => {}
+                    State::OnPath => {
+                        let cycle_start = path.iter().position(|&i| i == index).unwrap();
+                        loops.extend(
+                            path[cycle_start..]
+                                .iter()
+                                .map(|&i| env_module.func_index(i)),
+                        );
+                        break;
+                    }
+                    State::Done => break,
+                }
+                states[index] = State::OnPath;
+                path.push(index);
+                current = module
+                    .func_unconditional_tail_callee(index)
+                    .and_then(|callee| env_module.defined_func_index(callee));
+            }
+            for index in path.drain(..) {
+                states[index] = State::Done;
+            }
+        }
+        loops.sort_unstable();
+        loops
+    }
+
     pub(crate) fn id(&self) -> CompiledModuleId {
         self.inner.module.unique_id()
     }
@@ -1166,6 +1768,77 @@ pub struct ModuleExport {
     pub(crate) entity: EntityIndex,
 }
 
+/// Statistics about how the functions in a [`Module`] were compiled, as
+/// returned by [`Module::compile_stats`].
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ModuleCompileStats {
+    /// The number of defined functions which only make tail calls; see
+    /// [`CallClass::TailCallOnly`].
+    pub tail_call_only_functions: usize,
+
+    /// The number of functions whose frames were optimized because they only
+    /// make tail calls; see [`TailCallOptions::relaxed_stack_check`].
+    ///
+    /// [`TailCallOptions::relaxed_stack_check`]: crate::TailCallOptions::relaxed_stack_check
+    pub optimized_frames: usize,
+
+    /// An estimate of the number of stack bytes saved across all optimized
+    /// frames.
+    ///
+    /// For each optimized function this is the difference between the stack
+    /// that a standard frame would reserve for its callees and the stack that
+    /// the optimized frame reserves. This is an estimate of the savings per
+    /// activation of each function, not a measurement of any particular
+    /// execution.
+    pub estimated_stack_bytes_saved: u64,
+}
+
+/// An advisory diagnostic about how a [`Module`] was compiled, as returned by
+/// [`Module::compile_warnings`].
+#[non_exhaustive]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CompileWarning {
+    /// Tail calls were explicitly enabled with
+    /// [`Config::wasm_tail_call`](crate::Config::wasm_tail_call), but no
+    /// function in the module makes a tail call.
+    ///
+    /// This usually means that whatever produced the module emitted regular
+    /// calls where tail calls were expected, so none of its calls reuse the
+    /// caller's frame. It isn't reported when tail calls are only enabled by
+    /// default.
+    UnusedTailCallFlag,
+
+    /// The function is part of a cycle of tail calls with no way out, so
+    /// calling it never returns unless it traps or is interrupted.
+    ///
+    /// This is a best-effort lint for the simplest infinite loops: it's only
+    /// reported for functions which consist of straight-line code ending in a
+    /// `return_call`, such as a function which unconditionally tail calls
+    /// itself, and never for functions with a conditional base case. Each
+    /// function in the cycle is reported separately.
+    UnconditionalTailLoop {
+        /// The index of the function.
+        func_index: wasmtime_environ::FuncIndex,
+    },
+}
+
+impl fmt::Display for CompileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CompileWarning::UnusedTailCallFlag => f.write_str(
+                "tail calls were explicitly enabled but the module doesn't make any tail calls",
+            ),
+            CompileWarning::UnconditionalTailLoop { func_index } => write!(
+                f,
+                "function {} unconditionally tail calls itself, possibly through other \
+                 functions, and never returns",
+                func_index.as_u32()
+            ),
+        }
+    }
+}
+
 fn _assert_send_sync() {
     fn _assert<T: Send + Sync>() {}
     _assert::<Module>();
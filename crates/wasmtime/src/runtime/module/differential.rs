@@ -0,0 +1,139 @@
+//! Differential execution of functions compiled with and without tail-call
+//! stack check relaxation.
+
+use crate::prelude::*;
+use crate::{Config, Engine, Instance, Module, Store, Trap, Val};
+use core::mem;
+
+impl Module {
+    /// Runs the exported function `func` of the WebAssembly module `bytes`
+    /// twice, once compiled with
+    /// [`TailCallOptions::relaxed_stack_check`](crate::TailCallOptions::relaxed_stack_check)
+    /// enabled and once with it disabled, and checks that both runs produce the
+    /// same results or trap with the same [`Trap`] code.
+    ///
+    /// This is a testing API, for example for fuzzing, which exercises that
+    /// optimizing the frames of functions that only make tail calls doesn't
+    /// change the behavior of a program. The reference run also disables
+    /// [`TailCallOptions::self_recursion_loop`](crate::TailCallOptions::self_recursion_loop)
+    /// so that tail calls are always compiled as calls.
+    ///
+    /// Each run compiles `bytes` with its own [`Engine`], created from a copy
+    /// of `config`, and calls `func` with `inputs` in a fresh [`Store`]. A
+    /// [`Module`] can't be used for this because it's only ever compiled for
+    /// one engine and the original WebAssembly isn't retained, and a [`Store`]
+    /// only runs modules of its own engine. This means that `config` must
+    /// enable [`Config::wasm_tail_call`], the module must not have any imports,
+    /// and any reference-typed `inputs` must be null.
+    ///
+    /// The optimized run uses less stack than the reference run, so if only
+    /// the reference run overflows the stack the check is inconclusive and
+    /// this returns `Ok(())`. An overflow in only the optimized run is reported
+    /// as a mismatch.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the module fails to compile or instantiate, if
+    /// `func` isn't an exported function, or if the two runs disagree.
+    pub fn differential_tail_call_check(
+        config: &Config,
+        bytes: impl AsRef<[u8]>,
+        func: &str,
+        inputs: &[Val],
+    ) -> Result<()> {
+        let bytes = bytes.as_ref();
+        for input in inputs {
+            if let Some(r) = input.ref_() {
+                ensure!(
+                    r.is_null(),
+                    "differential tail-call checks only support null reference inputs"
+                );
+            }
+        }
+
+        let optimized = run(config, bytes, func, inputs, true)?;
+        let reference = run(config, bytes, func, inputs, false)?;
+
+        let same = match (&optimized, &reference) {
+            (Ok(a), Ok(b)) => a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_val(a, b)),
+            (_, Err(Outcome::Trap(Trap::StackOverflow))) => return Ok(()),
+            (Err(a), Err(b)) => a == b,
+            _ => false,
+        };
+        ensure!(
+            same,
+            "differential tail-call check of `{func}` failed: with the relaxed stack check \
+             the result was {optimized:?}, but without it the result was {reference:?}"
+        );
+        Ok(())
+    }
+}
+
+/// How a run that didn't return results ended.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Trap(Trap),
+    Error(String),
+}
+
+/// Compiles and instantiates `bytes` with the relaxed stack check set to
+/// `optimize`, then calls `func`.
+///
+/// The outer `Result` is for failures to get as far as calling `func`, and the
+/// inner one is the outcome of the call.
+fn run(
+    config: &Config,
+    bytes: &[u8],
+    func: &str,
+    inputs: &[Val],
+    optimize: bool,
+) -> Result<Result<Vec<Val>, Outcome>> {
+    let mut options = config.tail_call.clone();
+    options.relaxed_stack_check(optimize);
+    if !optimize {
+        options.require_optimization(false);
+        options.self_recursion_loop(false);
+    }
+    let mut config = config.clone();
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, bytes)?;
+    ensure!(
+        module.imports().len() == 0,
+        "differential tail-call checks only support modules without imports"
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let func = instance
+        .get_func(&mut store, func)
+        .ok_or_else(|| anyhow!("no exported function named `{func}`"))?;
+    let mut results = vec![Val::I32(0); func.ty(&store).results().len()];
+    Ok(match func.call(&mut store, inputs, &mut results) {
+        Ok(()) => Ok(results),
+        Err(e) => Err(match e.downcast_ref::<Trap>() {
+            Some(trap) => Outcome::Trap(*trap),
+            None => Outcome::Error(e.to_string()),
+        }),
+    })
+}
+
+/// Returns whether two values from different stores are the same.
+///
+/// References can't be compared across stores, so they're considered the
+/// same if they have the same type of reference and nullness.
+fn same_val(a: &Val, b: &Val) -> bool {
+    match (a, b) {
+        (Val::I32(a), Val::I32(b)) => a == b,
+        (Val::I64(a), Val::I64(b)) => a == b,
+        (Val::F32(a), Val::F32(b)) => a == b,
+        (Val::F64(a), Val::F64(b)) => a == b,
+        (Val::V128(a), Val::V128(b)) => a.as_u128() == b.as_u128(),
+        _ => match (a.ref_(), b.ref_()) {
+            (Some(x), Some(y)) => {
+                mem::discriminant(a) == mem::discriminant(b) && x.is_null() == y.is_null()
+            }
+            _ => false,
+        },
+    }
+}
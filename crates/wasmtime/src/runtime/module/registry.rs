@@ -6,7 +6,7 @@ use crate::component::Component;
 use crate::prelude::*;
 use crate::runtime::vm::VMWasmCallFunction;
 use crate::sync::{OnceLock, RwLock};
-use crate::{FrameInfo, Module, code_memory::CodeMemory};
+use crate::{CallClass, FrameInfo, Module, code_memory::CodeMemory};
 use alloc::collections::btree_map::{BTreeMap, Entry};
 use alloc::sync::Arc;
 use core::ptr::NonNull;
@@ -184,6 +184,16 @@ impl ModuleRegistry {
         Some((info, module))
     }
 
+    /// Returns the kinds of calls made by the Wasm function whose code
+    /// contains `pc`, or `None` if `pc` isn't within a defined Wasm function
+    /// or they weren't recorded.
+    pub(crate) fn lookup_call_class(&self, pc: usize) -> Option<CallClass> {
+        let (module, offset) = self.module_and_offset(pc)?;
+        let compiled_module = module.compiled_module();
+        let (index, _) = compiled_module.func_by_text_offset(offset)?;
+        compiled_module.func_call_class(index)
+    }
+
     pub fn wasm_to_array_trampoline(
         &self,
         sig: VMSharedTypeIndex,
@@ -110,7 +110,9 @@ use core::num::NonZeroU64;
 use core::ops::{Deref, DerefMut};
 use core::pin::Pin;
 use core::ptr::NonNull;
-use wasmtime_environ::{DefinedGlobalIndex, DefinedTableIndex, EntityRef, PrimaryMap, TripleExt};
+use wasmtime_environ::{
+    DefinedGlobalIndex, DefinedTableIndex, EntityRef, FuncIndex, PrimaryMap, TripleExt,
+};
 
 mod context;
 pub use self::context::*;
@@ -451,6 +453,17 @@ pub struct StoreOpaque {
     // until the reserve is empty.
     fuel_reserve: u64,
     pub(crate) fuel_yield_interval: Option<NonZeroU64>,
+    // The amount of tail-call fuel most recently given to this store, used to
+    // compute how much of it has been consumed since.
+    tail_call_fuel_budget: u64,
+    // The hook invoked for each `return_call` when `TailCallOptions::hooks`
+    // is enabled.
+    tail_call_hook:
+        Option<Box<dyn FnMut(&Module, FuncIndex, FuncIndex) -> Result<()> + Send + Sync>>,
+    // The functions which most recently made tail calls, most recent first,
+    // for coredumps. See `WasmCoreDump::tail_call_trail`.
+    #[cfg(feature = "coredump")]
+    tail_call_trail: alloc::collections::VecDeque<(InstanceId, FuncIndex)>,
     /// Indexed data within this `Store`, used to store information about
     /// globals, functions, memories, etc.
     store_data: StoreData,
@@ -681,6 +694,10 @@ impl<T> Store<T> {
             async_state: Default::default(),
             fuel_reserve: 0,
             fuel_yield_interval: None,
+            tail_call_fuel_budget: 0,
+            tail_call_hook: None,
+            #[cfg(feature = "coredump")]
+            tail_call_trail: Default::default(),
             store_data,
             traitobj: StorePtr(None),
             default_caller_vmctx: SendSyncPtr::new(NonNull::dangling()),
@@ -708,6 +725,22 @@ impl<T> Store<T> {
 
         inner.traitobj = StorePtr(Some(NonNull::from(&mut *inner)));
 
+        if let Some(fuel) = engine.config().tail_call.fuel {
+            inner.set_tail_call_fuel(fuel).unwrap();
+        }
+        // Coredumps record the trail of tail calls through the hook's libcall,
+        // so it's always made when both are enabled.
+        #[cfg(feature = "coredump")]
+        if engine.tunables().tail_call_hooks && engine.config().coredump_on_trap {
+            *inner.vm_store_context.tail_call_hook_active.get_mut() = 1;
+        }
+        if let Some(limit) = engine.config().tail_call.stack_limit {
+            *inner
+                .vm_store_context
+                .regular_call_depth_remaining
+                .get_mut() = limit;
+        }
+
         // Wasmtime uses the callee argument to host functions to learn about
         // the original pointer to the `Store` itself, allowing it to
         // reconstruct a `StoreContextMut<T>`. When we initially call a `Func`,
@@ -959,6 +992,71 @@ impl<T> Store<T> {
         self.inner.set_fuel(fuel)
     }
 
+    /// Returns the number of tail calls that WebAssembly has made in this
+    /// [`Store`] since its tail-call fuel was last set.
+    ///
+    /// Each `return_call`, `return_call_indirect`, and `return_call_ref`
+    /// consumes one unit of tail-call fuel, so this can be used to count the
+    /// tail calls made by a single invocation by resetting the fuel with
+    /// [`Store::set_tail_call_fuel`] beforehand.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if tail-call fuel is not enabled
+    /// via [`TailCallOptions::fuel`](crate::TailCallOptions::fuel).
+    pub fn tail_call_fuel_consumed(&self) -> Result<u64> {
+        self.inner.tail_call_fuel_consumed()
+    }
+
+    /// Sets the number of tail calls that WebAssembly may make in this
+    /// [`Store`] before trapping with
+    /// [`Trap::TailCallFuelExhausted`](crate::Trap::TailCallFuelExhausted).
+    ///
+    /// A [`Store`] starts with the amount of tail-call fuel configured with
+    /// [`TailCallOptions::fuel`](crate::TailCallOptions::fuel). Setting it
+    /// again also resets [`Store::tail_call_fuel_consumed`] to zero.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if tail-call fuel is not enabled
+    /// via [`TailCallOptions::fuel`](crate::TailCallOptions::fuel).
+    pub fn set_tail_call_fuel(&mut self, fuel: u64) -> Result<()> {
+        self.inner.set_tail_call_fuel(fuel)
+    }
+
+    /// Configures a function that runs whenever WebAssembly in this [`Store`]
+    /// makes a direct tail call with `return_call`.
+    ///
+    /// The function is passed the [`Module`] of the instance making the tail
+    /// call, along with the index of the calling function and of the function
+    /// being called, both within that module's function index space, before
+    /// the tail call is made. The callee may be imported, in which case its
+    /// index refers to that import rather than to the function that's
+    /// ultimately called. If the hook returns an error then the tail call
+    /// isn't made and the error is raised as a trap, as if the callee had
+    /// trapped.
+    ///
+    /// Only `return_call` is observed. Tail calls made with
+    /// `return_call_indirect` and `return_call_ref` don't invoke the hook,
+    /// since their callees aren't known by index.
+    ///
+    /// Compiled code only calls into the host for the hook once one has been
+    /// installed in the store, so stores without a hook only pay for checking
+    /// whether one is installed; see
+    /// [`TailCallOptions::hooks`](crate::TailCallOptions::hooks) for more
+    /// information.
+    ///
+    /// # Errors
+    ///
+    /// This function will return an error if tail-call hooks are not enabled
+    /// via [`TailCallOptions::hooks`](crate::TailCallOptions::hooks).
+    pub fn tail_call_hook(
+        &mut self,
+        hook: impl FnMut(&Module, FuncIndex, FuncIndex) -> Result<()> + Send + Sync + 'static,
+    ) -> Result<()> {
+        self.inner.set_tail_call_hook(Box::new(hook))
+    }
+
     /// Configures a [`Store`] to yield execution of async WebAssembly code
     /// periodically.
     ///
@@ -1190,6 +1288,14 @@ impl<'a, T> StoreContext<'a, T> {
     pub fn get_fuel(&self) -> Result<u64> {
         self.0.get_fuel()
     }
+
+    /// Returns the number of tail calls made since the tail-call fuel was last
+    /// set.
+    ///
+    /// For more information see [`Store::tail_call_fuel_consumed`].
+    pub fn tail_call_fuel_consumed(&self) -> Result<u64> {
+        self.0.tail_call_fuel_consumed()
+    }
 }
 
 impl<'a, T> StoreContextMut<'a, T> {
@@ -1238,6 +1344,21 @@ impl<'a, T> StoreContextMut<'a, T> {
         self.0.set_fuel(fuel)
     }
 
+    /// Returns the number of tail calls made since the tail-call fuel was last
+    /// set.
+    ///
+    /// For more information see [`Store::tail_call_fuel_consumed`].
+    pub fn tail_call_fuel_consumed(&self) -> Result<u64> {
+        self.0.tail_call_fuel_consumed()
+    }
+
+    /// Set the amount of tail-call fuel in this store.
+    ///
+    /// For more information see [`Store::set_tail_call_fuel`].
+    pub fn set_tail_call_fuel(&mut self, fuel: u64) -> Result<()> {
+        self.0.set_tail_call_fuel(fuel)
+    }
+
     /// Configures this `Store` to periodically yield while executing futures.
     ///
     /// For more information see [`Store::fuel_async_yield_interval`]
@@ -2065,6 +2186,78 @@ impl StoreOpaque {
         Ok(())
     }
 
+    pub fn tail_call_fuel_consumed(&self) -> Result<u64> {
+        anyhow::ensure!(
+            self.engine().tunables().tail_call_fuel,
+            "tail-call fuel is not configured in this store"
+        );
+        let remaining = unsafe { *self.vm_store_context.tail_call_fuel.get() };
+        Ok(self
+            .tail_call_fuel_budget
+            .saturating_sub(u64::try_from(remaining).unwrap()))
+    }
+
+    pub fn set_tail_call_fuel(&mut self, fuel: u64) -> Result<()> {
+        anyhow::ensure!(
+            self.engine().tunables().tail_call_fuel,
+            "tail-call fuel is not configured in this store"
+        );
+        let remaining = usize::try_from(fuel).unwrap_or(usize::MAX);
+        *self.vm_store_context.tail_call_fuel.get_mut() = remaining;
+        self.tail_call_fuel_budget = u64::try_from(remaining).unwrap();
+        Ok(())
+    }
+
+    pub fn set_tail_call_hook(
+        &mut self,
+        hook: Box<dyn FnMut(&Module, FuncIndex, FuncIndex) -> Result<()> + Send + Sync>,
+    ) -> Result<()> {
+        anyhow::ensure!(
+            self.engine().tunables().tail_call_hooks,
+            "tail-call hooks are not configured in this store"
+        );
+        self.tail_call_hook = Some(hook);
+        *self.vm_store_context.tail_call_hook_active.get_mut() = 1;
+        Ok(())
+    }
+
+    pub(crate) fn call_tail_call_hook(
+        &mut self,
+        instance: InstanceId,
+        from: FuncIndex,
+        to: FuncIndex,
+    ) -> Result<()> {
+        if self.tail_call_hook.is_none() {
+            return Ok(());
+        }
+        let module = self
+            .module_for_instance(StoreInstanceId::new(self.id(), instance))
+            .expect("tail calls are only made by real instances")
+            .clone();
+        match &mut self.tail_call_hook {
+            Some(hook) => hook(&module, from, to),
+            None => Ok(()),
+        }
+    }
+
+    /// Records that `from` made a tail call, if coredumps are enabled,
+    /// forgetting the oldest such call once the trail is full.
+    #[cfg(feature = "coredump")]
+    pub(crate) fn record_tail_call_trail(&mut self, instance: InstanceId, from: FuncIndex) {
+        if !self.engine().config().coredump_on_trap {
+            return;
+        }
+        if self.tail_call_trail.len() == super::coredump::TAIL_CALL_TRAIL_LEN {
+            self.tail_call_trail.pop_back();
+        }
+        self.tail_call_trail.push_front((instance, from));
+    }
+
+    #[cfg(feature = "coredump")]
+    pub(crate) fn tail_call_trail(&self) -> impl Iterator<Item = (InstanceId, FuncIndex)> + '_ {
+        self.tail_call_trail.iter().copied()
+    }
+
     pub fn fuel_async_yield_interval(&mut self, interval: Option<u64>) -> Result<()> {
         anyhow::ensure!(
             self.engine().tunables().consume_fuel,
@@ -6,7 +6,9 @@ use crate::prelude::*;
 use crate::store::StoreOpaque;
 use crate::{AsContext, Module};
 use core::fmt;
-use wasmtime_environ::{FilePos, demangle_function_name, demangle_function_name_or_index};
+use wasmtime_environ::{
+    CallClass, FilePos, demangle_function_name, demangle_function_name_or_index,
+};
 
 /// Representation of a WebAssembly trap and what caused it to occur.
 ///
@@ -118,6 +120,11 @@ pub(crate) fn from_runtime_box(
 
     if let Some(bt) = backtrace {
         let bt = WasmBacktrace::from_captured(store, bt, pc);
+        if error.downcast_ref::<Trap>() == Some(&Trap::StackOverflow) {
+            if let Some(site) = StackOverflowSite::from_backtrace(&bt) {
+                error = error.context(site);
+            }
+        }
         if !bt.wasm_trace.is_empty() {
             error = error.context(bt);
         }
@@ -186,6 +193,7 @@ pub(crate) fn from_runtime_box(
 pub struct WasmBacktrace {
     wasm_trace: Vec<FrameInfo>,
     hint_wasm_backtrace_details_env: bool,
+    had_tail_calls: bool,
     // This is currently only present for the `Debug` implementation for extra
     // context.
     _runtime_trace: crate::runtime::vm::Backtrace,
@@ -247,6 +255,7 @@ impl WasmBacktrace {
             WasmBacktrace {
                 wasm_trace: Vec::new(),
                 hint_wasm_backtrace_details_env: false,
+                had_tail_calls: false,
                 _runtime_trace: crate::runtime::vm::Backtrace::empty(),
             }
         }
@@ -270,6 +279,7 @@ impl WasmBacktrace {
     ) -> Self {
         let mut wasm_trace = Vec::<FrameInfo>::with_capacity(runtime_trace.frames().len());
         let mut hint_wasm_backtrace_details_env = false;
+        let mut had_tail_calls = false;
         let wasm_backtrace_details_env_used =
             store.engine().config().wasm_backtrace_details_env_used;
 
@@ -319,6 +329,11 @@ impl WasmBacktrace {
             if let Some((info, module)) = store.modules().lookup_frame_info(pc_to_lookup) {
                 wasm_trace.push(info);
 
+                // Tail calls reuse the caller's frame, so if this frame's
+                // module makes any tail calls then frames may be missing from
+                // the trace.
+                had_tail_calls |= module.compiled_module().has_tail_calls();
+
                 // If this frame has unparsed debug information and the
                 // store's configuration indicates that we were
                 // respecting the environment variable of whether to
@@ -339,6 +354,7 @@ impl WasmBacktrace {
             wasm_trace,
             _runtime_trace: runtime_trace,
             hint_wasm_backtrace_details_env,
+            had_tail_calls,
         }
     }
 
@@ -347,6 +363,25 @@ impl WasmBacktrace {
     pub fn frames(&self) -> &[FrameInfo] {
         self.wasm_trace.as_slice()
     }
+
+    /// Returns whether frames may be missing from this backtrace because they
+    /// were replaced by tail calls.
+    ///
+    /// A tail call (such as `return_call`) reuses the frame of the function
+    /// making the call, so the functions which made tail calls don't appear in
+    /// [`WasmBacktrace::frames`]. For example a trap in the middle of a deep
+    /// tail recursion will only have a single frame for the recursive
+    /// function. Wasmtime doesn't record which tail calls were actually
+    /// executed at runtime, so this is a conservative approximation: it
+    /// returns `true` if any frame in this backtrace belongs to a module that
+    /// contains tail calls.
+    ///
+    /// This can be used to render a marker such as `[tail calls elided]` when
+    /// formatting a backtrace. The [`Display`](fmt::Display) implementation
+    /// for this type includes a note when this returns `true`.
+    pub fn had_tail_calls(&self) -> bool {
+        self.had_tail_calls
+    }
 }
 
 impl fmt::Display for WasmBacktrace {
@@ -404,6 +439,9 @@ impl fmt::Display for WasmBacktrace {
                 }
             }
         }
+        if self.had_tail_calls {
+            write!(f, "\nnote: frames may have been elided by tail calls")?;
+        }
         if self.hint_wasm_backtrace_details_env {
             write!(
                 f,
@@ -428,6 +466,8 @@ pub struct FrameInfo {
     func_start: FilePos,
     instr: Option<FilePos>,
     symbols: Vec<FrameSymbol>,
+    call_class: Option<CallClass>,
+    is_tail_call_site: bool,
 }
 
 impl FrameInfo {
@@ -439,10 +479,13 @@ impl FrameInfo {
         let compiled_module = module.compiled_module();
         let (index, _func_offset) = compiled_module.func_by_text_offset(text_offset)?;
         let func_start = compiled_module.func_start_srcloc(index);
+        let call_class = compiled_module.func_call_class(index);
         let instr = wasmtime_environ::lookup_file_pos(
             compiled_module.code_memory().address_map_data(),
             text_offset,
         );
+        let is_tail_call_site =
+            instr.is_some_and(|instr| compiled_module.func_tail_call_sites(index).contains(&instr));
         let index = compiled_module.module().func_index(index);
         let func_index = index.as_u32();
         let func_name = compiled_module.func_name(index).map(|s| s.to_string());
@@ -502,6 +545,8 @@ impl FrameInfo {
             instr,
             func_start,
             symbols,
+            call_class,
+            is_tail_call_site,
         })
     }
 
@@ -570,6 +615,34 @@ impl FrameInfo {
         Some((instr_offset - self.func_start.file_offset()?) as usize)
     }
 
+    /// Returns the kinds of calls that this frame's function makes, or `None`
+    /// if its module was compiled without the tail call proposal enabled.
+    ///
+    /// Every frame in a backtrace other than the innermost one is stopped at a
+    /// regular call: tail calls replace the caller's frame, so functions that
+    /// only make tail calls never appear there.
+    pub fn call_class(&self) -> Option<CallClass> {
+        self.call_class
+    }
+
+    /// Returns whether this frame was stopped at a tail call, such as a
+    /// `return_call_indirect` which trapped because its table entry was null.
+    ///
+    /// Such a frame was handing itself over to its callee: had the tail call
+    /// completed, the callee would have reused the frame and this function
+    /// would no longer appear in the backtrace. Every other frame is a real
+    /// activation, stopped at a regular call or, for the innermost frame, at
+    /// the instruction which trapped. Frames of functions which completed
+    /// tail calls earlier are never present; see
+    /// [`WasmBacktrace::had_tail_calls`].
+    ///
+    /// This is always `false` when the module was compiled without an address
+    /// map; see
+    /// [`Config::generate_address_map`](crate::Config::generate_address_map).
+    pub fn is_tail_call_site(&self) -> bool {
+        self.is_tail_call_site
+    }
+
     /// Returns the debug symbols found, if any, for this function frame.
     ///
     /// When a wasm program is compiled with DWARF debug information then this
@@ -582,6 +655,127 @@ impl FrameInfo {
     }
 }
 
+/// Description of the innermost regular call on the stack when a
+/// [`Trap::StackOverflow`] occurred.
+///
+/// Tail calls reuse their caller's frame, so deep recursion usually exhausts
+/// the stack through regular (non-tail) calls. This structure identifies the
+/// WebAssembly function that made the innermost regular call still on the
+/// stack, which is useful for finding a recursive call that was meant to be a
+/// tail call, for example in a function that also makes tail calls (see
+/// [`CallClass::Mixed`]).
+///
+/// Note that this isn't necessarily the call that overflowed. A tail call
+/// still grows the stack when its callee needs more space for stack arguments
+/// than its caller had, and if that's what overflowed then the function which
+/// made the tail call was already replaced and isn't described here.
+///
+/// This is attached as context to the [`anyhow::Error`] returned for a stack
+/// overflow when a [`WasmBacktrace`] was captured, the overflowing call was
+/// made from WebAssembly, and the module of the function which made it
+/// contains tail calls. Without tail calls every frame on the stack made a
+/// regular call and the backtrace alone already describes the recursion. It's
+/// computed from the backtrace, so it's never attached when backtraces are
+/// disabled with [`Config::wasm_backtrace`](crate::Config::wasm_backtrace). It
+/// can be acquired with the [`anyhow::Error::downcast`] family of methods.
+///
+/// # Examples
+///
+/// ```
+/// # use wasmtime::*;
+/// # fn main() -> Result<()> {
+/// let mut config = Config::new();
+/// config.wasm_tail_call(true);
+/// let engine = Engine::new(&config)?;
+/// let module = Module::new(
+///     &engine,
+///     r#"
+///         (module
+///             (func $recurse (export "run")
+///                 call $recurse
+///                 return_call $recurse)
+///         )
+///     "#,
+/// )?;
+/// let mut store = Store::new(&engine, ());
+/// let instance = Instance::new(&mut store, &module, &[])?;
+/// let func = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+/// let error = func.call(&mut store, ()).unwrap_err();
+/// assert_eq!(*error.downcast_ref::<Trap>().unwrap(), Trap::StackOverflow);
+/// let site = error.downcast_ref::<StackOverflowSite>().unwrap();
+/// assert_eq!(site.func_name(), Some("recurse"));
+/// assert_eq!(site.call_class(), CallClass::Mixed);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct StackOverflowSite {
+    func_index: u32,
+    func_name: Option<String>,
+    call_class: CallClass,
+}
+
+impl StackOverflowSite {
+    /// Finds the site of a stack overflow from its backtrace, if the caller's
+    /// module contains any tail calls.
+    ///
+    /// This relies on the innermost frame of a stack overflow being the
+    /// function whose prologue found the stack exhausted, so that
+    /// `frames()[1]` is the innermost frame which made a regular call, since
+    /// any frames which made tail calls in between were replaced. That holds
+    /// for overflows detected by the stack limit check in a function's
+    /// prologue. If the overflow was instead caused by a tail call that grew
+    /// the stack for its callee's arguments, the frame which made that tail
+    /// call is gone and the frame found here is further up the stack.
+    fn from_backtrace(bt: &WasmBacktrace) -> Option<StackOverflowSite> {
+        let caller = bt.frames().get(1)?;
+        let module = caller.module().compiled_module();
+        if !module.finished_functions().any(|(idx, _)| {
+            module
+                .func_call_class(idx)
+                .is_some_and(|c| c.has_tail_calls())
+        }) {
+            return None;
+        }
+        Some(StackOverflowSite {
+            func_index: caller.func_index(),
+            func_name: caller.func_name().map(|s| s.to_string()),
+            call_class: caller.call_class()?,
+        })
+    }
+
+    /// Returns the index, in its module's function index space, of the
+    /// WebAssembly function that made the innermost regular call.
+    pub fn func_index(&self) -> u32 {
+        self.func_index
+    }
+
+    /// Returns a descriptive name of the function that made the innermost
+    /// regular call, if one is available.
+    ///
+    /// See [`FrameInfo::func_name`] for where this name comes from.
+    pub fn func_name(&self) -> Option<&str> {
+        self.func_name.as_deref()
+    }
+
+    /// Returns the kinds of calls that the function that made the innermost
+    /// regular call makes.
+    pub fn call_class(&self) -> CallClass {
+        self.call_class
+    }
+}
+
+impl fmt::Display for StackOverflowSite {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stack overflowed beneath a regular (non-tail) call in ")?;
+        demangle_function_name_or_index(f, self.func_name(), self.func_index as usize)?;
+        if self.call_class.has_tail_calls() {
+            write!(f, ", which also makes tail calls")?;
+        }
+        Ok(())
+    }
+}
+
 /// Debug information for a symbol that is attached to a [`FrameInfo`].
 ///
 /// When DWARF debug information is present in a wasm file then this structure
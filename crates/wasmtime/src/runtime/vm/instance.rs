@@ -3,6 +3,8 @@
 //! `InstanceHandle` is a reference-counting handle for an `Instance`.
 
 use crate::OpaqueRootScope;
+#[cfg(target_has_atomic = "64")]
+use crate::engine::TailCallCounter;
 use crate::prelude::*;
 use crate::runtime::vm::const_expr::{ConstEvalContext, ConstExprEvaluator};
 use crate::runtime::vm::export::Export;
@@ -30,10 +32,11 @@ use core::{mem, ptr};
 #[cfg(feature = "gc")]
 use wasmtime_environ::ModuleInternedTypeIndex;
 use wasmtime_environ::{
-    DataIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex, DefinedTagIndex,
-    ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex, HostPtr, MemoryIndex,
-    Module, PrimaryMap, PtrSize, TableIndex, TableInitialValue, TableSegmentElements, TagIndex,
-    Trap, VMCONTEXT_MAGIC, VMOffsets, VMSharedTypeIndex, packed_option::ReservedValue,
+    DataIndex, DefinedFuncIndex, DefinedGlobalIndex, DefinedMemoryIndex, DefinedTableIndex,
+    DefinedTagIndex, ElemIndex, EntityIndex, EntityRef, EntitySet, FuncIndex, GlobalIndex, HostPtr,
+    MemoryIndex, Module, PrimaryMap, PtrSize, SecondaryMap, TableIndex, TableInitialValue,
+    TableSegmentElements, TagIndex, Trap, VMCONTEXT_MAGIC, VMOffsets, VMSharedTypeIndex,
+    packed_option::ReservedValue,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::Wmemcheck;
@@ -129,6 +132,15 @@ pub struct Instance {
     #[cfg(feature = "wmemcheck")]
     pub(crate) wmemcheck_state: Option<Wmemcheck>,
 
+    /// The number of tail calls made by each defined function, when tail-call
+    /// profiling is enabled.
+    tail_call_counts: SecondaryMap<DefinedFuncIndex, u64>,
+
+    /// The total number of tail calls made by this instance, when tail-call
+    /// profiling is enabled, as summed by `Engine::tail_calls_executed`.
+    #[cfg(target_has_atomic = "64")]
+    tail_call_counter: Option<TailCallCounter>,
+
     /// Self-pointer back to `Store<T>` and its functions. Not present for
     /// the brief time that `Store<T>` is itself being created. Also not
     /// present for some niche uses that are disconnected from stores (e.g.
@@ -187,6 +199,13 @@ impl Instance {
                     None
                 }
             },
+            tail_call_counts: SecondaryMap::new(),
+            #[cfg(target_has_atomic = "64")]
+            tail_call_counter: if req.store.engine().tunables().tail_call_profiling {
+                Some(req.store.engine().new_tail_call_counter())
+            } else {
+                None
+            },
             store: None,
             vmctx: OwnedVMContext::new(),
         });
@@ -1545,6 +1564,29 @@ impl Instance {
         // SAFETY: see `store_mut` above.
         unsafe { &mut self.get_unchecked_mut().wmemcheck_state }
     }
+
+    /// Returns the number of tail calls made by each defined function in this
+    /// instance when tail-call profiling is enabled.
+    pub(crate) fn tail_call_counts(&self) -> &SecondaryMap<DefinedFuncIndex, u64> {
+        &self.tail_call_counts
+    }
+
+    pub(crate) fn tail_call_counts_mut(
+        self: Pin<&mut Self>,
+    ) -> &mut SecondaryMap<DefinedFuncIndex, u64> {
+        // SAFETY: see `store_mut` above.
+        unsafe { &mut self.get_unchecked_mut().tail_call_counts }
+    }
+
+    /// Counts a tail call made by the defined function `func` when tail-call
+    /// profiling is enabled.
+    pub(crate) fn record_tail_call(mut self: Pin<&mut Self>, func: DefinedFuncIndex) {
+        self.as_mut().tail_call_counts_mut()[func] += 1;
+        #[cfg(target_has_atomic = "64")]
+        if let Some(counter) = &self.tail_call_counter {
+            counter.increment();
+        }
+    }
 }
 
 // SAFETY: `layout` should describe this accurately and `OwnedVMContext` is the
@@ -70,8 +70,8 @@ use core::ptr::NonNull;
 #[cfg(feature = "threads")]
 use core::time::Duration;
 use wasmtime_environ::{
-    DataIndex, DefinedMemoryIndex, DefinedTableIndex, ElemIndex, FuncIndex, MemoryIndex,
-    TableIndex, Trap,
+    DataIndex, DefinedFuncIndex, DefinedMemoryIndex, DefinedTableIndex, ElemIndex, FuncIndex,
+    MemoryIndex, TableIndex, Trap,
 };
 #[cfg(feature = "wmemcheck")]
 use wasmtime_wmemcheck::AccessError::{
@@ -1304,6 +1304,36 @@ unsafe impl HostResultHasUnwindSentinel for NextEpoch {
     }
 }
 
+// Hook for counting the tail calls made by a function when tail-call profiling
+// is enabled.
+fn tail_call_profile(store: &mut dyn VMStore, instance: InstanceId, func: u32) {
+    let func = DefinedFuncIndex::from_u32(func);
+    store.instance_mut(instance).record_tail_call(func);
+    #[cfg(feature = "coredump")]
+    {
+        let func = store.instance(instance).env_module().func_index(func);
+        store.record_tail_call_trail(instance, func);
+    }
+}
+
+// Hook for observing a `return_call` from `from` to `to` when tail-call hooks
+// are enabled.
+fn tail_call_hook(
+    store: &mut dyn VMStore,
+    instance: InstanceId,
+    from: u32,
+    to: u32,
+) -> Result<()> {
+    let from = FuncIndex::from_u32(from);
+    // With profiling enabled this tail call was already recorded by
+    // `tail_call_profile`.
+    #[cfg(feature = "coredump")]
+    if !store.engine().tunables().tail_call_profiling {
+        store.record_tail_call_trail(instance, from);
+    }
+    store.call_tail_call_hook(instance, from, FuncIndex::from_u32(to))
+}
+
 // Hook for validating malloc using wmemcheck_state.
 #[cfg(feature = "wmemcheck")]
 fn check_malloc(store: &mut dyn VMStore, instance: InstanceId, addr: u32, len: u32) -> Result<()> {
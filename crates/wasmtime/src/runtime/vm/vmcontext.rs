@@ -1160,6 +1160,44 @@ pub struct VMStoreContext {
     /// on `VMStackChain` for details.
     pub stack_chain: UnsafeCell<VMStackChain>,
 
+    /// The number of tail calls that WebAssembly may still make before
+    /// trapping.
+    ///
+    /// This field is only read or modified if wasm is configured to consume
+    /// tail-call fuel, see `TailCallOptions::fuel`.
+    pub tail_call_fuel: UnsafeCell<usize>,
+
+    /// The number of further regular, non-tail calls that WebAssembly may
+    /// nest before trapping.
+    ///
+    /// This field is only read or modified if wasm is configured to limit
+    /// the depth of regular calls, see `TailCallOptions::stack_limit`.
+    pub regular_call_depth_remaining: UnsafeCell<usize>,
+
+    /// The number of consecutive tail calls made since the last regular call
+    /// or entry into WebAssembly.
+    ///
+    /// This field is only read or modified if wasm is configured to limit
+    /// the length of chains of tail calls, see
+    /// `TailCallOptions::max_chain_length`.
+    pub tail_call_chain_length: UnsafeCell<usize>,
+
+    /// Nonzero if the most recent call out of WebAssembly to a function which
+    /// may be a host function was a tail call.
+    ///
+    /// This field is only modified if wasm is configured to track how host
+    /// functions are called, see `TailCallOptions::host_tracking`, and is
+    /// read by `Caller::is_tail_called`.
+    pub host_call_is_tail: UnsafeCell<usize>,
+
+    /// Nonzero if each `return_call` should call into the host to invoke the
+    /// store's tail-call hook.
+    ///
+    /// This field is only read if wasm is configured with tail-call hooks,
+    /// see `TailCallOptions::hooks`, and is set once a hook is installed so
+    /// that tail calls don't leave wasm while there's nothing to invoke.
+    pub tail_call_hook_active: UnsafeCell<usize>,
+
     /// The range, in addresses, of the guard page that is currently in use.
     ///
     /// This field is used when signal handlers are run to determine whether a
@@ -1250,6 +1288,11 @@ impl Default for VMStoreContext {
             last_wasm_exit_pc: UnsafeCell::new(0),
             last_wasm_entry_fp: UnsafeCell::new(0),
             stack_chain: UnsafeCell::new(VMStackChain::Absent),
+            tail_call_fuel: UnsafeCell::new(0),
+            regular_call_depth_remaining: UnsafeCell::new(0),
+            tail_call_chain_length: UnsafeCell::new(0),
+            host_call_is_tail: UnsafeCell::new(0),
+            tail_call_hook_active: UnsafeCell::new(0),
             async_guard_range: ptr::null_mut()..ptr::null_mut(),
         }
     }
@@ -1304,6 +1347,26 @@ mod test_vmstore_context {
         assert_eq!(
             offset_of!(VMStoreContext, stack_chain),
             usize::from(offsets.ptr.vmstore_context_stack_chain())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, tail_call_fuel),
+            usize::from(offsets.ptr.vmstore_context_tail_call_fuel())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, regular_call_depth_remaining),
+            usize::from(offsets.ptr.vmstore_context_regular_call_depth_remaining())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, tail_call_chain_length),
+            usize::from(offsets.ptr.vmstore_context_tail_call_chain_length())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, host_call_is_tail),
+            usize::from(offsets.ptr.vmstore_context_host_call_is_tail())
+        );
+        assert_eq!(
+            offset_of!(VMStoreContext, tail_call_hook_active),
+            usize::from(offsets.ptr.vmstore_context_tail_call_hook_active())
         )
     }
 }
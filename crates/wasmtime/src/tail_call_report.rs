@@ -0,0 +1,145 @@
+//! Reports of how the tail calls of a module's functions were compiled.
+
+use crate::prelude::*;
+use alloc::collections::BTreeMap;
+use core::fmt;
+use serde_derive::Serialize;
+use wasmtime_environ::{CallClass, EntityIndex, FuncIndex};
+
+/// A report of how each function defined in a module was classified by the
+/// kinds of calls it makes and whether its frame was optimized for tail calls.
+///
+/// This is produced by [`Engine::precompile_module_with_report`] and
+/// [`Module::tail_call_report`], and is also what
+/// `wasmtime compile --emit-tail-call-report` prints when the CLI is built with
+/// its `tail-call-report` feature. It's intended to be archived alongside
+/// compiled artifacts so that optimization decisions can be diffed across
+/// toolchain versions.
+///
+/// The [`Display`](fmt::Display) implementation prints a table with a header
+/// row and one row per function, and [`TailCallReport::to_json`] produces a
+/// JSON array with one object per function. Each object has the following
+/// fields:
+///
+/// * `index` - the function's index in the module's function index space.
+/// * `exports` - the names the function is exported under, if any.
+/// * `class` - the function's [`CallClass`], such as `"TailCallOnly"`.
+/// * `frame_optimized` - whether the function's frame was optimized because
+///   it only makes tail calls; see
+///   [`TailCallOptions::relaxed_stack_check`](crate::TailCallOptions::relaxed_stack_check).
+/// * `stack_bytes_saved` - the number of bytes of stack that optimization
+///   saved, or `0` if it didn't apply.
+///
+/// [`Engine::precompile_module_with_report`]: crate::Engine::precompile_module_with_report
+/// [`Module::tail_call_report`]: crate::Module::tail_call_report
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct TailCallReport {
+    functions: Vec<TailCallReportFunction>,
+}
+
+/// The entry for a single function in a [`TailCallReport`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct TailCallReportFunction {
+    /// The function's index in the module's function index space.
+    pub index: u32,
+    /// The names the function is exported under.
+    pub exports: Vec<String>,
+    /// The function's [`CallClass`], as its variant's name.
+    pub class: String,
+    /// Whether the function's frame was optimized for tail calls.
+    pub frame_optimized: bool,
+    /// The number of bytes of stack saved by optimizing the function's frame,
+    /// or `0` if it wasn't optimized.
+    pub stack_bytes_saved: u32,
+}
+
+impl TailCallReport {
+    /// Creates a report for `module` given each of its defined functions'
+    /// index, call class, and frame savings, in index order.
+    pub(crate) fn new(
+        module: &wasmtime_environ::Module,
+        funcs: impl IntoIterator<Item = (FuncIndex, CallClass, Option<u32>)>,
+    ) -> TailCallReport {
+        let mut exports = BTreeMap::<FuncIndex, Vec<String>>::new();
+        for (name, entity) in module.exports.iter() {
+            if let EntityIndex::Function(func) = *entity {
+                exports.entry(func).or_default().push(name.clone());
+            }
+        }
+        let functions = funcs
+            .into_iter()
+            .map(|(index, class, saved)| TailCallReportFunction {
+                index: index.as_u32(),
+                exports: exports.remove(&index).unwrap_or_default(),
+                class: format!("{class:?}"),
+                frame_optimized: saved.is_some(),
+                stack_bytes_saved: saved.unwrap_or(0),
+            })
+            .collect();
+        TailCallReport { functions }
+    }
+
+    /// Returns the entry for each function defined in the module, in index
+    /// order.
+    pub fn functions(&self) -> &[TailCallReportFunction] {
+        &self.functions
+    }
+
+    /// Returns this report as a pretty-printed JSON array with one object per
+    /// function.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+impl fmt::Display for TailCallReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self
+            .functions
+            .iter()
+            .map(|func| {
+                let exports = func
+                    .exports
+                    .iter()
+                    .map(|name| format!("{name:?}"))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                [
+                    format!("func[{}]", func.index),
+                    func.class.clone(),
+                    if func.frame_optimized { "yes" } else { "no" }.to_string(),
+                    func.stack_bytes_saved.to_string(),
+                    exports,
+                ]
+            })
+            .collect::<Vec<_>>();
+        let header = [
+            "function",
+            "class",
+            "frame optimized",
+            "bytes saved",
+            "exports",
+        ];
+        let mut widths = header.map(str::len);
+        for row in rows.iter() {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.len());
+            }
+        }
+
+        let header = header.map(String::from);
+        for row in core::iter::once(&header).chain(rows.iter()) {
+            let [func, class, optimized, saved, exports] = row;
+            let line = format!(
+                "{func:<w0$}  {class:<w1$}  {optimized:<w2$}  {saved:>w3$}  {exports}",
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            );
+            writeln!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -7,8 +7,9 @@ use std::sync::Mutex;
 use wasmparser::FuncValidatorAllocations;
 use wasmtime_cranelift::CompiledFunction;
 use wasmtime_environ::{
-    CompileError, CompiledFunctionBody, DefinedFuncIndex, FuncKey, FunctionBodyData, FunctionLoc,
-    ModuleTranslation, ModuleTypesBuilder, PrimaryMap, StaticModuleIndex, Tunables, VMOffsets,
+    CallConv, CompileError, CompiledFunctionBody, DefinedFuncIndex, FuncKey, FunctionBodyData,
+    FunctionLoc, ModuleTranslation, ModuleTypesBuilder, PrimaryMap, StaticModuleIndex, Tunables,
+    VMOffsets,
 };
 use winch_codegen::{BuiltinFunctions, CallingConvention, TargetIsa};
 
@@ -162,6 +163,11 @@ impl wasmtime_environ::Compiler for Compiler {
             code: box_dyn_any_compiled_function(func),
             // TODO: Winch doesn't support GC objects and stack maps and all that yet.
             needs_gc_heap: false,
+            tail_call_deopt: None,
+            tail_call_frame_savings: None,
+            self_tail_loop: false,
+            call_conv: Some(CallConv::Winch),
+            frame_layout: None,
         })
     }
 
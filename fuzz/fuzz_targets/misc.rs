@@ -67,6 +67,7 @@ run_fuzzers! {
     stacks
     api_calls
     dominator_tree
+    tail_calls
 }
 
 fn pulley_roundtrip(u: Unstructured<'_>) -> Result<()> {
@@ -96,6 +97,10 @@ fn api_calls(u: Unstructured<'_>) -> Result<()> {
     Ok(())
 }
 
+fn tail_calls(mut u: Unstructured<'_>) -> Result<()> {
+    wasmtime_fuzzing::oracles::check_tail_calls(&mut u)
+}
+
 fn dominator_tree(mut data: Unstructured<'_>) -> Result<()> {
     use cranelift_codegen::cursor::{Cursor, FuncCursor};
     use cranelift_codegen::dominator_tree::{DominatorTree, SimpleDominatorTree};
@@ -43,6 +43,22 @@ pub struct CompileCommand {
     #[arg(long = "emit-clif", value_name = "PATH")]
     pub emit_clif: Option<PathBuf>,
 
+    /// Print a report of each function's tail-call classification and
+    /// whether its frame was optimized for tail calls.
+    #[cfg(feature = "tail-call-report")]
+    #[arg(long)]
+    pub emit_tail_call_report: bool,
+
+    /// The format of the report printed by `--emit-tail-call-report`.
+    #[cfg(feature = "tail-call-report")]
+    #[arg(
+        long,
+        default_value = "text",
+        value_name = "FORMAT",
+        requires = "emit_tail_call_report"
+    )]
+    pub format: ReportFormat,
+
     /// The path of the WebAssembly to compile
     #[arg(index = 1, value_name = "MODULE")]
     pub module: PathBuf,
@@ -88,6 +104,11 @@ impl CompileCommand {
             output
         });
 
+        #[cfg(feature = "tail-call-report")]
+        if self.emit_tail_call_report && matches!(code.hint(), Some(CodeHint::Component)) {
+            bail!("'--emit-tail-call-report' is only supported for core wasm modules");
+        }
+
         let output_bytes = match code.hint() {
             #[cfg(feature = "component-model")]
             Some(CodeHint::Component) => code.compile_component_serialized()?,
@@ -95,8 +116,18 @@ impl CompileCommand {
             Some(CodeHint::Component) => {
                 bail!("component model support was disabled at compile time")
             }
+            #[cfg(feature = "tail-call-report")]
+            Some(CodeHint::Module) | None if self.emit_tail_call_report => {
+                let (bytes, report) = code.compile_module_serialized_with_report()?;
+                match self.format {
+                    ReportFormat::Text => print!("{report}"),
+                    ReportFormat::Json => println!("{}", report.to_json()),
+                }
+                bytes
+            }
             Some(CodeHint::Module) | None => code.compile_module_serialized()?,
         };
+
         fs::write(&output, output_bytes)
             .with_context(|| format!("failed to write output: {}", output.display()))?;
 
@@ -104,6 +135,16 @@ impl CompileCommand {
     }
 }
 
+/// The format of the report printed by `--emit-tail-call-report`.
+#[cfg(feature = "tail-call-report")]
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// A human-readable table with one row per function.
+    Text,
+    /// A JSON array with one object per function.
+    Json,
+}
+
 #[cfg(all(test, not(miri)))]
 mod test {
     use super::*;
@@ -142,6 +183,59 @@ mod test {
         Ok(())
     }
 
+    #[cfg(feature = "tail-call-report")]
+    #[test]
+    fn test_tail_call_report() -> Result<()> {
+        let (mut input, input_path) = NamedTempFile::new()?.into_parts();
+        input.write_all(
+            "(module
+                (func $leaf (export \"leaf\") (param i32) (result i32) local.get 0)
+                (func $countdown (export \"countdown\") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $countdown
+                    end)
+            )"
+            .as_bytes(),
+        )?;
+        drop(input);
+
+        let output_path = NamedTempFile::new()?.into_temp_path();
+
+        let command = CompileCommand::try_parse_from(vec![
+            "compile",
+            "-Dlogging=n",
+            "--emit-tail-call-report",
+            "--format",
+            "json",
+            "-o",
+            output_path.to_str().unwrap(),
+            input_path.to_str().unwrap(),
+        ])?;
+        assert_eq!(command.format, ReportFormat::Json);
+
+        command.execute()?;
+
+        // `--format` is only meaningful with a report.
+        assert!(
+            CompileCommand::try_parse_from(vec![
+                "compile",
+                "--format",
+                "json",
+                input_path.to_str().unwrap(),
+            ])
+            .is_err()
+        );
+
+        Ok(())
+    }
+
     #[cfg(target_arch = "x86_64")]
     #[test]
     fn test_x64_flags_compile() -> Result<()> {
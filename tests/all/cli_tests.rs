@@ -430,6 +430,56 @@ fn run_cwasm() -> Result<()> {
     Ok(())
 }
 
+#[cfg(feature = "tail-call-report")]
+#[test]
+fn compile_emit_tail_call_report() -> Result<()> {
+    let td = TempDir::new()?;
+    let cwasm = td.path().join("foo.cwasm");
+    let stdout = run_wasmtime(&[
+        "compile",
+        "--emit-tail-call-report",
+        "--format",
+        "json",
+        "tests/all/cli_tests/tail_call_report.wat",
+        "-o",
+        cwasm.to_str().unwrap(),
+    ])?;
+    let report: serde_json::Value = serde_json::from_str(&stdout)?;
+    assert_eq!(report[0]["index"], 0);
+    assert_eq!(report[0]["exports"], serde_json::json!(["leaf"]));
+    assert_eq!(report[0]["class"], "Leaf");
+    assert_eq!(report[0]["frame_optimized"], false);
+    assert_eq!(report[1]["index"], 1);
+    assert_eq!(report[1]["exports"], serde_json::json!(["countdown"]));
+    assert_eq!(report[1]["class"], "TailCallOnly");
+    assert_eq!(report.as_array().unwrap().len(), 2);
+
+    // The compiled module is still written alongside the report.
+    let stdout = run_wasmtime(&[
+        "run",
+        "--allow-precompiled",
+        "--invoke",
+        "countdown",
+        cwasm.to_str().unwrap(),
+        "10",
+    ])?;
+    assert_eq!(stdout, "0\n");
+
+    let stdout = run_wasmtime(&[
+        "compile",
+        "--emit-tail-call-report",
+        "tests/all/cli_tests/tail_call_report.wat",
+        "-o",
+        cwasm.to_str().unwrap(),
+    ])?;
+    let lines = stdout.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("function"));
+    assert!(lines[1].starts_with("func[0]") && lines[1].contains("Leaf"));
+    assert!(lines[2].starts_with("func[1]") && lines[2].contains("TailCallOnly"));
+    Ok(())
+}
+
 #[cfg(unix)]
 #[test]
 fn hello_wasi_snapshot0_from_stdin() -> Result<()> {
@@ -0,0 +1,15 @@
+(module
+  (func $leaf (export "leaf") (param i32) (result i32)
+    local.get 0)
+  (func $countdown (export "countdown") (param i32) (result i32)
+    local.get 0
+    i32.eqz
+    if (result i32)
+      i32.const 0
+    else
+      local.get 0
+      i32.const 1
+      i32.sub
+      return_call $countdown
+    end)
+)
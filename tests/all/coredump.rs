@@ -258,3 +258,87 @@ fn multiple_globals_memories_and_instances() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn coredump_has_tail_call_trail() -> Result<()> {
+    let wat = r#"
+      (module
+          (table 1 funcref)
+          (elem (i32.const 0) $d)
+          (func $a (export "a") (param i32)
+              call $b
+          )
+          (func $b
+              return_call $c
+          )
+          (func $c
+              (return_call_indirect (i32.const 0))
+          )
+          (func $d
+              unreachable
+          )
+          (func $countdown (export "countdown") (param i32)
+              (if (i32.eqz (local.get 0)) (then unreachable))
+              (return_call $countdown (i32.sub (local.get 0) (i32.const 1)))
+          )
+      )
+    "#;
+
+    let trail = |profiling: bool, hooks: bool, export: &str| -> Result<(Vec<u32>, String)> {
+        let mut options = TailCallOptions::new();
+        options.profiling(profiling).hooks(hooks);
+        let mut config = Config::default();
+        config
+            .coredump_on_trap(true)
+            .wasm_tail_call(true)
+            .tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let mut store = Store::<()>::new(&engine, ());
+        let module = Module::new(&engine, wat)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let e = instance
+            .get_typed_func::<i32, ()>(&mut store, export)?
+            .call(&mut store, 20)
+            .unwrap_err();
+        let cd = e.downcast_ref::<WasmCoreDump>().unwrap();
+
+        // The functions which made tail calls are missing from the stack.
+        let frames = cd
+            .frames()
+            .iter()
+            .map(|f| f.func_index())
+            .collect::<Vec<_>>();
+        if export == "a" {
+            assert_eq!(frames, [3, 0]);
+        }
+
+        for entry in cd.tail_call_trail() {
+            assert!(Module::same(entry.module(), &module));
+            assert!(Module::same(entry.instance().module(&store), &module));
+        }
+        let trail = cd
+            .tail_call_trail()
+            .iter()
+            .map(|f| f.func_index().as_u32())
+            .collect();
+        Ok((trail, cd.to_string()))
+    };
+
+    let (profiled, display) = trail(true, false, "a")?;
+    assert_eq!(profiled, [2, 1]);
+    assert!(display.contains("tail call trail:\n  <module>!func[2]\n  <module>!func[1]\n"));
+
+    // Hooks only observe `return_call`.
+    assert_eq!(trail(false, true, "a")?.0, [1]);
+    assert_eq!(trail(true, true, "a")?.0, [2, 1]);
+
+    let (untracked, display) = trail(false, false, "a")?;
+    assert!(untracked.is_empty());
+    assert!(!display.contains("tail call trail:"));
+
+    // Only the most recent tail calls are kept.
+    assert_eq!(trail(true, false, "countdown")?.0, [4; 16]);
+
+    Ok(())
+}
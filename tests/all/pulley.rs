@@ -511,3 +511,66 @@ fn decode_unaligned() -> Result<()> {
 
     Ok(())
 }
+
+// Tail calls reuse the interpreter's frame, so deep tail recursion runs within
+// a small interpreter stack where the same recursion made with regular calls
+// overflows it.
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_calls_run_in_constant_stack() -> Result<()> {
+    let mut config = pulley_config();
+    config.wasm_tail_call(true).max_wasm_stack(64 << 10);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $ty (func (param i32) (result i32)))
+                (table 1 funcref)
+                (elem (i32.const 0) $countdown_indirect)
+
+                (func $countdown (export "countdown") (param i32) (result i32)
+                    (if (i32.eqz (local.get 0)) (then (return (i32.const 42))))
+                    (return_call $countdown (i32.sub (local.get 0) (i32.const 1))))
+
+                (func $countdown_indirect (export "countdown-indirect")
+                    (param i32) (result i32)
+                    (if (i32.eqz (local.get 0)) (then (return (i32.const 42))))
+                    (return_call_indirect (type $ty)
+                        (i32.sub (local.get 0) (i32.const 1))
+                        (i32.const 0)))
+
+                (func $even (export "even") (param i32) (result i32)
+                    (if (i32.eqz (local.get 0)) (then (return (i32.const 1))))
+                    (return_call $odd (i32.sub (local.get 0) (i32.const 1))))
+                (func $odd (param i32) (result i32)
+                    (if (i32.eqz (local.get 0)) (then (return (i32.const 0))))
+                    (return_call $even (i32.sub (local.get 0) (i32.const 1))))
+
+                (func $recurse (export "recurse") (param i32) (result i32)
+                    (if (i32.eqz (local.get 0)) (then (return (i32.const 42))))
+                    (call $recurse (i32.sub (local.get 0) (i32.const 1))))
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    let depth = 100_000;
+    for (export, n, expected) in [
+        ("countdown", depth, 42),
+        ("countdown-indirect", depth, 42),
+        ("even", depth, 1),
+        ("even", depth + 1, 0),
+    ] {
+        let func = instance.get_typed_func::<i32, i32>(&mut store, export)?;
+        assert_eq!(func.call(&mut store, n)?, expected, "{export}({n})");
+    }
+
+    let recurse = instance.get_typed_func::<i32, i32>(&mut store, "recurse")?;
+    assert_eq!(recurse.call(&mut store, 10)?, 42);
+    let e = recurse.call(&mut store, depth).unwrap_err();
+    assert_eq!(e.downcast_ref::<Trap>(), Some(&Trap::StackOverflow));
+
+    Ok(())
+}
@@ -0,0 +1,6275 @@
+use std::mem;
+use std::sync::{Arc, Mutex};
+use wasmtime::*;
+use wasmtime_test_macros::wasmtime_test;
+
+fn config() -> Config {
+    let mut config = Config::new();
+    config.wasm_tail_call(true);
+    config
+}
+
+fn engine() -> Engine {
+    Engine::new(&config()).unwrap()
+}
+
+/// Registers a [`TailCallOptions::on_deopt`] callback with `options` which
+/// collects every reported deopt.
+fn collect_deopts(options: &mut TailCallOptions) -> Arc<Mutex<Vec<TailCallDeoptInfo>>> {
+    let deopts = Arc::new(Mutex::new(Vec::new()));
+    options.on_deopt({
+        let deopts = deopts.clone();
+        move |info| deopts.lock().unwrap().push(info)
+    });
+    deopts
+}
+
+/// A function which counts its argument down to zero with `return_call`, to
+/// be spliced into test modules with `format!`.
+const COUNTDOWN: &str = r#"
+    (func $countdown (param i32) (result i32)
+        local.get 0
+        i32.eqz
+        if (result i32)
+            i32.const 0
+        else
+            local.get 0
+            i32.const 1
+            i32.sub
+            return_call $countdown
+        end)
+"#;
+
+/// Compiles a module which only defines [`COUNTDOWN`], exported as
+/// `countdown`.
+fn countdown_module(engine: &Engine) -> Result<Module> {
+    Module::new(
+        engine,
+        format!(r#"(module {COUNTDOWN} (export "countdown" (func $countdown)))"#),
+    )
+}
+
+/// Returns the call classes of each defined function in `module`, in function
+/// index order.
+fn call_classes(module: &Module) -> Vec<CallClass> {
+    module
+        .function_call_classes()
+        .into_iter()
+        .map(|(_, class)| class)
+        .collect()
+}
+
+/// Returns the smallest `Config::max_wasm_stack`, to within 256 bytes, with
+/// which calling `export` in `module` with `params` doesn't overflow the
+/// stack.
+///
+/// The `module` must have been compiled with `config`. The result is
+/// effectively the high-water mark of native stack used by the call.
+fn min_wasm_stack(config: &Config, module: &Module, export: &str, params: &[Val]) -> Result<usize> {
+    let bytes = module.serialize()?;
+    let succeeds = |max_wasm_stack: usize| -> Result<bool> {
+        let mut config = config.clone();
+        config.max_wasm_stack(max_wasm_stack);
+        let engine = Engine::new(&config)?;
+        let module = unsafe { Module::deserialize(&engine, &bytes)? };
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let func = instance.get_func(&mut store, export).unwrap();
+        let mut results = vec![Val::I32(0); func.ty(&store).results().len()];
+        match func.call(&mut store, params, &mut results) {
+            Ok(()) => Ok(true),
+            Err(e) if e.downcast_ref::<Trap>() == Some(&Trap::StackOverflow) => Ok(false),
+            Err(e) => Err(e),
+        }
+    };
+
+    let (mut lo, mut hi) = (0, 4 << 20);
+    assert!(succeeds(hi)?, "`{export}` overflows even a {hi}-byte stack");
+    while hi - lo > 256 {
+        let mid = lo + (hi - lo) / 2;
+        if succeeds(mid)? {
+            hi = mid;
+        } else {
+            lo = mid;
+        }
+    }
+    Ok(hi)
+}
+
+/// Asserts that calling `export` in `module` uses the same amount of native
+/// stack, to within a small constant, with both `shallow` and `deep`
+/// parameters.
+///
+/// Use this with parameters that cause very different recursion depths to
+/// check that the recursion doesn't grow the stack. The `module` must have been
+/// compiled with `config`.
+fn assert_constant_stack(
+    config: &Config,
+    module: &Module,
+    export: &str,
+    shallow: &[Val],
+    deep: &[Val],
+) {
+    let shallow = min_wasm_stack(config, module, export, shallow).unwrap();
+    let deep = min_wasm_stack(config, module, export, deep).unwrap();
+    assert!(
+        deep <= shallow + 1024,
+        "`{export}` needed {shallow} bytes of stack when shallow but {deep} bytes when deep"
+    );
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn leaf_functions() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func (export "add") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func (export "nop"))
+            )
+        "#,
+    )?;
+    assert_eq!(call_classes(&module), [CallClass::Leaf, CallClass::Leaf]);
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let add = instance.get_func(&mut store, "add").unwrap();
+    assert_eq!(add.tail_call_class(&store), Some(CallClass::Leaf));
+    let add = add.typed::<(i32, i32), i32>(&store)?;
+    assert_eq!(add.call(&mut store, (1, 2))?, 3);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn mixed_call_types() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host))
+                (func $helper (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add)
+                (func $regular (export "regular") (param i32) (result i32)
+                    local.get 0
+                    call $helper)
+                (func $tail (export "tail") (param i32) (result i32)
+                    local.get 0
+                    return_call $helper)
+                (func $mixed (export "mixed") (param i32) (result i32)
+                    call $host
+                    local.get 0
+                    return_call $helper)
+            )
+        "#,
+    )?;
+
+    // Imported functions are not classified, so the first entry is `$helper`
+    // at function index 1.
+    let classes = module.function_call_classes();
+    assert_eq!(classes[0].0.as_u32(), 1);
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::Leaf,
+            CallClass::Regular,
+            CallClass::TailCallOnly,
+            CallClass::Mixed,
+        ]
+    );
+
+    // Only the function which makes nothing but tail calls has its frame
+    // optimized; the `Mixed` function keeps a standard frame since its
+    // regular call pushes a frame on top of its own.
+    let supported = cfg!(any(target_arch = "x86_64", target_arch = "aarch64"));
+    let optimized = classes
+        .iter()
+        .map(|(index, _)| module.tail_call_optimized(*index))
+        .collect::<Vec<_>>();
+    assert_eq!(optimized, [false, false, supported, false]);
+    let import = module
+        .call_graph()
+        .into_iter()
+        .find_map(|edge| match edge.to {
+            CallTarget::Direct(to) if to.as_u32() == 0 => Some(to),
+            _ => None,
+        })
+        .unwrap();
+    assert!(!module.tail_call_optimized(import));
+
+    let mut store = Store::new(&engine, ());
+    let host = Func::wrap(&mut store, || {});
+    let instance = Instance::new(&mut store, &module, &[host.into()])?;
+    assert_eq!(host.tail_call_class(&store), None);
+    for (name, class) in [
+        ("regular", CallClass::Regular),
+        ("tail", CallClass::TailCallOnly),
+        ("mixed", CallClass::Mixed),
+    ] {
+        let func = instance.get_func(&mut store, name).unwrap();
+        assert_eq!(func.tail_call_class(&store), Some(class));
+    }
+    for name in ["regular", "tail", "mixed"] {
+        let func = instance.get_typed_func::<i32, i32>(&mut store, name)?;
+        assert_eq!(func.call(&mut store, 41)?, 42);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn host_reentry_from_mixed_function() -> Result<()> {
+    let engine = engine();
+    let sum = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $sum (export "sum") (param i32 i64) (result i64)
+                    local.get 0
+                    i32.eqz
+                    if (result i64)
+                        local.get 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.get 1
+                        local.get 0
+                        i64.extend_i32_u
+                        i64.add
+                        return_call $sum
+                    end)
+            )
+        "#,
+    )?;
+    let mixed = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host (param i32) (result i64)))
+                (func $helper (param i64 i64 i64) (result i64)
+                    local.get 0
+                    local.get 1
+                    i64.add
+                    local.get 2
+                    i64.add)
+                (func $mixed (export "mixed") (param i32) (result i64)
+                    (local $a i64) (local $b i64)
+                    local.get 0
+                    i64.extend_i32_u
+                    i64.const 1000
+                    i64.mul
+                    local.set $a
+                    local.get 0
+                    i64.extend_i32_u
+                    i64.const -1
+                    i64.mul
+                    local.set $b
+                    ;; `$a` and `$b` are live across the call into the host,
+                    ;; which itself tail calls deeply in another instance.
+                    local.get 0
+                    call $host
+                    local.get $a
+                    local.get $b
+                    return_call $helper)
+            )
+        "#,
+    )?;
+    assert_eq!(call_classes(&sum), [CallClass::TailCallOnly]);
+    assert_eq!(call_classes(&mixed), [CallClass::Leaf, CallClass::Mixed]);
+    let supported = cfg!(any(target_arch = "x86_64", target_arch = "aarch64"));
+    assert_eq!(
+        sum.tail_call_optimized(sum.function_call_classes()[0].0),
+        supported
+    );
+    assert!(!mixed.tail_call_optimized(mixed.function_call_classes()[1].0));
+
+    let mut store = Store::<Option<TypedFunc<(i32, i64), i64>>>::new(&engine, None);
+    let sum = Instance::new(&mut store, &sum, &[])?;
+    let sum = sum.get_typed_func::<(i32, i64), i64>(&mut store, "sum")?;
+    *store.data_mut() = Some(sum.clone());
+    let host = Func::wrap(
+        &mut store,
+        |mut caller: Caller<'_, Option<TypedFunc<(i32, i64), i64>>>, n: i32| -> Result<i64> {
+            let sum = caller.data().clone().unwrap();
+            sum.call(&mut caller, (n, 0))
+        },
+    );
+    let instance = Instance::new(&mut store, &mixed, &[host.into()])?;
+    let mixed = instance.get_typed_func::<i32, i64>(&mut store, "mixed")?;
+
+    for n in 0..5000 {
+        let expected = i64::from(n) * i64::from(n + 1) / 2 + i64::from(n) * 999;
+        assert_eq!(mixed.call(&mut store, n)?, expected, "n = {n}");
+        // Interleave direct calls into the tail-calling function too.
+        assert_eq!(
+            sum.call(&mut store, (n, 1))?,
+            expected - i64::from(n) * 999 + 1
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn state_machine_tail_calls() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $state_a (param i32 i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        local.get 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.get 1
+                        i32.const 1
+                        i32.add
+                        return_call $state_b
+                    end)
+                (func $state_b (param i32 i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        local.get 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.get 1
+                        i32.const 2
+                        i32.add
+                        return_call $state_a
+                    end)
+                (func (export "start_machine") (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    return_call $state_a)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+        ]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let start = instance.get_typed_func::<i32, i32>(&mut store, "start_machine")?;
+    assert_eq!(start.call(&mut store, 10)?, 15);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn deep_tail_recursion() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                {COUNTDOWN}
+                (export "countdown" (func $countdown))
+                (func $factorial (export "factorial-tail") (param i32 i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.le_s
+                    if (result i32)
+                        local.get 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.get 0
+                        local.get 1
+                        i32.mul
+                        return_call $factorial
+                    end)
+            )
+        "#
+        ),
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::TailCallOnly, CallClass::TailCallOnly]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 10000)?, 0);
+    let factorial = instance.get_typed_func::<(i32, i32), i32>(&mut store, "factorial-tail")?;
+    assert_eq!(factorial.call(&mut store, (5, 1))?, 120);
+
+    // Tail recursion doesn't grow the stack, no matter how deep it goes.
+    assert_constant_stack(
+        &config(),
+        &module,
+        "countdown",
+        &[Val::I32(10)],
+        &[Val::I32(1_000_000)],
+    );
+    assert_constant_stack(
+        &config(),
+        &module,
+        "factorial-tail",
+        &[Val::I32(10), Val::I32(1)],
+        &[Val::I32(1_000_000), Val::I32(1)],
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn min_wasm_stack_grows_with_regular_recursion() -> Result<()> {
+    // Sanity check that `min_wasm_stack` actually measures stack usage, and
+    // thus that `assert_constant_stack` can fail.
+    let module = Module::new(
+        &engine(),
+        r#"
+            (module
+                (func $countdown (export "countdown") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        call $countdown
+                    end)
+            )
+        "#,
+    )?;
+    let shallow = min_wasm_stack(&config(), &module, "countdown", &[Val::I32(10)])?;
+    let deep = min_wasm_stack(&config(), &module, "countdown", &[Val::I32(1000)])?;
+    assert!(deep > shallow + 1024, "shallow: {shallow}, deep: {deep}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn deep_tail_recursion_multi_value() -> Result<()> {
+    // `$many` returns more values than fit in registers, so its results are
+    // returned through the stack.
+    const MANY: usize = 20;
+    let many_results = vec!["i64"; MANY].join(" ");
+    let many_values = (0..MANY)
+        .map(|i| format!("local.get 0 i64.const {i} i64.add"))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let wat = format!(
+        r#"
+            (module
+                (func $countdown (export "countdown") (param i32 i64 f64) (result i32 i64 f64)
+                    local.get 0
+                    i32.eqz
+                    if (result i32 i64 f64)
+                        local.get 0
+                        local.get 1
+                        local.get 2
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.get 1
+                        local.get 0
+                        i64.extend_i32_u
+                        i64.add
+                        local.get 2
+                        f64.const 0.5
+                        f64.add
+                        return_call $countdown
+                    end)
+                (func $many (export "many") (param i64 i32) (result {many_results})
+                    local.get 1
+                    i32.eqz
+                    if (result {many_results})
+                        {many_values}
+                    else
+                        local.get 0
+                        i64.const 1
+                        i64.add
+                        local.get 1
+                        i32.const 1
+                        i32.sub
+                        return_call $many
+                    end)
+            )
+        "#
+    );
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    let deopts = collect_deopts(&mut options);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, &wat)?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::TailCallOnly, CallClass::TailCallOnly]
+    );
+
+    // Multiple results don't affect whether frames are optimized, and the
+    // savings are accounted for each optimized frame.
+    let stats = module.compile_stats();
+    assert_eq!(stats.tail_call_only_functions, 2);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert_eq!(stats.optimized_frames, 2);
+        assert!(stats.estimated_stack_bytes_saved > 0);
+        for deopt in deopts.lock().unwrap().iter() {
+            assert_ne!(deopt.reason, TailCallDeoptReason::UnsupportedCallConv);
+        }
+    } else {
+        assert_eq!(stats.optimized_frames, 0);
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown =
+        instance.get_typed_func::<(i32, i64, f64), (i32, i64, f64)>(&mut store, "countdown")?;
+    assert_eq!(
+        countdown.call(&mut store, (100_000, 0, 0.0))?,
+        (0, 5_000_050_000, 50_000.0)
+    );
+
+    let many = instance.get_func(&mut store, "many").unwrap();
+    let mut results = vec![Val::I64(0); MANY];
+    many.call(&mut store, &[Val::I64(0), Val::I32(100_000)], &mut results)?;
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.unwrap_i64(), 100_000 + i64::try_from(i)?);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn indirect_tail_calls() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $sig (func (param i32) (result i32)))
+                (type $other (func (result i32)))
+                (table 3 funcref)
+                (elem (i32.const 0) $double $dispatch)
+                (func $double (type $sig)
+                    local.get 0
+                    i32.const 2
+                    i32.mul)
+                (func $dispatch (export "dispatch") (type $sig)
+                    local.get 0
+                    i32.const 0
+                    return_call_indirect (type $sig))
+                (func (export "call-dispatch") (type $sig)
+                    local.get 0
+                    i32.const 1
+                    call_indirect (type $sig))
+                (func (export "dispatch-at") (param i32 i32) (result i32)
+                    local.get 1
+                    local.get 0
+                    return_call_indirect (type $sig))
+                (func (export "dispatch-other-at") (param i32) (result i32)
+                    local.get 0
+                    return_call_indirect (type $other))
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::Leaf,
+            CallClass::TailCallOnly,
+            CallClass::Regular,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+        ]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let dispatch = instance.get_typed_func::<i32, i32>(&mut store, "dispatch")?;
+    assert_eq!(dispatch.call(&mut store, 21)?, 42);
+    let call_dispatch = instance.get_typed_func::<i32, i32>(&mut store, "call-dispatch")?;
+    assert_eq!(call_dispatch.call(&mut store, 4)?, 8);
+
+    // The frames of the functions making these tail calls are optimized, but
+    // the calls still check the table's bounds, the element for null, and the
+    // element's signature before jumping.
+    let dispatch_at = instance.get_typed_func::<(i32, i32), i32>(&mut store, "dispatch-at")?;
+    let dispatch_other_at = instance.get_typed_func::<i32, i32>(&mut store, "dispatch-other-at")?;
+    assert_eq!(dispatch_at.call(&mut store, (0, 21))?, 42);
+    for (index, trap) in [
+        (3, Trap::TableOutOfBounds),
+        (-1, Trap::TableOutOfBounds),
+        (2, Trap::IndirectCallToNull),
+    ] {
+        let err = dispatch_at.call(&mut store, (index, 21)).unwrap_err();
+        assert_eq!(err.downcast::<Trap>()?, trap, "index = {index}");
+        let err = dispatch_other_at.call(&mut store, index).unwrap_err();
+        assert_eq!(err.downcast::<Trap>()?, trap, "index = {index}");
+    }
+    let err = dispatch_other_at.call(&mut store, 0).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::BadSignature);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn indirect_tail_calls_after_table_grow() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $sig (func (param i32) (result i32)))
+                (table $t (export "table") 1 funcref)
+                (elem (i32.const 0) $double)
+                (func $double (type $sig)
+                    local.get 0
+                    i32.const 2
+                    i32.mul)
+                (func (export "triple") (type $sig)
+                    local.get 0
+                    i32.const 3
+                    i32.mul)
+                (func (export "dispatch") (param i32 i32) (result i32)
+                    local.get 1
+                    local.get 0
+                    return_call_indirect $t (type $sig))
+                (func (export "call-dispatch") (param i32 i32) (result i32)
+                    local.get 1
+                    local.get 0
+                    call_indirect $t (type $sig))
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let table = instance.get_table(&mut store, "table").unwrap();
+    let dispatch = instance.get_typed_func::<(i32, i32), i32>(&mut store, "dispatch")?;
+    let call_dispatch = instance.get_typed_func::<(i32, i32), i32>(&mut store, "call-dispatch")?;
+
+    // Run both paths before growing the table so that any state derived from
+    // the table's original storage would be stale afterwards.
+    for _ in 0..3 {
+        assert_eq!(dispatch.call(&mut store, (0, 21))?, 42);
+        assert_eq!(call_dispatch.call(&mut store, (0, 21))?, 42);
+    }
+    let err = dispatch.call(&mut store, (1, 21)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TableOutOfBounds);
+
+    // Grow the table by enough to force its storage to be reallocated, then
+    // install a wasm function and a host function in the new slots.
+    let triple = instance.get_func(&mut store, "triple").unwrap();
+    let negate = Func::wrap(&mut store, |x: i32| -x);
+    assert_eq!(table.grow(&mut store, 1000, Ref::Func(None))?, 1);
+    table.set(&mut store, 1, triple.into())?;
+    table.set(&mut store, 1000, negate.into())?;
+
+    for (index, expected) in [(0, 42), (1, 63), (1000, -21)] {
+        assert_eq!(dispatch.call(&mut store, (index, 21))?, expected);
+        assert_eq!(call_dispatch.call(&mut store, (index, 21))?, expected);
+    }
+
+    // Slots which are still null and slots beyond the new size trap the same
+    // way on both paths.
+    for index in [2, 1001] {
+        let tail = dispatch.call(&mut store, (index, 21)).unwrap_err();
+        let regular = call_dispatch.call(&mut store, (index, 21)).unwrap_err();
+        assert_eq!(tail.downcast::<Trap>()?, regular.downcast::<Trap>()?);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn indirect_tail_calls_multiple_tables() -> Result<()> {
+    let mut config = config();
+    // Lazily initialized tables are filled in by a libcall, which is a regular
+    // call that would keep the dispatch functions' frames from being optimized.
+    config.wasm_function_references(true).table_lazy_init(false);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $sig (func (param i32) (result i32)))
+                (type $other (func (param i32 i32) (result i32)))
+                (table $t0 1 funcref)
+                (table $t1 2 funcref)
+                (table $typed 1 (ref null $sig))
+                (elem (table $t0) (i32.const 0) func $add)
+                (elem (table $t1) (i32.const 0) func $double $add)
+                (elem (table $typed) (i32.const 0) (ref null $sig) (ref.func $triple))
+                (func $double (type $sig)
+                    local.get 0
+                    i32.const 2
+                    i32.mul)
+                (func $triple (type $sig)
+                    local.get 0
+                    i32.const 3
+                    i32.mul)
+                (func $add (type $other)
+                    local.get 0
+                    local.get 1
+                    i32.add)
+                (func $dispatch (export "dispatch") (param i32 i32) (result i32)
+                    local.get 0
+                    local.get 1
+                    return_call_indirect $t1 (type $sig))
+                (func $dispatch-typed (export "dispatch-typed") (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    return_call_indirect $typed (type $sig))
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::Leaf,
+            CallClass::Leaf,
+            CallClass::Leaf,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+        ]
+    );
+    let stats = module.compile_stats();
+    assert_eq!(stats.tail_call_only_functions, 2);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert_eq!(stats.optimized_frames, 2);
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let dispatch = instance.get_typed_func::<(i32, i32), i32>(&mut store, "dispatch")?;
+    assert_eq!(dispatch.call(&mut store, (21, 0))?, 42);
+
+    // The entry in the second table has a different type than the one
+    // declared by `return_call_indirect`.
+    let err = dispatch.call(&mut store, (21, 1)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::BadSignature);
+
+    // An index beyond the second table traps too, even though the first table
+    // isn't involved at all.
+    let err = dispatch.call(&mut store, (21, 2)).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TableOutOfBounds);
+
+    let dispatch_typed = instance.get_typed_func::<i32, i32>(&mut store, "dispatch-typed")?;
+    assert_eq!(dispatch_typed.call(&mut store, 14)?, 42);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn typed_function_reference_tail_calls() -> Result<()> {
+    let mut config = config();
+    config.wasm_function_references(true);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $sig (func (param i32) (result i32)))
+                (elem declare func $double $dispatch)
+                (func $double (type $sig)
+                    local.get 0
+                    i32.const 2
+                    i32.mul)
+                (func $dispatch (export "dispatch") (type $sig)
+                    (local $f (ref $sig))
+                    ref.func $double
+                    local.set $f
+                    local.get 0
+                    local.get $f
+                    return_call_ref $sig)
+                (func (export "call-dispatch") (type $sig)
+                    local.get 0
+                    ref.func $dispatch
+                    call_ref $sig)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::Leaf, CallClass::TailCallOnly, CallClass::Regular]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let dispatch = instance.get_typed_func::<i32, i32>(&mut store, "dispatch")?;
+    assert_eq!(dispatch.call(&mut store, 21)?, 42);
+    let call_dispatch = instance.get_typed_func::<i32, i32>(&mut store, "call-dispatch")?;
+    assert_eq!(call_dispatch.call(&mut store, 4)?, 8);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_calls_do_not_grow_wasm_stack_depth() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "record" (func $record))
+                (func $tail (export "tail") (param i32)
+                    call $record
+                    local.get 0
+                    i32.eqz
+                    if
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $tail)
+                (func $regular (export "regular") (param i32)
+                    call $record
+                    local.get 0
+                    i32.eqz
+                    if
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    call $regular)
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, Vec::new());
+    let record = Func::wrap(&mut store, |mut caller: Caller<'_, Vec<usize>>| {
+        let depth = caller.current_wasm_stack_depth();
+        caller.data_mut().push(depth);
+    });
+    let instance = Instance::new(&mut store, &module, &[record.into()])?;
+
+    let tail = instance.get_typed_func::<i32, ()>(&mut store, "tail")?;
+    tail.call(&mut store, 100)?;
+    let depths = mem::take(store.data_mut());
+    assert_eq!(depths.len(), 101);
+    assert!(depths.iter().all(|d| *d == 1), "{depths:?}");
+
+    let regular = instance.get_typed_func::<i32, ()>(&mut store, "regular")?;
+    regular.call(&mut store, 100)?;
+    let depths = mem::take(store.data_mut());
+    assert_eq!(depths, (1..=101).collect::<Vec<_>>());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_relaxed_stack_check_disabled() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.relaxed_stack_check(false);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = countdown_module(&engine)?;
+    assert_eq!(call_classes(&module), [CallClass::TailCallOnly]);
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 10000)?, 0);
+    Ok(())
+}
+
+#[test]
+fn tail_call_options_require_tail_calls() {
+    let setters: &[(&str, fn(&mut TailCallOptions))] = &[
+        ("relaxed_stack_check", |o| {
+            o.relaxed_stack_check(true);
+        }),
+        ("max_stack_args_bytes", |o| {
+            o.max_stack_args_bytes(16);
+        }),
+        ("min_frame_savings", |o| {
+            o.min_frame_savings(16);
+        }),
+        ("max_optimized_body_bytes", |o| {
+            o.max_optimized_body_bytes(Some(128));
+        }),
+        ("require_optimization", |o| {
+            o.require_optimization(true);
+        }),
+        ("self_recursion_loop", |o| {
+            o.self_recursion_loop(true);
+        }),
+        ("fuel", |o| {
+            o.fuel(Some(1));
+        }),
+        ("stack_limit", |o| {
+            o.stack_limit(10);
+        }),
+        ("profiling", |o| {
+            o.profiling(true);
+        }),
+        ("hooks", |o| {
+            o.hooks(true);
+        }),
+        ("debug_assertions", |o| {
+            o.debug_assertions(true);
+        }),
+        ("max_chain_length", |o| {
+            o.max_chain_length(Some(10));
+        }),
+        ("fuel_cost", |o| {
+            o.fuel_cost(0);
+        }),
+        ("host_tracking", |o| {
+            o.host_tracking(true);
+        }),
+        ("zero_unused_frame", |o| {
+            o.zero_unused_frame(true);
+        }),
+    ];
+    for (name, set) in setters {
+        let mut options = TailCallOptions::new();
+        set(&mut options);
+        let mut config = Config::new();
+        config.wasm_tail_call(false).tail_call_options(options);
+        let err = Engine::new(&config).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains(&format!("TailCallOptions::{name} requires wasm_tail_call")),
+            "bad error for `{name}`: {err:?}"
+        );
+    }
+
+    // Options which only observe or restrict compilation have no effect
+    // without tail calls, so they're accepted.
+    let setters: &[fn(&mut TailCallOptions)] = &[
+        |o| {
+            o.on_deopt(|_| {});
+        },
+        |o| {
+            o.report_to_log(true);
+        },
+        |o| {
+            o.classifier(|_, class| class);
+        },
+        |o| {
+            o.reject_modules(true);
+        },
+    ];
+    for set in setters {
+        let mut options = TailCallOptions::new();
+        set(&mut options);
+        let mut config = Config::new();
+        config.wasm_tail_call(false).tail_call_options(options);
+        Engine::new(&config).unwrap();
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_deopt_callback() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    let deopts = collect_deopts(&mut options);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let wasm = wat::parse_str(format!(
+        r#"
+            (module
+                (func $many (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                    (result i32)
+                    local.get 0
+                    local.get 11
+                    i32.add)
+                (func $few (export "few") (param i32) (result i32)
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    return_call $many)
+                {COUNTDOWN}
+            )
+        "#
+    ))?;
+    let module = Module::new(&engine, &wasm)?;
+
+    // The callback is invoked synchronously, so everything has been reported
+    // by the time `Module::new` returns.
+    let deopts = deopts.lock().unwrap().clone();
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        // `$few` needs to grow its incoming argument area to make its tail
+        // call while `$countdown` is fully optimized.
+        assert_eq!(deopts.len(), 1, "{deopts:?}");
+        assert_eq!(deopts[0].func_index.as_u32(), 1);
+        assert_eq!(deopts[0].reason, TailCallDeoptReason::StackArgsPresent);
+        assert!(deopts[0].outgoing_args_size > 0);
+
+        // The deopt points at `$few`'s `return_call` instruction, but there's
+        // no debug information to map it to a source location.
+        let offset = deopts[0].tail_call_offset.unwrap().file_offset().unwrap();
+        assert_eq!(wasm[usize::try_from(offset).unwrap()], 0x12);
+        assert_eq!(deopts[0].source_loc, None);
+    } else {
+        // Other targets don't support the optimization at all.
+        assert_eq!(deopts.len(), 2, "{deopts:?}");
+        for deopt in deopts.iter() {
+            assert_eq!(deopt.reason, TailCallDeoptReason::UnsupportedCallConv);
+        }
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let few = instance.get_typed_func::<i32, i32>(&mut store, "few")?;
+    assert_eq!(few.call(&mut store, 21)?, 42);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_optimized_and_deopt_are_exclusive() -> Result<()> {
+    // Tail-call-only functions of various shapes: a simple loop, one with
+    // enough live values to need callee-saved registers and spill slots, and
+    // one which needs stack space for its callee's arguments.
+    const PARAMS: usize = 16;
+    let params = vec!["i64"; PARAMS].join(" ");
+    // `$mix`'s first parameter is its counter, so its `i64`s start at local 1.
+    let mix = (0..PARAMS)
+        .map(|i| {
+            format!(
+                "(i64.add (local.get {}) (i64.mul (local.get {}) (i64.const 3)))",
+                i + 1,
+                (i + 1) % PARAMS + 1
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let wat = format!(
+        r#"
+            (module
+                {COUNTDOWN}
+                (func $mix (param i32 {params}) (result i64)
+                    (if (result i64) (i32.eqz (local.get 0))
+                        (then (local.get 1))
+                        (else
+                            (return_call $mix
+                                (i32.sub (local.get 0) (i32.const 1))
+                                {mix}))))
+                (func $wide (param {params}) (result i64) (local.get 0))
+                (func $grow (param i64) (result i64)
+                    (return_call $wide {args}))
+            )
+        "#,
+        args = vec!["(local.get 0)"; PARAMS].join(" "),
+    );
+
+    for relaxed_stack_check in [true, false] {
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        let deopts = collect_deopts(&mut options);
+        let mut config = config();
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, &wat)?;
+        let deopts = deopts.lock().unwrap().clone();
+
+        let classes = module.function_call_classes();
+        let mut optimized_frames = 0;
+        for (index, class) in classes {
+            let optimized = module.tail_call_optimized(index);
+            let deopted = deopts.iter().any(|d| d.func_index == index);
+            assert!(
+                !(optimized && deopted),
+                "function {index:?} is both optimized and deopted: {deopts:?}"
+            );
+            // With the relaxation enabled every tail-call-only function is
+            // reported one way or the other.
+            if relaxed_stack_check && class.is_tail_call_only() {
+                assert!(optimized || deopted, "function {index:?} is neither");
+            } else {
+                assert!(!optimized && !deopted);
+            }
+            optimized_frames += usize::from(optimized);
+        }
+        assert_eq!(module.compile_stats().optimized_frames, optimized_frames);
+    }
+    Ok(())
+}
+
+#[test]
+fn winch_rejects_tail_calls() {
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch);
+    config.wasm_tail_call(true);
+    let err = Engine::new(&config).unwrap_err().to_string();
+    assert!(
+        err.contains("wasm_tail_call feature is not supported"),
+        "bad error: {err}"
+    );
+    assert!(err.contains("Winch strategy"), "bad error: {err}");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_max_stack_args_bytes() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.max_stack_args_bytes(240);
+    let deopts = collect_deopts(&mut options);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module_wat = r#"
+            (module
+                (func $many (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                    (result i32)
+                    local.get 0
+                    local.get 11
+                    i32.add)
+                (func $few (export "few") (param i32) (result i32)
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    return_call $many)
+            )
+        "#;
+    let module = Module::new(&engine, module_wat)?;
+
+    // The stack arguments of `$few` fit within the configured size, so they no
+    // longer keep its standard stack check, while by default they do.
+    let deopts = deopts.lock().unwrap().clone();
+    let few = module.function_call_classes()[1].0;
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(
+            deopts
+                .iter()
+                .all(|d| d.reason != TailCallDeoptReason::StackArgsPresent),
+            "{deopts:?}"
+        );
+        assert!(module.tail_call_optimized(few));
+        let default = Module::new(&self::engine(), module_wat)?;
+        assert!(!default.tail_call_optimized(few));
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let few = instance.get_typed_func::<i32, i32>(&mut store, "few")?;
+    assert_eq!(few.call(&mut store, 21)?, 42);
+
+    // Artifacts aren't compatible with engines using a different size.
+    let bytes = module.serialize()?;
+    assert!(unsafe { Module::deserialize(&self::engine(), &bytes) }.is_err());
+    Ok(())
+}
+
+#[test]
+fn tail_call_max_stack_args_bytes_validation() {
+    let engine = |bytes, tail_calls| {
+        let mut config = Config::new();
+        config.wasm_tail_call(tail_calls);
+        let mut options = TailCallOptions::new();
+        options.max_stack_args_bytes(bytes);
+        config.tail_call_options(options);
+        Engine::new(&config)
+    };
+
+    let err = engine(20, true).unwrap_err();
+    assert!(
+        err.to_string().contains("must be a multiple of"),
+        "bad error: {err:?}"
+    );
+    let err = engine(256, true).unwrap_err();
+    assert!(
+        err.to_string().contains("cannot exceed 255"),
+        "bad error: {err:?}"
+    );
+    let err = engine(16, false).unwrap_err();
+    assert!(
+        err.to_string().contains("requires wasm_tail_call"),
+        "bad error: {err:?}"
+    );
+    assert!(engine(0, true).is_ok());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn compile_stats() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $leaf (param i32) (result i32)
+                local.get 0)
+            (func $regular (param i32) (result i32)
+                local.get 0
+                call $leaf)
+            (func $even (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 1
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $odd
+                end)
+            (func $odd (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $even
+                end)
+        )
+    "#;
+
+    let module = Module::new(&engine(), wat)?;
+    let stats = module.compile_stats();
+    assert_eq!(stats.tail_call_only_functions, 2);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert_eq!(stats.optimized_frames, 2);
+        assert!(stats.estimated_stack_bytes_saved > 0);
+    } else {
+        assert_eq!(stats.optimized_frames, 0);
+        assert_eq!(stats.estimated_stack_bytes_saved, 0);
+    }
+
+    // The statistics survive a round trip through serialization.
+    let bytes = module.serialize()?;
+    let deserialized = unsafe { Module::deserialize(module.engine(), &bytes)? };
+    assert_eq!(deserialized.compile_stats(), stats);
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.relaxed_stack_check(false);
+    config.tail_call_options(options);
+    let module = Module::new(&Engine::new(&config)?, wat)?;
+    let stats = module.compile_stats();
+    assert_eq!(stats.tail_call_only_functions, 2);
+    assert_eq!(stats.optimized_frames, 0);
+    assert_eq!(stats.estimated_stack_bytes_saved, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_fuel() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.fuel(Some(100));
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                {COUNTDOWN}
+                (export "countdown" (func $countdown))
+                (func $forever (export "forever")
+                    return_call $forever)
+            )
+        "#
+        ),
+    )?;
+
+    // 100 tail calls are allowed, but the fuel is shared across the whole
+    // store and never refilled.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 100)?, 0);
+    let err = countdown.call(&mut store, 1).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TailCallFuelExhausted);
+
+    // An infinite tail loop is detected rather than hanging.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let forever = instance.get_typed_func::<(), ()>(&mut store, "forever")?;
+    let err = forever.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TailCallFuelExhausted);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn inline_tail_call_only() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $helper (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add)
+            (func $tail_helper (param i32) (result i32)
+                local.get 0
+                return_call $helper)
+            (func $regular (export "regular") (param i32) (result i32)
+                local.get 0
+                call $helper)
+            (func $via_tail (export "via_tail") (param i32) (result i32)
+                local.get 0
+                call $tail_helper
+                i32.const 1
+                i32.add)
+        )
+    "#;
+
+    for opt_level in [OptLevel::None, OptLevel::Speed, OptLevel::SpeedAndSize] {
+        let mut config = config();
+        config.cranelift_opt_level(opt_level);
+        config.compiler_inlining(true);
+        config.inline_tail_call_only(true);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let regular = instance.get_typed_func::<i32, i32>(&mut store, "regular")?;
+        assert_eq!(regular.call(&mut store, 41)?, 42);
+        let via_tail = instance.get_typed_func::<i32, i32>(&mut store, "via_tail")?;
+        assert_eq!(via_tail.call(&mut store, 40)?, 42);
+
+        // Inlining changes the compiled code, so artifacts aren't compatible
+        // with engines that don't inline tail-call-only callees.
+        if opt_level != OptLevel::None {
+            let bytes = module.serialize()?;
+            let mut config = self::config();
+            config.cranelift_opt_level(opt_level);
+            config.compiler_inlining(true);
+            let other = Engine::new(&config)?;
+            assert!(unsafe { Module::deserialize(&other, &bytes) }.is_err());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn backtrace_had_tail_calls() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $countdown (export "countdown") (param i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        unreachable
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $countdown)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, ()>(&mut store, "countdown")?;
+    let err = countdown.call(&mut store, 1000).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert_eq!(trace.frames().len(), 1);
+    assert!(trace.had_tail_calls());
+    assert!(
+        trace.to_string().contains("elided by tail calls"),
+        "bad backtrace: {trace}"
+    );
+
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $abort unreachable)
+                (func (export "run") call $abort)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(), ()>(&mut store, "run")?;
+    let err = run.call(&mut store, ()).unwrap_err();
+    let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+    assert_eq!(trace.frames().len(), 2);
+    assert!(!trace.had_tail_calls());
+    assert!(!trace.to_string().contains("elided by tail calls"));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_fuel_consumed() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.fuel(Some(1000));
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $state_a (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $state_b
+                    end)
+                (func $state_b (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $state_a
+                    end)
+                (func (export "start_machine") (param i32) (result i32)
+                    local.get 0
+                    return_call $state_a)
+            )
+        "#,
+    )?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let start = instance.get_typed_func::<i32, i32>(&mut store, "start_machine")?;
+    assert_eq!(store.tail_call_fuel_consumed()?, 0);
+
+    // Each run makes one tail call into the machine plus one per step.
+    for steps in [0, 10, 25, 10] {
+        store.set_tail_call_fuel(1000)?;
+        start.call(&mut store, steps)?;
+        assert_eq!(store.tail_call_fuel_consumed()?, u64::try_from(steps)? + 1);
+    }
+
+    // Without a reset the consumed count accumulates.
+    start.call(&mut store, 5)?;
+    assert_eq!(store.tail_call_fuel_consumed()?, 17);
+
+    store.set_tail_call_fuel(3)?;
+    let err = start.call(&mut store, 5).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TailCallFuelExhausted);
+    assert_eq!(store.tail_call_fuel_consumed()?, 3);
+
+    // Tail-call fuel must be enabled in the config.
+    let mut store = Store::new(&self::engine(), ());
+    assert!(store.tail_call_fuel_consumed().is_err());
+    assert!(store.set_tail_call_fuel(1).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn return_call_result_mismatch_error() -> Result<()> {
+    let engine = engine();
+    let wasm = wat::parse_str(
+        r#"
+            (module
+                (type $t (func (param i32) (result i64)))
+                (table 1 funcref)
+                (func $callee (param i32) (result i64)
+                    local.get 0
+                    i64.extend_i32_u)
+                (func $direct (param i32) (result i32)
+                    local.get 0
+                    return_call $callee)
+            )
+        "#,
+    )?;
+    let err = Module::new(&engine, &wasm).unwrap_err();
+    let err = format!("{err:?}");
+    assert!(
+        err.contains("function 1 returns [i32] but tail calls function 0 which returns [i64]"),
+        "bad error: {err}"
+    );
+
+    let wasm = wat::parse_str(
+        r#"
+            (module
+                (type $t (func (param i32) (result i64)))
+                (table 1 funcref)
+                (func $indirect (param i32) (result i32 i32)
+                    local.get 0
+                    i32.const 0
+                    return_call_indirect (type $t))
+            )
+        "#,
+    )?;
+    let err = Module::new(&engine, &wasm).unwrap_err();
+    let err = format!("{err:?}");
+    assert!(
+        err.contains(
+            "function 0 returns [i32, i32] but tail calls a function of type 0 which returns [i64]"
+        ),
+        "bad error: {err}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_profiling() -> Result<()> {
+    let mut options = TailCallOptions::new();
+    options.profiling(true);
+    let mut config = config();
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let wat = r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (table $table 1 funcref)
+                (elem (table $table) (i32.const 0) func $b)
+
+                (func $a (export "run") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        i32.const 0
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    i32.const 0
+                    return_call_indirect (type $t))
+                (func $b (param i32) (result i32)
+                    local.get 0
+                    return_call $a)
+                (func $unused (param i32) (result i32)
+                    local.get 0
+                    return_call $a)
+            )
+        "#;
+    let module = Module::new(&engine, wat)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    assert!(tail_call_counts(&instance, &store).is_empty());
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 10)?, 0);
+    assert_eq!(tail_call_counts(&instance, &store), [(0, 10), (1, 10)]);
+    run.call(&mut store, 5)?;
+    assert_eq!(tail_call_counts(&instance, &store), [(0, 15), (1, 15)]);
+
+    // Counts are kept per instance.
+    let instance2 = Instance::new(&mut store, &module, &[])?;
+    assert!(tail_call_counts(&instance2, &store).is_empty());
+
+    // Without profiling enabled nothing is counted.
+    let engine = self::engine();
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    assert_eq!(run.call(&mut store, 10)?, 0);
+    assert!(tail_call_counts(&instance, &store).is_empty());
+    Ok(())
+}
+
+fn tail_call_counts(instance: &Instance, store: &Store<()>) -> Vec<(u32, u64)> {
+    instance
+        .tail_call_counts(store)
+        .into_iter()
+        .map(|(index, count)| (index.as_u32(), count))
+        .collect()
+}
+
+#[test]
+#[cfg(target_arch = "aarch64")]
+#[cfg_attr(miri, ignore)]
+fn aarch64_tail_call_relaxed_stack_check() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    let deopts = collect_deopts(&mut options);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = countdown_module(&engine)?;
+    assert_eq!(call_classes(&module), [CallClass::TailCallOnly]);
+
+    // The frame record of `fp` and `lr` is reused by each tail call, so the
+    // function's frame is fully optimized.
+    let deopts = deopts.lock().unwrap().clone();
+    assert!(deopts.is_empty(), "{deopts:?}");
+    let stats = module.compile_stats();
+    assert_eq!(stats.optimized_frames, 1);
+    assert_eq!(stats.estimated_stack_bytes_saved, 16);
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 1_000_000)?, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_require_optimization() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.require_optimization(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+
+    let countdown = format!(
+        r#"
+        (module
+            {COUNTDOWN}
+        )
+    "#
+    );
+    let few = r#"
+        (module
+            (func $many (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                (result i32)
+                local.get 0
+                local.get 11
+                i32.add)
+            (func $few (param i32) (result i32)
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                local.get 0
+                return_call $many)
+        )
+    "#;
+
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        Module::new(&engine, &countdown)?;
+        let err = format!("{:?}", Module::new(&engine, few).unwrap_err());
+        assert!(
+            err.contains("function 1 only makes tail calls but its frame couldn't be optimized"),
+            "bad error: {err}"
+        );
+        assert!(err.contains("outgoing arguments"), "bad error: {err}");
+    } else {
+        let err = format!("{:?}", Module::new(&engine, &countdown).unwrap_err());
+        assert!(err.contains("isn't supported"), "bad error: {err}");
+    }
+
+    // Without the requirement the same module compiles fine.
+    Module::new(&self::engine(), few)?;
+
+    let mut config = self::config();
+    let mut options = TailCallOptions::new();
+    options.relaxed_stack_check(false);
+    options.require_optimization(true);
+    config.tail_call_options(options);
+    let err = Engine::new(&config).unwrap_err();
+    assert!(
+        err.to_string()
+            .contains("requires TailCallOptions::relaxed_stack_check"),
+        "bad error: {err:?}"
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_require_optimization_with_profiling() -> Result<()> {
+    // Profiling adds a call into the host before each tail call, so
+    // `$countdown` no longer only makes tail calls once compiled even though
+    // its Wasm body does.
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.require_optimization(true);
+    options.profiling(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+
+    let err = format!(
+        "{:?}",
+        Module::new(
+            &engine,
+            format!(
+                r#"
+                (module
+                    {COUNTDOWN}
+                )
+            "#
+            ),
+        )
+        .unwrap_err()
+    );
+    assert!(
+        err.contains("function 0 only makes tail calls but its frame couldn't be optimized"),
+        "bad error: {err}"
+    );
+    assert!(
+        err.contains("TailCallOptions::profiling"),
+        "bad error: {err}"
+    );
+    Ok(())
+}
+
+#[wasmtime_test(wasm_features(tail_call, exceptions))]
+#[cfg_attr(miri, ignore)]
+fn tail_call_within_try_table(config: &mut Config) -> Result<()> {
+    let engine = Engine::new(config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (tag $e (param i32))
+                (func $thrower (param i32) (result i32)
+                    (throw $e (local.get 0)))
+
+                ;; The tail call removes this frame, handler included, so
+                ;; exceptions thrown by `$thrower` are never caught here.
+                (func $tail-in-try (export "tail-in-try") (param i32) (result i32)
+                    (block $h (result i32)
+                        (try_table (catch $e $h)
+                            (return_call $thrower (local.get 0)))
+                        unreachable)
+                    i32.const 1000
+                    i32.add)
+
+                (func $outer (export "outer") (param i32) (result i32)
+                    (block $h (result i32)
+                        (try_table (catch $e $h)
+                            (return (call $tail-in-try (local.get 0))))
+                        unreachable)
+                    i32.const 2000
+                    i32.add)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::Leaf, CallClass::TailCallOnly, CallClass::Regular,]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let outer = instance.get_typed_func::<i32, i32>(&mut store, "outer")?;
+    for i in 0..100 {
+        assert_eq!(outer.call(&mut store, i)?, 2000 + i);
+    }
+
+    // With no outer frame to catch it the exception escapes to the host.
+    let tail_in_try = instance.get_typed_func::<i32, i32>(&mut store, "tail-in-try")?;
+    assert!(tail_in_try.call(&mut store, 5).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_metadata_survives_serialization() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host))
+                (func $leaf (param i32) (result i32)
+                    local.get 0)
+                (func $regular (param i32) (result i32)
+                    local.get 0
+                    call $leaf)
+                (func $tail (param i32) (result i32)
+                    local.get 0
+                    return_call $leaf)
+                (func $mixed (param i32) (result i32)
+                    call $host
+                    local.get 0
+                    return_call $leaf)
+            )
+        "#,
+    )?;
+
+    let bytes = module.serialize()?;
+    let deserialized = unsafe { Module::deserialize(&engine, &bytes)? };
+    assert_eq!(
+        deserialized.function_call_classes(),
+        module.function_call_classes()
+    );
+    assert_eq!(
+        call_classes(&deserialized),
+        [
+            CallClass::Leaf,
+            CallClass::Regular,
+            CallClass::TailCallOnly,
+            CallClass::Mixed,
+        ]
+    );
+    assert_eq!(
+        deserialized.function_tail_call_frame_savings(),
+        module.function_tail_call_frame_savings()
+    );
+    assert_eq!(deserialized.compile_stats(), module.compile_stats());
+
+    // Deserializing from a file goes through the same metadata.
+    let file = tempfile::NamedTempFile::new()?;
+    std::fs::write(file.path(), &bytes)?;
+    let from_file = unsafe { Module::deserialize_file(&engine, file.path())? };
+    assert_eq!(
+        from_file.function_call_classes(),
+        module.function_call_classes()
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_self_recursion_loop() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $factorial (export "factorial-tail") (param i32 i32) (result i32)
+                (local $scratch i32)
+                ;; `$scratch` must start out as zero on each iteration.
+                local.get $scratch
+                if
+                    unreachable
+                end
+                i32.const 1
+                local.set $scratch
+                local.get 0
+                i32.const 1
+                i32.le_s
+                if (result i32)
+                    local.get 1
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    local.get 0
+                    local.get 1
+                    i32.mul
+                    return_call $factorial
+                end)
+            (func $ping (export "ping") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $pong
+                end)
+            (func $pong (param i32) (result i32)
+                local.get 0
+                return_call $ping)
+        )
+    "#;
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.self_recursion_loop(true);
+    options.fuel(Some(1_000_000));
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+
+    // Only the function which tail calls itself is compiled as a loop.
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::SelfTailLoop,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+        ]
+    );
+    assert!(CallClass::SelfTailLoop.has_tail_calls());
+    assert!(!CallClass::SelfTailLoop.has_regular_calls());
+    assert_eq!(module.compile_stats().tail_call_only_functions, 3);
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let factorial = instance.get_typed_func::<(i32, i32), i32>(&mut store, "factorial-tail")?;
+    assert_eq!(factorial.call(&mut store, (5, 1))?, 120);
+    assert_eq!(factorial.call(&mut store, (10, 1))?, 3628800);
+    assert_constant_stack(
+        &config,
+        &module,
+        "factorial-tail",
+        &[Val::I32(10), Val::I32(1)],
+        &[Val::I32(1_000_000), Val::I32(1)],
+    );
+
+    // Each iteration still counts as a tail call.
+    assert_eq!(store.tail_call_fuel_consumed()?, 4 + 9);
+    let ping = instance.get_typed_func::<i32, i32>(&mut store, "ping")?;
+    assert_eq!(ping.call(&mut store, 10)?, 0);
+
+    // Without the option the function is an ordinary tail-call-only function.
+    let module = Module::new(&self::engine(), wat)?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_self_recursion_loop_with_dead_tail_call() -> Result<()> {
+    // The `return_call $other` is dead code, but detecting self-recursion is
+    // conservative about it, so `$countdown` isn't compiled as a loop.
+    let wat = r#"
+        (module
+            (func $countdown (export "countdown") (param i32) (result i32)
+                i32.const 0
+                if
+                    i32.const 100
+                    return_call $other
+                end
+                local.get 0
+                i32.eqz
+                if
+                    i32.const 7
+                    return
+                end
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $countdown)
+            (func $other (param i32) (result i32)
+                local.get 0)
+        )
+    "#;
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.self_recursion_loop(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::TailCallOnly, CallClass::Leaf]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 10)?, 7);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_cliques() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host))
+
+                ;; A two-state machine.
+                (func $even (export "even") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        i32.const 1
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $odd)
+                (func $odd (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        i32.const 0
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $even)
+
+                ;; A three-state cycle.
+                (func $a (param i32) (result i32)
+                    local.get 0
+                    return_call $b)
+                (func $b (param i32) (result i32)
+                    local.get 0
+                    return_call $c)
+                (func $c (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        i32.const 0
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $a)
+
+                ;; Tail calls into a clique without being reachable from it.
+                (func $enter (param i32) (result i32)
+                    local.get 0
+                    return_call $a)
+
+                ;; Only tail calls itself.
+                (func $self (param i32) (result i32)
+                    local.get 0
+                    return_call $self)
+
+                ;; Part of a cycle, but also makes a regular call.
+                (func $mixed (param i32) (result i32)
+                    call $host
+                    local.get 0
+                    return_call $other)
+                (func $other (param i32) (result i32)
+                    local.get 0
+                    return_call $mixed)
+            )
+        "#,
+    )?;
+
+    let cliques = |module: &Module| {
+        module
+            .tail_call_cliques()
+            .into_iter()
+            .map(|clique| clique.into_iter().map(|f| f.as_u32()).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(cliques(&module), [vec![1, 2], vec![3, 4, 5]]);
+
+    // Cliques are recorded in the compiled artifact.
+    let bytes = module.serialize()?;
+    let deserialized = unsafe { Module::deserialize(&engine, &bytes)? };
+    assert_eq!(cliques(&deserialized), [vec![1, 2], vec![3, 4, 5]]);
+
+    // Functions in a clique are still called like any other.
+    let mut store = Store::new(&engine, ());
+    let host = Func::wrap(&mut store, || {});
+    let instance = Instance::new(&mut store, &module, &[host.into()])?;
+    let even = instance.get_typed_func::<i32, i32>(&mut store, "even")?;
+    assert_eq!(even.call(&mut store, 100_000)?, 1);
+    assert_eq!(even.call(&mut store, 7)?, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn stack_overflow_site() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $helper (param i32) (result i32)
+                local.get 0)
+
+            ;; Its tail path is fine, but the stray regular call to itself
+            ;; recurses without bound.
+            (func $mixed (export "mixed") (param i32) (result i32)
+                local.get 0
+                call $mixed
+                drop
+                local.get 0
+                return_call $helper)
+
+            ;; Tail calls into `$mixed`, so its frame is replaced and it
+            ;; never appears as the overflowing call site.
+            (func $enter (export "enter") (param i32) (result i32)
+                local.get 0
+                return_call $mixed)
+
+            (func $regular (export "regular") (param i32) (result i32)
+                local.get 0
+                call $regular)
+        )
+    "#;
+    let engine = engine();
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+
+    for (export, func_index, call_class) in [
+        ("mixed", 1, CallClass::Mixed),
+        ("enter", 1, CallClass::Mixed),
+        ("regular", 3, CallClass::Regular),
+    ] {
+        let func = instance.get_typed_func::<i32, i32>(&mut store, export)?;
+        let error = func.call(&mut store, 0).unwrap_err();
+        assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::StackOverflow));
+        let site = error.downcast_ref::<StackOverflowSite>().unwrap();
+        assert_eq!(site.func_index(), func_index);
+        assert_eq!(site.call_class(), call_class);
+
+        let message = format!("{error:?}");
+        assert!(
+            message.contains("stack overflowed beneath a regular (non-tail) call"),
+            "{message}"
+        );
+        assert_eq!(
+            message.contains("which also makes tail calls"),
+            call_class.has_tail_calls(),
+            "{message}"
+        );
+    }
+
+    // The site isn't attached for modules without tail calls, where the
+    // backtrace already describes the recursion.
+    let regular = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $regular (export "regular") (param i32) (result i32)
+                    local.get 0
+                    call $regular)
+            )
+        "#,
+    )?;
+    let instance = Instance::new(&mut store, &regular, &[])?;
+    let func = instance.get_typed_func::<i32, i32>(&mut store, "regular")?;
+    let error = func.call(&mut store, 0).unwrap_err();
+    assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::StackOverflow));
+    assert!(error.downcast_ref::<StackOverflowSite>().is_none());
+
+    // The site is computed from the backtrace, so it's absent without one.
+    let mut config = config();
+    config.wasm_backtrace(false);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let func = instance.get_typed_func::<i32, i32>(&mut store, "regular")?;
+    let error = func.call(&mut store, 0).unwrap_err();
+    assert_eq!(error.downcast_ref::<Trap>(), Some(&Trap::StackOverflow));
+    assert!(error.downcast_ref::<StackOverflowSite>().is_none());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn differential_tail_call_check() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $even (export "even") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if
+                    i32.const 1
+                    return
+                end
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $odd)
+            (func $odd (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if
+                    i32.const 0
+                    return
+                end
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $even)
+
+            (func $sum (export "sum") (param i64 i64 f64) (result i64 f64)
+                local.get 0
+                i64.eqz
+                if
+                    local.get 1
+                    local.get 2
+                    return
+                end
+                local.get 0
+                i64.const 1
+                i64.sub
+                local.get 1
+                local.get 0
+                i64.add
+                local.get 2
+                f64.const 0.5
+                f64.add
+                return_call $sum)
+
+            (func $helper (param i32) (result i32)
+                local.get 0
+                i32.const 3
+                i32.mul)
+            (func (export "mixed") (param i32) (result i32)
+                local.get 0
+                call $helper
+                return_call $helper)
+
+            (func (export "trap") (param i32) (result i32)
+                local.get 0
+                i32.const 100
+                i32.gt_u
+                if
+                    unreachable
+                end
+                local.get 0
+                return_call $even)
+        )
+    "#;
+    let config = config();
+
+    Module::differential_tail_call_check(&config, wat, "even", &[Val::I32(100_001)])?;
+    Module::differential_tail_call_check(
+        &config,
+        wat,
+        "sum",
+        &[Val::I64(100_000), Val::I64(0), Val::F64(0.0f64.to_bits())],
+    )?;
+    Module::differential_tail_call_check(&config, wat, "mixed", &[Val::I32(7)])?;
+    Module::differential_tail_call_check(&config, wat, "trap", &[Val::I32(1)])?;
+    Module::differential_tail_call_check(&config, wat, "trap", &[Val::I32(101)])?;
+
+    // The check also works with self-recursive tail calls compiled as loops.
+    let mut loops = self::config();
+    let mut options = TailCallOptions::new();
+    options.self_recursion_loop(true);
+    loops.tail_call_options(options);
+    Module::differential_tail_call_check(
+        &loops,
+        wat,
+        "sum",
+        &[Val::I64(1_000), Val::I64(5), Val::F64(1.0f64.to_bits())],
+    )?;
+
+    let err = Module::differential_tail_call_check(&config, wat, "missing", &[]).unwrap_err();
+    assert!(err.to_string().contains("no exported function"), "{err:?}");
+
+    let err = Module::differential_tail_call_check(
+        &config,
+        r#"(module (import "" "" (func)))"#,
+        "f",
+        &[],
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("without imports"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[cfg(all(feature = "stack-switching", unix, target_arch = "x86_64"))]
+fn tail_calls_with_stack_switching() -> Result<()> {
+    let mut config = config();
+    config
+        .wasm_function_references(true)
+        .wasm_exceptions(true)
+        .wasm_stack_switching(true);
+    let engine = Engine::new(&config)?;
+    let countdown = r#"
+        (type $ft (func (param i32) (result i32)))
+        (type $ct (cont $ft))
+        (func $countdown (export "countdown") (type $ft)
+            local.get 0
+            i32.eqz
+            if
+                i32.const 0
+                return
+            end
+            local.get 0
+            i32.const 1
+            i32.sub
+            return_call $countdown)
+        (elem declare func $countdown)
+    "#;
+
+    // A tail loop meant to be a continuation's body is classified and runs as
+    // usual when stack switching is enabled.
+    let module = Module::new(&engine, format!("(module {countdown})"))?;
+    assert_eq!(call_classes(&module), [CallClass::TailCallOnly]);
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let f = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(f.call(&mut store, 1_000_000)?, 0);
+
+    // Creating and resuming continuations isn't supported by Cranelift yet, so
+    // tail calls on a continuation's stack can't be exercised. Once it is, this
+    // should resume `$countdown` and check that its tail calls run in constant
+    // stack on the continuation's stack.
+    let err = Module::new(
+        &engine,
+        format!(
+            r#"
+                (module
+                    {countdown}
+                    (func (export "run") (result i32)
+                        i32.const 1000
+                        ref.func $countdown
+                        cont.new $ct
+                        resume $ct)
+                )
+            "#
+        ),
+    )
+    .unwrap_err();
+    assert!(format!("{err:?}").contains("not implemented"), "{err:?}");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn precompile_module_with_report() -> Result<()> {
+    let engine = engine();
+    let wat = format!(
+        r#"
+        (module
+            (func $leaf (export "leaf") (export "also-leaf") (param i32) (result i32)
+                local.get 0)
+            {COUNTDOWN}
+            (export "countdown" (func $countdown))
+            (func $mixed (param i32) (result i32)
+                local.get 0
+                call $leaf
+                return_call $countdown)
+        )
+    "#
+    );
+
+    let mut report = Vec::new();
+    let compiled = engine.precompile_module_with_report(wat.as_bytes(), &mut report)?;
+    let json: serde_json::Value = serde_json::from_slice(&report)?;
+    let functions = json.as_array().unwrap();
+    assert_eq!(functions.len(), 3);
+    assert_eq!(functions[0]["index"], 0);
+    assert_eq!(
+        functions[0]["exports"],
+        serde_json::json!(["leaf", "also-leaf"])
+    );
+    assert_eq!(functions[0]["class"], "Leaf");
+    assert_eq!(functions[1]["exports"], serde_json::json!(["countdown"]));
+    assert_eq!(functions[1]["class"], "TailCallOnly");
+    assert_eq!(functions[2]["exports"], serde_json::json!([]));
+    assert_eq!(functions[2]["class"], "Mixed");
+    assert_eq!(functions[2]["frame_optimized"], false);
+    assert_eq!(functions[2]["stack_bytes_saved"], 0);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert_eq!(functions[1]["frame_optimized"], true);
+        assert!(functions[1]["stack_bytes_saved"].as_u64().unwrap() > 0);
+    }
+
+    // The report describes the artifact that was produced, which reports the
+    // same thing once it's loaded.
+    let module = unsafe { Module::deserialize(&engine, &compiled)? };
+    let loaded: serde_json::Value = serde_json::from_str(&module.tail_call_report().to_json())?;
+    assert_eq!(loaded, json);
+
+    // The text report is a table with a header row.
+    let text = module.tail_call_report().to_string();
+    let rows = text
+        .lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(rows.len(), 4, "{text}");
+    assert_eq!(
+        rows[0],
+        [
+            "function",
+            "class",
+            "frame",
+            "optimized",
+            "bytes",
+            "saved",
+            "exports"
+        ]
+    );
+    assert_eq!(
+        rows[1],
+        ["func[0]", "Leaf", "no", "0", r#""leaf""#, r#""also-leaf""#]
+    );
+    assert_eq!(rows[3], ["func[2]", "Mixed", "no", "0"]);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn unreachable_regular_calls_are_not_counted() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $helper (result i32)
+                    i32.const 1)
+                (func $countdown (export "countdown") (param i32) (result i32)
+                    i32.const 0
+                    if
+                        call $helper
+                        drop
+                    end
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $countdown
+                    end)
+                (func $after_br (param i32) (result i32)
+                    block
+                        br 0
+                        call $helper
+                        drop
+                    end
+                    local.get 0
+                    return_call $countdown)
+                (func $live_else (param i32) (result i32)
+                    i32.const 1
+                    if
+                        nop
+                    else
+                        call $helper
+                        drop
+                    end
+                    local.get 0
+                    return_call $countdown)
+                (func $live (param i32) (result i32)
+                    i32.const 1
+                    if
+                        call $helper
+                        drop
+                    end
+                    local.get 0
+                    return_call $countdown)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::Leaf,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+            CallClass::Mixed,
+        ]
+    );
+
+    // The dead calls are compiled as traps rather than calls, so the frames of
+    // the functions that only make tail calls are still optimized.
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        let savings = module
+            .function_tail_call_frame_savings()
+            .into_iter()
+            .map(|(_, saved)| saved.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(savings, [false, true, true, true, false]);
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 10)?, 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_hook() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.hooks(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let wat = r#"
+        (module $states
+            (func $state_a (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 100
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $state_b
+                end)
+            (func $state_b (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 200
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $state_a
+                end)
+            (func (export "start_machine") (param i32) (result i32)
+                local.get 0
+                return_call $state_a)
+        )
+    "#;
+    let module = Module::new(&engine, wat)?;
+
+    let transitions = Arc::new(Mutex::new(Vec::new()));
+    let mut store = Store::new(&engine, ());
+    let recorded = transitions.clone();
+    store.tail_call_hook(move |module, from, to| {
+        assert_eq!(module.name(), Some("states"));
+        let mut transitions = recorded.lock().unwrap();
+        if transitions.len() == 10 {
+            anyhow::bail!("too many transitions");
+        }
+        transitions.push((from.as_u32(), to.as_u32()));
+        Ok(())
+    })?;
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let start = instance.get_typed_func::<i32, i32>(&mut store, "start_machine")?;
+    assert_eq!(start.call(&mut store, 3)?, 200);
+    assert_eq!(
+        *transitions.lock().unwrap(),
+        [(2, 0), (0, 1), (1, 0), (0, 1)]
+    );
+
+    // An error from the hook stops the tail call and is raised as a trap,
+    // after which the store is still usable.
+    transitions.lock().unwrap().clear();
+    let err = start.call(&mut store, 20).unwrap_err();
+    assert!(
+        format!("{err:?}").contains("too many transitions"),
+        "bad error: {err:?}"
+    );
+    assert_eq!(transitions.lock().unwrap().len(), 10);
+    transitions.lock().unwrap().clear();
+    assert_eq!(start.call(&mut store, 0)?, 100);
+    assert_eq!(*transitions.lock().unwrap(), [(2, 0)]);
+
+    // Hooks can't be installed unless they're enabled.
+    let engine = self::engine();
+    let mut store = Store::new(&engine, ());
+    assert!(store.tail_call_hook(|_, _, _| Ok(())).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn epoch_interruption_in_tail_call_loops() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $spin (export "spin") (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add
+                return_call $spin)
+            (func $ping (export "ping") (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add
+                return_call $pong)
+            (func $pong (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $ping)
+        )
+    "#;
+    for self_recursion_loop in [false, true] {
+        let mut options = TailCallOptions::new();
+        options.self_recursion_loop(self_recursion_loop);
+        let mut config = config();
+        config.epoch_interruption(true).tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        if self_recursion_loop {
+            assert_eq!(call_classes(&module)[0], CallClass::SelfTailLoop);
+        }
+
+        for export in ["spin", "ping"] {
+            let mut store = Store::new(&engine, 0_usize);
+            store.set_epoch_deadline(1);
+            store.epoch_deadline_callback(|mut cx| {
+                *cx.data_mut() += 1;
+                Ok(UpdateDeadline::Interrupt)
+            });
+            let instance = Instance::new(&mut store, &module, &[])?;
+            let func = instance.get_typed_func::<i32, i32>(&mut store, export)?;
+
+            // The infinite tail-call loop can only end by being interrupted
+            // once the epoch is incremented.
+            let incrementer = std::thread::spawn({
+                let engine = engine.clone();
+                move || {
+                    std::thread::sleep(std::time::Duration::from_millis(10));
+                    engine.increment_epoch();
+                }
+            });
+            let err = func.call(&mut store, 0).unwrap_err();
+            incrementer.join().unwrap();
+            assert_eq!(
+                err.downcast_ref::<Trap>(),
+                Some(&Trap::Interrupt),
+                "self_recursion_loop = {self_recursion_loop}, export = {export}: {err:?}"
+            );
+            assert_eq!(*store.data(), 1);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn is_tail_call_pure() -> Result<()> {
+    let engine = engine();
+    let pure = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                (func $leaf (param i32) (result i32)
+                    local.get 0)
+                {COUNTDOWN}
+                (func (param i32) (result i32)
+                    local.get 0
+                    return_call $leaf)
+            )
+        "#
+        ),
+    )?;
+    assert!(pure.is_tail_call_pure());
+    assert!(Module::new(&engine, "(module)")?.is_tail_call_pure());
+
+    for body in ["call $leaf", "call $leaf return_call $leaf"] {
+        let module = Module::new(
+            &engine,
+            &format!(
+                r#"
+                    (module
+                        (func $leaf (param i32) (result i32)
+                            local.get 0)
+                        (func (param i32) (result i32)
+                            local.get 0
+                            {body})
+                    )
+                "#
+            ),
+        )?;
+        assert!(!module.is_tail_call_pure(), "{body}");
+    }
+
+    // Self-recursive tail calls compiled as loops don't affect purity.
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.self_recursion_loop(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, "(module (func $f return_call $f))")?;
+    assert_eq!(call_classes(&module), [CallClass::SelfTailLoop]);
+    assert!(module.is_tail_call_pure());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_stack_limit() -> Result<()> {
+    let mut options = TailCallOptions::new();
+    options.stack_limit(10);
+    let mut config = config();
+    config.wasm_exceptions(true).tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                (tag $e)
+                (func $recurse (export "recurse") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        call $recurse
+                        i32.const 1
+                        i32.add
+                    end)
+                {COUNTDOWN}
+                (export "countdown" (func $countdown))
+                (func $throw-at (param i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        throw $e
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    call $throw-at)
+                (func (export "catch-many") (param i32) (result i32)
+                    (local $caught i32)
+                    loop $l
+                        block $h
+                            try_table (catch $e $h)
+                                i32.const 5
+                                call $throw-at
+                            end
+                        end
+                        local.get $caught
+                        i32.const 1
+                        i32.add
+                        local.set $caught
+                        local.get $caught
+                        local.get 0
+                        i32.lt_u
+                        br_if $l
+                    end
+                    local.get $caught)
+            )
+        "#
+        ),
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let recurse = instance.get_typed_func::<i32, i32>(&mut store, "recurse")?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    let catch_many = instance.get_typed_func::<i32, i32>(&mut store, "catch-many")?;
+
+    // Up to 10 regular calls may be in progress at once.
+    assert_eq!(recurse.call(&mut store, 10)?, 10);
+    let err = recurse.call(&mut store, 11).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::RegularCallDepthExceeded),
+        "{err:?}"
+    );
+
+    // The trap didn't use up any of the depth of later calls.
+    assert_eq!(recurse.call(&mut store, 10)?, 10);
+
+    // Tail calls aren't limited.
+    assert_eq!(countdown.call(&mut store, 1_000_000)?, 0);
+
+    // Calls unwound by a caught exception don't use up any depth either.
+    assert_eq!(catch_many.call(&mut store, 100)?, 100);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn br_table_dispatch_to_tail_calls() -> Result<()> {
+    let engine = engine();
+    for arms in [1, 2, 3, 8, 33] {
+        // `$dispatch` lowers a `match` on its parameter with `arms` cases and
+        // a default case, each of which tail calls a different function
+        // returning the case's number.
+        let mut wat = String::from("(module\n");
+        for case in 0..=arms {
+            wat.push_str(&format!(
+                "(func $case{case} (result i32) i32.const {case})\n"
+            ));
+        }
+        wat.push_str("(func $dispatch (export \"dispatch\") (param i32) (result i32)\n");
+        for case in (0..=arms).rev() {
+            wat.push_str(&format!("block $l{case}\n"));
+        }
+        wat.push_str("local.get 0\nbr_table");
+        for case in 0..=arms {
+            wat.push_str(&format!(" $l{case}"));
+        }
+        wat.push('\n');
+        for case in 0..=arms {
+            wat.push_str(&format!("end\nreturn_call $case{case}\n"));
+        }
+        wat.push_str("))\n");
+
+        let module = Module::new(&engine, &wat)?;
+        let classes = call_classes(&module);
+        assert_eq!(
+            classes[usize::try_from(arms).unwrap() + 1],
+            CallClass::TailCallOnly,
+            "arms = {arms}"
+        );
+        if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+            let savings = module.function_tail_call_frame_savings();
+            assert!(savings.last().unwrap().1.is_some(), "arms = {arms}");
+        }
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let dispatch = instance.get_typed_func::<i32, i32>(&mut store, "dispatch")?;
+        for case in 0..arms {
+            assert_eq!(dispatch.call(&mut store, case)?, case, "arms = {arms}");
+        }
+        // Out-of-range values take the default arm.
+        for value in [arms, arms + 1, -1] {
+            assert_eq!(dispatch.call(&mut store, value)?, arms, "arms = {arms}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_hints_section() -> Result<()> {
+    // Functions 0 and 1 only make tail calls, but function 1 needs stack space
+    // for its outgoing arguments, and function 3 makes a regular call.
+    let module = |hints: &str| {
+        format!(
+            r#"
+                (module
+                    {COUNTDOWN}
+                    (func $few (param i32) (result i32)
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        local.get 0
+                        return_call $many)
+                    (func $many (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                        (result i32)
+                        local.get 0
+                        local.get 11
+                        i32.add)
+                    (func $regular (result i32)
+                        i32.const 10
+                        call $countdown)
+                    (@custom "tail_call_hints" (after last) "{hints}")
+                )
+            "#
+        )
+    };
+    let countdown_optimized = |module: &Module| {
+        module
+            .function_tail_call_frame_savings()
+            .into_iter()
+            .map(|(_, saved)| saved.is_some())
+            .collect::<Vec<_>>()[0]
+    };
+    let supported = cfg!(any(target_arch = "x86_64", target_arch = "aarch64"));
+
+    // `forbid` opts a function out of the engine's optimization.
+    let engine = engine();
+    let m = Module::new(&engine, module("\\00"))?;
+    assert_eq!(countdown_optimized(&m), supported);
+    let m = Module::new(&engine, module("\\01\\00\\02"))?;
+    assert!(!countdown_optimized(&m));
+
+    // A malformed section is ignored entirely.
+    let m = Module::new(&engine, module("\\02\\00\\02\\01\\07"))?;
+    assert_eq!(countdown_optimized(&m), supported);
+
+    // `force` opts a function in even if the engine doesn't optimize frames.
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.relaxed_stack_check(false);
+    config.tail_call_options(options);
+    let unoptimized = Engine::new(&config)?;
+    let m = Module::new(&unoptimized, module("\\00"))?;
+    assert!(!countdown_optimized(&m));
+    if supported {
+        let m = Module::new(&unoptimized, module("\\01\\00\\01"))?;
+        assert!(countdown_optimized(&m));
+    } else {
+        let err = format!(
+            "{:?}",
+            Module::new(&unoptimized, module("\\01\\00\\01")).unwrap_err()
+        );
+        assert!(err.contains("isn't supported"), "bad error: {err}");
+    }
+
+    // Forcing a function whose frame can't be optimized fails, but only for
+    // that function.
+    if supported {
+        Module::new(&engine, module("\\01\\00\\01"))?;
+        let err = format!(
+            "{:?}",
+            Module::new(&engine, module("\\01\\01\\01")).unwrap_err()
+        );
+        assert!(
+            err.contains(
+                "function 1 is marked `force` in the `tail_call_hints` section but its frame \
+                 couldn't be optimized"
+            ),
+            "bad error: {err}"
+        );
+        assert!(err.contains("outgoing arguments"), "bad error: {err}");
+    }
+    let err = format!(
+        "{:?}",
+        Module::new(&engine, module("\\01\\03\\01")).unwrap_err()
+    );
+    assert!(err.contains("it makes regular calls"), "bad error: {err}");
+
+    // Forcing a function which profiling makes call into the host names that
+    // as the cause.
+    let mut config = self::config();
+    let mut options = TailCallOptions::new();
+    options.profiling(true);
+    config.tail_call_options(options);
+    let profiled = Engine::new(&config)?;
+    let err = format!(
+        "{:?}",
+        Module::new(&profiled, module("\\01\\00\\01")).unwrap_err()
+    );
+    assert!(
+        err.contains("TailCallOptions::profiling"),
+        "bad error: {err}"
+    );
+
+    // Forcing a function that doesn't make any calls has no effect.
+    Module::new(&engine, module("\\01\\02\\01"))?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn div_by_zero_in_deep_tail_recursion() -> Result<()> {
+    // `$divide` traps once its counter reaches zero, after replacing its own
+    // frame many times. `$mixed` makes a regular call before tail calling into
+    // it, and `outer` calls `$mixed` regularly, so its frame stays on the
+    // stack below the trapping one.
+    let wat = r#"
+        (module
+            (func $divide (param i32 i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    local.get 1
+                    local.get 0
+                    i32.div_s
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    local.get 1
+                    return_call $divide
+                end)
+            (func $helper (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add)
+            (func $mixed (export "mixed") (param i32) (result i32)
+                local.get 0
+                local.get 0
+                call $helper
+                return_call $divide)
+            (func (export "outer") (param i32) (result i32)
+                local.get 0
+                call $mixed
+                i32.const 1
+                i32.add)
+        )
+    "#;
+
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let mixed = instance.get_typed_func::<i32, i32>(&mut store, "mixed")?;
+        let outer = instance.get_typed_func::<i32, i32>(&mut store, "outer")?;
+
+        for (func, depth) in [(&mixed, 1), (&outer, 2)] {
+            let err = func.call(&mut store, 100_000).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<Trap>(),
+                Some(&Trap::IntegerDivisionByZero),
+                "bad error: {err:?}"
+            );
+            let trace = err.downcast_ref::<WasmBacktrace>().unwrap();
+            assert_eq!(trace.frames().len(), depth, "bad backtrace: {trace}");
+            assert_eq!(trace.frames()[0].func_index(), 0);
+            assert!(trace.had_tail_calls());
+        }
+
+        // The store is still usable after unwinding through the trap.
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (func $countdown (export "countdown") (param i32) (result i32)
+                        local.get 0
+                        i32.eqz
+                        if (result i32)
+                            i32.const 7
+                        else
+                            local.get 0
+                            i32.const 1
+                            i32.sub
+                            return_call $countdown
+                        end)
+                )
+            "#,
+        )?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+        assert_eq!(countdown.call(&mut store, 100_000)?, 7);
+    }
+    Ok(())
+}
+
+#[wasmtime_test(wasm_features(tail_call, gc, function_references))]
+#[cfg_attr(miri, ignore)]
+fn tail_call_with_gc_ref_params(config: &mut Config) -> Result<()> {
+    let engine = Engine::new(config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $s (struct (field i32)))
+                (type $garbage (array i32))
+
+                ;; Threads a `structref` through its own parameters while
+                ;; allocating garbage on every iteration, so collections happen
+                ;; while the only reference to the struct is in the arguments
+                ;; of a tail call.
+                (func $walk (param (ref $s) anyref i32) (result i32)
+                    local.get 2
+                    i32.eqz
+                    if (result i32)
+                        local.get 0
+                        struct.get $s 0
+                        local.get 1
+                        ref.cast (ref $s)
+                        struct.get $s 0
+                        i32.add
+                    else
+                        local.get 0
+                        local.get 1
+                        local.get 2
+                        i32.const 1
+                        i32.sub
+                        i32.const 0
+                        i32.const 64
+                        array.new $garbage
+                        drop
+                        return_call $walk
+                    end)
+
+                (func (export "run") (param i32) (result i32)
+                    i32.const 40
+                    struct.new $s
+                    i32.const 2
+                    struct.new $s
+                    local.get 0
+                    return_call $walk)
+            )
+        "#,
+    )?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::TailCallOnly, CallClass::TailCallOnly]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+    for n in [0, 1, 10_000, 100_000] {
+        assert_eq!(run.call(&mut store, n)?, 42);
+    }
+    store.gc(None);
+    assert_eq!(run.call(&mut store, 1_000)?, 42);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_min_frame_savings() -> Result<()> {
+    let compile = |min_frame_savings: u32| -> Result<(Module, Vec<TailCallDeoptInfo>)> {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.min_frame_savings(min_frame_savings);
+        let deopts = collect_deopts(&mut options);
+        config.tail_call_options(options);
+        let module = countdown_module(&Engine::new(&config)?)?;
+        let deopts = deopts.lock().unwrap().clone();
+        Ok((module, deopts))
+    };
+
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        // A small threshold still optimizes the frame, which saves at least
+        // the frame pointer and return address.
+        let (module, deopts) = compile(8)?;
+        assert!(deopts.is_empty(), "{deopts:?}");
+        let saved = module.function_tail_call_frame_savings()[0].1.unwrap();
+        assert!(saved >= 8);
+
+        // A threshold above what can be saved leaves a standard frame.
+        let (module, deopts) = compile(u32::from(u8::MAX))?;
+        assert_eq!(deopts.len(), 1, "{deopts:?}");
+        assert_eq!(deopts[0].reason, TailCallDeoptReason::BelowSavingsThreshold);
+        assert_eq!(module.function_tail_call_frame_savings()[0].1, None);
+        assert_eq!(call_classes(&module), [CallClass::TailCallOnly]);
+
+        let mut store = Store::new(module.engine(), ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+        assert_eq!(countdown.call(&mut store, 100_000)?, 0);
+    }
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.min_frame_savings(256);
+    config.tail_call_options(options);
+    assert!(Engine::new(&config).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn nan_canonicalization_through_tail_calls() -> Result<()> {
+    // `$step` passes its float arguments along unchanged (`keep`) or after
+    // adding zero to them (`add`), via either a tail call or a regular call.
+    // Canonicalization applies to the results of arithmetic, not to
+    // arguments, so both kinds of call must agree on what the base case sees.
+    let wat = |call: &str| {
+        format!(
+            r#"
+                (module
+                    (func $step (param $n i32) (param $add i32) (param f32 f64)
+                        (result i32 i64)
+                        local.get $n
+                        i32.eqz
+                        if (result i32 i64)
+                            local.get 2
+                            i32.reinterpret_f32
+                            local.get 3
+                            i64.reinterpret_f64
+                        else
+                            local.get $n
+                            i32.const 1
+                            i32.sub
+                            local.get $add
+                            local.get 2
+                            local.get 3
+                            local.get $add
+                            if (param f32 f64) (result f32 f64)
+                                local.set 3
+                                f32.const 0
+                                f32.add
+                                local.get 3
+                                f64.const 0
+                                f64.add
+                            end
+                            {call} $step
+                        end)
+                    (func (export "run") (param i32 i32 i32 i64) (result i32 i64)
+                        local.get 0
+                        local.get 1
+                        local.get 2
+                        f32.reinterpret_i32
+                        local.get 3
+                        f64.reinterpret_i64
+                        {call} $step)
+                )
+            "#
+        )
+    };
+
+    const F32_NAN: i32 = 0x7fa0_0001;
+    const F64_NAN: i64 = 0x7ff4_0000_0000_0001;
+    const F32_CANONICAL: i32 = 0x7fc0_0000;
+    const F64_CANONICAL: i64 = 0x7ff8_0000_0000_0000;
+
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        config.cranelift_nan_canonicalization(true);
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let mut store = Store::new(&engine, ());
+
+        for call in ["return_call", "call"] {
+            let module = Module::new(&engine, wat(call))?;
+            let instance = Instance::new(&mut store, &module, &[])?;
+            let run =
+                instance.get_typed_func::<(i32, i32, i32, i64), (i32, i64)>(&mut store, "run")?;
+
+            // Arithmetic on the way canonicalizes the NaNs.
+            for n in [1, 100] {
+                let (f, d) = run.call(&mut store, (n, 1, F32_NAN, F64_NAN))?;
+                assert_eq!(f, F32_CANONICAL, "{call}, n = {n}");
+                assert_eq!(d, F64_CANONICAL, "{call}, n = {n}");
+            }
+
+            // Without any arithmetic the bits pass through untouched.
+            for n in [0, 1, 100] {
+                let (f, d) = run.call(&mut store, (n, 0, F32_NAN, F64_NAN))?;
+                assert_eq!(f, F32_NAN, "{call}, n = {n}");
+                assert_eq!(d, F64_NAN, "{call}, n = {n}");
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn call_graph() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (import "" "host" (func $host (type $t)))
+                (table 1 funcref)
+                (func $even (type $t)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $odd
+                    end)
+                (func $odd (type $t)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $even
+                    end)
+                (func $main (type $t)
+                    local.get 0
+                    call $host
+                    call $even
+                    i32.const 0
+                    return_call_indirect (type $t))
+                (func $leaf (type $t)
+                    local.get 0)
+            )
+        "#,
+    )?;
+
+    let describe = |module: &Module| {
+        module
+            .call_graph()
+            .into_iter()
+            .map(|edge| {
+                let to = match edge.to {
+                    CallTarget::Direct(func) => format!("func {}", func.as_u32()),
+                    CallTarget::Indirect { type_index } => {
+                        format!("type {}", type_index.as_u32())
+                    }
+                };
+                (edge.from.as_u32(), to, edge.kind)
+            })
+            .collect::<Vec<_>>()
+    };
+    let expected = [
+        (1, "func 2".to_string(), CallKind::Tail),
+        (2, "func 1".to_string(), CallKind::Tail),
+        (3, "func 0".to_string(), CallKind::Regular),
+        (3, "func 1".to_string(), CallKind::Regular),
+        (3, "type 0".to_string(), CallKind::Tail),
+    ];
+    assert_eq!(describe(&module), expected);
+
+    // The call graph is recorded in the compiled artifact.
+    let deserialized = unsafe { Module::deserialize(&engine, &module.serialize()?)? };
+    assert_eq!(describe(&deserialized), expected);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn return_call_host_closure() -> Result<()> {
+    let wat = r#"
+        (module
+            (import "" "scale" (func $scale (param i32 i64) (result i64)))
+            (import "" "fail" (func $fail (param i32 i64) (result i64)))
+
+            ;; Tail calls the host directly, so the host's result is this
+            ;; function's result.
+            (func $direct (export "direct") (param i32 i64) (result i64)
+                local.get 0
+                local.get 1
+                return_call $scale)
+
+            ;; Counts down through tail calls before handing off to the host.
+            (func $countdown (export "countdown") (param i32 i64) (result i64)
+                local.get 0
+                i32.eqz
+                if (result i64)
+                    i32.const 3
+                    local.get 1
+                    return_call $scale
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    local.get 1
+                    i64.const 1
+                    i64.add
+                    return_call $countdown
+                end)
+
+            ;; Consumes the host's result after a regular call into a
+            ;; function which tail calls the host.
+            (func (export "regular") (param i32 i64) (result i64)
+                local.get 0
+                local.get 1
+                call $direct
+                i64.const 1
+                i64.add)
+
+            (func (export "fail") (param i32 i64) (result i64)
+                local.get 0
+                local.get 1
+                return_call $fail)
+        )
+    "#;
+
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        assert_eq!(
+            call_classes(&module),
+            [
+                CallClass::TailCallOnly,
+                CallClass::TailCallOnly,
+                CallClass::Regular,
+                CallClass::TailCallOnly,
+            ]
+        );
+
+        let mut store = Store::new(&engine, 0_u32);
+        let offset = 1000_i64;
+        let scale = Func::wrap(
+            &mut store,
+            move |mut caller: Caller<'_, u32>, factor: i32, x: i64| -> i64 {
+                *caller.data_mut() += 1;
+                i64::from(factor) * x + offset
+            },
+        );
+        let fail = Func::wrap(&mut store, |_: i32, _: i64| -> Result<i64> {
+            anyhow::bail!("host failure")
+        });
+        let instance = Instance::new(&mut store, &module, &[scale.into(), fail.into()])?;
+        let direct = instance.get_typed_func::<(i32, i64), i64>(&mut store, "direct")?;
+        let countdown = instance.get_typed_func::<(i32, i64), i64>(&mut store, "countdown")?;
+        let regular = instance.get_typed_func::<(i32, i64), i64>(&mut store, "regular")?;
+        let fail = instance.get_typed_func::<(i32, i64), i64>(&mut store, "fail")?;
+
+        assert_eq!(direct.call(&mut store, (2, 21))?, 1042);
+        assert_eq!(countdown.call(&mut store, (100_000, 0))?, 301_000);
+        assert_eq!(regular.call(&mut store, (2, 21))?, 1043);
+        assert_eq!(*store.data(), 3);
+
+        let err = fail.call(&mut store, (0, 0)).unwrap_err();
+        assert!(
+            format!("{err:?}").contains("host failure"),
+            "bad error: {err:?}"
+        );
+
+        // The store is still usable after the host's error unwound through
+        // the tail call.
+        assert_eq!(direct.call(&mut store, (1, 1))?, 1001);
+        assert_eq!(*store.data(), 4);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_debug_assertions() -> Result<()> {
+    let wat = format!(
+        r#"
+        (module
+            (type $ft (func (param i32) (result i32)))
+            (table 1 funcref)
+            (elem (i32.const 0) $countdown)
+
+            {COUNTDOWN}
+            (export "countdown" (func $countdown))
+
+            (func $even (export "even") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 1
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $odd
+                end)
+            (func $odd (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $even
+                end)
+
+            (func (export "indirect") (param i32) (result i32)
+                local.get 0
+                i32.const 0
+                return_call_indirect (type $ft))
+
+            ;; Tail calls made from within a regular call check the frame of
+            ;; the function making the regular call.
+            (func (export "nested") (param i32) (result i32)
+                local.get 0
+                call $even
+                i32.const 1
+                i32.add)
+        )
+    "#
+    );
+
+    let compile = |debug_assertions: bool| -> Result<Module> {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.debug_assertions(debug_assertions);
+        config.tail_call_options(options);
+        Module::new(&Engine::new(&config)?, &wat)
+    };
+    let module = compile(true)?;
+
+    // The canary's slot doesn't get in the way of frames being optimized.
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        let savings = module
+            .function_tail_call_frame_savings()
+            .into_iter()
+            .map(|(_, saved)| saved.is_some())
+            .collect::<Vec<_>>();
+        assert_eq!(savings[..3], [true, true, true]);
+        assert!(!savings[4]);
+    }
+
+    // An intact frame passes the checks however many tail calls are made.
+    let mut store = Store::new(module.engine(), ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    let even = instance.get_typed_func::<i32, i32>(&mut store, "even")?;
+    let indirect = instance.get_typed_func::<i32, i32>(&mut store, "indirect")?;
+    let nested = instance.get_typed_func::<i32, i32>(&mut store, "nested")?;
+    assert_eq!(countdown.call(&mut store, 1_000_000)?, 0);
+    assert_eq!(even.call(&mut store, 1_000_001)?, 0);
+    assert_eq!(even.call(&mut store, 1_000_000)?, 1);
+    assert_eq!(indirect.call(&mut store, 1_000)?, 0);
+    assert_eq!(nested.call(&mut store, 10)?, 2);
+
+    // A tail call made after the canary in its caller's frame has been
+    // clobbered traps instead.
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.debug_assertions(true);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (import "" "clobber" (func $clobber))
+                    (func $callee (param i32) (result i32) local.get 0)
+                    (func (export "run") (param i32) (result i32)
+                        call $clobber
+                        local.get 0
+                        return_call $callee)
+                )
+            "#,
+        )?;
+        let mut store = Store::new(&engine, ());
+        let clobber = Func::wrap(&mut store, || {
+            // Wasm frames are above this host frame on the stack, so scan up
+            // from here for the canary stored by `run`, which is the
+            // `TAIL_CALL_CANARY` of the Cranelift backend.
+            const CANARY: u64 = 0x7a11_ca11_c0de_d00d;
+            let marker = 0u64;
+            let mut slot = std::ptr::addr_of!(marker) as *mut u64;
+            for _ in 0..(64 << 10) / 8 {
+                unsafe {
+                    if slot.read_volatile() == CANARY {
+                        slot.write_volatile(0);
+                        return;
+                    }
+                    slot = slot.add(1);
+                }
+            }
+            panic!("canary not found on the stack");
+        });
+        let instance = Instance::new(&mut store, &module, &[clobber.into()])?;
+        let run = instance.get_typed_func::<i32, i32>(&mut store, "run")?;
+        let err = run.call(&mut store, 1).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<Trap>(),
+            Some(&Trap::TailCallFrameCorruption)
+        );
+    }
+
+    // The assertions are part of the compiled code, so modules compiled with
+    // and without them aren't compatible.
+    let bytes = compile(false)?.serialize()?;
+    assert!(unsafe { Module::deserialize(module.engine(), &bytes) }.is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+#[cfg(target_os = "linux")]
+fn perfmap_annotates_reused_frames() -> Result<()> {
+    let mut config = config();
+    config.profiler(ProfilingStrategy::PerfMap);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $perfmap_countdown (export "countdown") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $perfmap_countdown
+                    end)
+                (func $perfmap_regular (export "regular") (param i32) (result i32)
+                    local.get 0
+                    call $perfmap_countdown
+                    i32.const 1
+                    i32.add)
+            )
+        "#,
+    )?;
+    let optimized = module.function_tail_call_frame_savings()[0].1.is_some();
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let regular = instance.get_typed_func::<i32, i32>(&mut store, "regular")?;
+    assert_eq!(regular.call(&mut store, 1_000)?, 1);
+
+    let map = std::fs::read_to_string(format!("/tmp/perf-{}.map", std::process::id()))?;
+    let symbols = map
+        .lines()
+        .filter_map(|line| line.splitn(3, ' ').nth(2))
+        .filter(|name| name.starts_with("perfmap_"))
+        .collect::<Vec<_>>();
+    let expected = if optimized {
+        "perfmap_countdown [reused frame]"
+    } else {
+        "perfmap_countdown"
+    };
+    assert!(symbols.contains(&expected), "{symbols:?}");
+    assert!(symbols.contains(&"perfmap_regular"), "{symbols:?}");
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(optimized);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn deep_many_params_tail_calls() -> Result<()> {
+    // Twelve parameters are more than any target passes in registers, so tail
+    // calls between these functions pass some of their arguments on the stack,
+    // and those from `$few` to `$many` and back have to grow and then shrink
+    // the incoming argument area each time.
+    let wat = r#"
+        (module
+            (func $many (export "many")
+                (param $n i32) (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                (result i32)
+                local.get $n
+                i32.eqz
+                if (result i32)
+                    local.get 1
+                    local.get 11
+                    i32.add
+                else
+                    ;; Rotate the stack-passed arguments through the register
+                    ;; ones so that both have to be set up for each call.
+                    local.get $n
+                    i32.const 1
+                    i32.sub
+                    local.get 11
+                    local.get 1
+                    local.get 2
+                    local.get 3
+                    local.get 4
+                    local.get 5
+                    local.get 6
+                    local.get 7
+                    local.get 8
+                    local.get 9
+                    local.get 10
+                    return_call $many
+                end)
+            (func $few (export "few") (param $n i32) (result i32)
+                local.get $n
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get $n
+                    i32.const 1
+                    i32.sub
+                    i32.const 1
+                    i32.const 2
+                    i32.const 3
+                    i32.const 4
+                    i32.const 5
+                    i32.const 6
+                    i32.const 7
+                    i32.const 8
+                    i32.const 9
+                    i32.const 10
+                    i32.const 11
+                    return_call $back
+                end)
+            (func $back
+                (param $n i32) (param i32 i32 i32 i32 i32 i32 i32 i32 i32 i32 i32)
+                (result i32)
+                local.get $n
+                return_call $few)
+        )
+    "#;
+
+    let many_params = |n: i32| {
+        let mut params = vec![Val::I32(n)];
+        params.extend((1..=11).map(Val::I32));
+        params
+    };
+
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let many = instance.get_func(&mut store, "many").unwrap();
+        let few = instance.get_typed_func::<i32, i32>(&mut store, "few")?;
+
+        // After `n` rotations the arguments are back where they started
+        // whenever `n` is a multiple of 11.
+        let mut results = [Val::I32(0)];
+        many.call(&mut store, &many_params(110_000), &mut results)?;
+        assert_eq!(results[0].unwrap_i32(), 12);
+        many.call(&mut store, &many_params(1), &mut results)?;
+        assert_eq!(results[0].unwrap_i32(), 11 + 10);
+        assert_eq!(few.call(&mut store, 100_000)?, 0);
+
+        // Neither the arguments left on the stack by each call nor the
+        // growing and shrinking of the argument area accumulate, whether or
+        // not the frames were optimized.
+        assert_constant_stack(
+            &config,
+            &module,
+            "many",
+            &many_params(11),
+            &many_params(110_000),
+        );
+        assert_constant_stack(
+            &config,
+            &module,
+            "few",
+            &[Val::I32(10)],
+            &[Val::I32(100_000)],
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn function_call_conv() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                (import "" "f" (func $f))
+                {COUNTDOWN}
+                (func (export "regular") (param i32) (result i32)
+                    call $f
+                    local.get 0
+                    call $countdown)
+            )
+        "#
+        ),
+    )?;
+
+    // Every function compiled by Cranelift uses the `tail` calling
+    // convention, whatever the target or the calls the function makes.
+    let classes = module.function_call_classes();
+    assert_eq!(classes.len(), 2);
+    for (index, _) in classes {
+        assert_eq!(module.function_call_conv(index), Some(CallConv::Tail));
+    }
+
+    // Imported functions have no calling convention of their own.
+    let imported = module
+        .call_graph()
+        .into_iter()
+        .find_map(|edge| match edge.to {
+            CallTarget::Direct(to) if to.as_u32() == 0 => Some(to),
+            _ => None,
+        })
+        .unwrap();
+    assert_eq!(module.function_call_conv(imported), None);
+    assert_eq!(CallConv::Tail.to_string(), "tail");
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_max_optimized_body_bytes() -> Result<()> {
+    // `$large` and `$large-regular` have bodies of several hundred bytes while
+    // `$countdown`'s is a few dozen.
+    let padding = "local.get 0 i32.const 1 i32.add local.set 0\n".repeat(64);
+    let wat = format!(
+        r#"
+            (module
+                {COUNTDOWN}
+                (export "countdown" (func $countdown))
+                (func $large (export "large") (param i32) (result i32)
+                    {padding}
+                    local.get 0
+                    return_call $countdown)
+                (func $large-regular (export "large-regular") (param i32) (result i32)
+                    {padding}
+                    local.get 0
+                    call $countdown)
+            )
+        "#
+    );
+    let compile = |max: Option<u32>| -> Result<(Module, Vec<TailCallDeoptInfo>)> {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.max_optimized_body_bytes(max);
+        let deopts = collect_deopts(&mut options);
+        config.tail_call_options(options);
+        let module = Module::new(&Engine::new(&config)?, &wat)?;
+        let deopts = deopts.lock().unwrap().clone();
+        Ok((module, deopts))
+    };
+
+    // Only the large function which only makes tail calls is affected, and
+    // it's still classified by the calls it makes.
+    let (module, deopts) = compile(Some(128))?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+            CallClass::Regular
+        ]
+    );
+    assert!(
+        deopts.iter().any(
+            |d| d.func_index.as_u32() == 1 && d.reason == TailCallDeoptReason::FunctionTooLarge
+        ),
+        "{deopts:?}"
+    );
+    assert!(
+        deopts.iter().all(
+            |d| d.func_index.as_u32() == 1 || d.reason != TailCallDeoptReason::FunctionTooLarge
+        ),
+        "{deopts:?}"
+    );
+    let savings = module.function_tail_call_frame_savings();
+    assert_eq!(savings[1].1, None);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(savings[0].1.is_some());
+    }
+
+    // The deopt describes the standard frame the function was compiled with.
+    let deopt = deopts.iter().find(|d| d.func_index.as_u32() == 1).unwrap();
+    let layout = module.function_frame_layout(deopt.func_index).unwrap();
+    assert_eq!(deopt.outgoing_args_size, layout.outgoing_args_size);
+    assert_eq!(deopt.stackslots_size, layout.stack_slots_size);
+
+    let mut store = Store::new(module.engine(), ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let large = instance.get_typed_func::<i32, i32>(&mut store, "large")?;
+    assert_eq!(large.call(&mut store, -64)?, 0);
+
+    // Without a limit, or with one larger than every function, nothing is
+    // reported as too large.
+    for max in [None, Some(1 << 20)] {
+        let (module, deopts) = compile(max)?;
+        assert!(
+            deopts
+                .iter()
+                .all(|d| d.reason != TailCallDeoptReason::FunctionTooLarge),
+            "{deopts:?}"
+        );
+        if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+            assert!(module.function_tail_call_frame_savings()[1].1.is_some());
+        }
+    }
+
+    // Artifacts aren't compatible with engines using a different limit.
+    let bytes = compile(Some(128))?.0.serialize()?;
+    let other = compile(None)?.0;
+    assert!(unsafe { Module::deserialize(other.engine(), &bytes) }.is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_classifier() -> Result<()> {
+    let wat = format!(
+        r#"
+        (module
+            {COUNTDOWN}
+            (export "countdown" (func $countdown))
+            (func $other (export "other") (param i32) (result i32)
+                local.get 0
+                return_call $countdown)
+            (func $regular (export "regular") (param i32) (result i32)
+                local.get 0
+                call $countdown)
+        )
+    "#
+    );
+    let compile = |classifier: fn(u32, CallClass) -> CallClass| -> Result<Module> {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.classifier(move |index, class| classifier(index.as_u32(), class));
+        config.tail_call_options(options);
+        Module::new(&Engine::new(&config)?, &wat)
+    };
+
+    // Reclassifying a function which only makes tail calls as making regular
+    // calls keeps it from being optimized, without changing its reported
+    // class.
+    let module = compile(|index, class| {
+        if index == 1 {
+            CallClass::Regular
+        } else {
+            class
+        }
+    })?;
+    assert_eq!(
+        call_classes(&module),
+        [
+            CallClass::TailCallOnly,
+            CallClass::TailCallOnly,
+            CallClass::Regular
+        ]
+    );
+    let savings = module.function_tail_call_frame_savings();
+    assert_eq!(savings[1].1, None);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(savings[0].1.is_some());
+    }
+    let mut store = Store::new(module.engine(), ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let other = instance.get_typed_func::<i32, i32>(&mut store, "other")?;
+    assert_eq!(other.call(&mut store, 1000)?, 0);
+
+    // Changes that could let a frame be optimized when it isn't safe are
+    // rejected.
+    let err = compile(|index, class| {
+        if index == 2 {
+            CallClass::TailCallOnly
+        } else {
+            class
+        }
+    })
+    .unwrap_err();
+    assert!(
+        format!("{err:?}").contains("reclassified function 2"),
+        "{err:?}"
+    );
+    assert!(compile(|_, _| CallClass::SelfTailLoop).is_err());
+    assert!(compile(|_, _| CallClass::Leaf).is_err());
+
+    // Returning each function's own class changes nothing.
+    let module = compile(|_, class| class)?;
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(module.function_tail_call_frame_savings()[1].1.is_some());
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_callers() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (import "" "host" (func $host (type $t)))
+                (table 1 funcref)
+                (func $state_a (type $t)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $state_b
+                    end)
+                (func $state_b (type $t)
+                    local.get 0
+                    i32.const 1
+                    i32.and
+                    if (result i32)
+                        local.get 0
+                        return_call $state_a
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $state_b
+                    end)
+                (func $start_machine (type $t)
+                    local.get 0
+                    call $state_b
+                    drop
+                    local.get 0
+                    return_call $state_a)
+                (func $dispatch (type $t)
+                    local.get 0
+                    i32.const 0
+                    return_call_indirect (type $t))
+                (func $to_host (type $t)
+                    local.get 0
+                    return_call $host)
+            )
+        "#,
+    )?;
+    let index = |i: u32| {
+        module
+            .function_call_classes()
+            .into_iter()
+            .map(|(index, _)| index)
+            .find(|index| index.as_u32() == i)
+            .unwrap()
+    };
+    let callers = |module: &Module, i: u32| {
+        module
+            .tail_call_callers(index(i))
+            .into_iter()
+            .map(|f| f.as_u32())
+            .collect::<Vec<_>>()
+    };
+
+    // `$state_a` is tail called from `$state_b` and `$start_machine`, while
+    // `$start_machine`'s regular call to `$state_b` isn't included.
+    assert_eq!(callers(&module, 1), [2, 3]);
+    assert_eq!(callers(&module, 2), [1, 2]);
+    assert_eq!(callers(&module, 3), [0u32; 0]);
+    assert_eq!(callers(&module, 4), [0u32; 0]);
+
+    // The callers of imported functions are included too.
+    let edge = module
+        .call_graph()
+        .into_iter()
+        .find(|edge| edge.from.as_u32() == 5)
+        .unwrap();
+    let CallTarget::Direct(host) = edge.to else {
+        panic!("unexpected call edge {edge:?}");
+    };
+    assert_eq!(host.as_u32(), 0);
+    let to_host = module
+        .tail_call_callers(host)
+        .into_iter()
+        .map(|f| f.as_u32())
+        .collect::<Vec<_>>();
+    assert_eq!(to_host, [5]);
+
+    // The reverse lookup is also available for deserialized modules.
+    let deserialized = unsafe { Module::deserialize(&engine, &module.serialize()?)? };
+    assert_eq!(callers(&deserialized, 1), [2, 3]);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn compile_warnings() -> Result<()> {
+    let regular = r#"
+        (module
+            (func $f (param i32) (result i32)
+                local.get 0)
+            (func (export "g") (param i32) (result i32)
+                local.get 0
+                call $f)
+        )
+    "#;
+    let tail = r#"
+        (module
+            (func $f (param i32) (result i32)
+                local.get 0)
+            (func (export "g") (param i32) (result i32)
+                local.get 0
+                return_call $f)
+        )
+    "#;
+
+    // Explicitly enabling tail calls for a module without any is reported.
+    let engine = engine();
+    let module = Module::new(&engine, regular)?;
+    assert_eq!(
+        module.compile_warnings(),
+        [CompileWarning::UnusedTailCallFlag]
+    );
+    assert!(
+        CompileWarning::UnusedTailCallFlag
+            .to_string()
+            .contains("tail calls")
+    );
+    let deserialized = unsafe { Module::deserialize(&engine, &module.serialize()?)? };
+    assert_eq!(
+        deserialized.compile_warnings(),
+        [CompileWarning::UnusedTailCallFlag]
+    );
+    assert!(Module::new(&engine, tail)?.compile_warnings().is_empty());
+
+    // Tail calls that are only enabled by default aren't reported.
+    let default = Engine::default();
+    assert!(
+        Module::new(&default, regular)?
+            .compile_warnings()
+            .is_empty()
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn drop_async_tail_call_loop() -> Result<()> {
+    use std::future::Future;
+    use std::pin::pin;
+    use std::task::{Context, Poll, Waker};
+
+    fn poll_once<F: Future>(future: std::pin::Pin<&mut F>) -> Poll<F::Output> {
+        future.poll(&mut Context::from_waker(Waker::noop()))
+    }
+
+    fn run<F: Future>(future: F) -> F::Output {
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(result) = poll_once(future.as_mut()) {
+                return result;
+            }
+        }
+    }
+
+    let wat = r#"
+        (module
+            (func $spin (export "spin") (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add
+                return_call $spin)
+            (func $ping (export "ping") (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.add
+                return_call $pong)
+            (func $pong (param i32) (result i32)
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $ping)
+        )
+    "#;
+    for relaxed_stack_check in [true, false] {
+        // With a single fiber stack in the pool, calls after a cancelled one
+        // only succeed if its stack was released.
+        let mut pool = crate::small_pool_config();
+        pool.total_stacks(1).table_elements(0);
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        let mut config = config();
+        config
+            .async_support(true)
+            .epoch_interruption(true)
+            .tail_call_options(options)
+            .allocation_strategy(InstanceAllocationStrategy::Pooling(pool));
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        let mut store = Store::new(&engine, 0_usize);
+        let instance = run(Instance::new_async(&mut store, &module, &[]))?;
+
+        for export in ["spin", "ping", "spin"] {
+            let func = instance.get_typed_func::<i32, i32>(&mut store, export)?;
+            *store.data_mut() = 0;
+            store.set_epoch_deadline(1);
+            // The new deadline is set once the loop resumes, relative to the
+            // epoch at that point, so a delta of zero makes every epoch check
+            // yield without needing to advance the epoch between polls.
+            store.epoch_deadline_callback(|mut cx| {
+                *cx.data_mut() += 1;
+                Ok(UpdateDeadline::Yield(0))
+            });
+            engine.increment_epoch();
+
+            // Each poll suspends the infinite loop once. Dropping the future
+            // then abandons the loop on its suspended fiber.
+            {
+                let mut future = pin!(func.call_async(&mut store, 0));
+                for _ in 0..3 {
+                    assert!(poll_once(future.as_mut()).is_pending());
+                }
+            }
+            assert_eq!(*store.data(), 3);
+
+            // The store remains usable, and another loop can be run on the
+            // released fiber stack until it's interrupted.
+            store.epoch_deadline_callback(|_| Ok(UpdateDeadline::Interrupt));
+            engine.increment_epoch();
+            let err = run(func.call_async(&mut store, 0)).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<Trap>(),
+                Some(&Trap::Interrupt),
+                "relaxed_stack_check = {relaxed_stack_check}, export = {export}: {err:?}"
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn untyped_call_with_references_through_tail_calls() -> Result<()> {
+    let wat = r#"
+        (module
+            (import "" "collect" (func $collect (param externref funcref) (result externref funcref)))
+            (func (export "seven") (result i32)
+                i32.const 7)
+            ;; Threads both references through `n` tail calls, then tail calls
+            ;; the host to collect garbage before they're returned.
+            (func $thread (export "thread") (param i32 externref funcref) (result externref funcref)
+                local.get 1
+                local.get 2
+                local.get 0
+                i32.eqz
+                if (param externref funcref) (result externref funcref)
+                    return_call $collect
+                end
+                local.set 2
+                local.set 1
+                local.get 0
+                i32.const 1
+                i32.sub
+                local.get 1
+                local.get 2
+                return_call $thread)
+        )
+    "#;
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        assert_eq!(call_classes(&module)[1], CallClass::TailCallOnly);
+
+        let mut store = Store::new(&engine, ());
+        let collect = Func::wrap(
+            &mut store,
+            |mut caller: Caller<'_, ()>, e: Option<Rooted<ExternRef>>, f: Option<Func>| {
+                // Collect garbage with the references only held by the
+                // arguments of this call.
+                caller.gc(None);
+                (e, f)
+            },
+        );
+        let instance = Instance::new(&mut store, &module, &[collect.into()])?;
+        let seven = instance.get_func(&mut store, "seven").unwrap();
+        let thread = instance.get_func(&mut store, "thread").unwrap();
+
+        let mut scope = RootScope::new(&mut store);
+        for n in [0, 1, 10_000] {
+            let externref = ExternRef::new(&mut scope, n)?;
+            let args = [
+                Val::I32(n),
+                Val::ExternRef(Some(externref)),
+                Val::FuncRef(Some(seven)),
+            ];
+            let mut results = [Val::null_extern_ref(), Val::null_func_ref()];
+            thread.call(&mut scope, &args, &mut results)?;
+
+            // The untyped path returns the same references it was given.
+            let Val::ExternRef(Some(r)) = results[0] else {
+                panic!("unexpected result {:?}", results[0]);
+            };
+            let data = r.data(&scope)?.unwrap().downcast_ref::<i32>().copied();
+            assert_eq!(data, Some(n));
+            let Val::FuncRef(Some(f)) = results[1] else {
+                panic!("unexpected result {:?}", results[1]);
+            };
+            let mut out = [Val::I32(0)];
+            f.call(&mut scope, &[], &mut out)?;
+            assert_eq!(out[0].unwrap_i32(), 7);
+
+            // And matches the typed path.
+            let typed = thread.typed::<
+                (i32, Option<Rooted<ExternRef>>, Option<Func>),
+                (Option<Rooted<ExternRef>>, Option<Func>),
+            >(&scope)?;
+            let (r, f) = typed.call(&mut scope, (n, Some(externref), Some(seven)))?;
+            let data = r
+                .unwrap()
+                .data(&scope)?
+                .unwrap()
+                .downcast_ref::<i32>()
+                .copied();
+            assert_eq!(data, Some(n));
+            assert_eq!(
+                f.unwrap().typed::<(), i32>(&scope)?.call(&mut scope, ())?,
+                7
+            );
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_report_to_log() -> Result<()> {
+    struct Capture(Mutex<Vec<(log::Level, String)>>);
+
+    impl log::Log for Capture {
+        fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+            metadata.target() == "wasmtime::compile"
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            if self.enabled(record.metadata()) {
+                let message = record.args().to_string();
+                self.0.lock().unwrap().push((record.level(), message));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static CAPTURE: Capture = Capture(Mutex::new(Vec::new()));
+    if log::set_logger(&CAPTURE).is_err() {
+        // Another logger is already installed for this process, so there's
+        // nothing to observe.
+        return Ok(());
+    }
+    log::set_max_level(log::LevelFilter::Trace);
+
+    // Other tests compile concurrently, so only look at lines mentioning the
+    // functions defined here.
+    let lines = |name: &str| {
+        CAPTURE
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, message)| message.contains(&format!("`{name}`")))
+            .cloned()
+            .collect::<Vec<_>>()
+    };
+    let wat = r#"
+        (module
+            (func $report_to_log_countdown (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $report_to_log_countdown
+                end)
+            (func $report_to_log_regular (param i32) (result i32)
+                local.get 0
+                call $report_to_log_countdown)
+        )
+    "#;
+
+    // Nothing is logged by default.
+    Module::new(&engine(), wat)?;
+    assert!(lines("report_to_log_regular").is_empty());
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.report_to_log(true);
+    config.tail_call_options(options);
+    Module::new(&Engine::new(&config)?, wat)?;
+    let regular = lines("report_to_log_regular");
+    assert_eq!(regular.len(), 1, "{regular:?}");
+    assert_eq!(regular[0].0, log::Level::Trace);
+    assert!(regular[0].1.contains("function 1"), "{regular:?}");
+    assert!(regular[0].1.contains("regular"), "{regular:?}");
+    assert_eq!(lines("report_to_log_countdown").len(), 1);
+
+    // Functions whose frames couldn't be optimized are logged at the debug
+    // level with the reason.
+    let mut options = TailCallOptions::new();
+    options
+        .report_to_log(true)
+        .max_optimized_body_bytes(Some(1));
+    let mut config = self::config();
+    config.tail_call_options(options);
+    Module::new(&Engine::new(&config)?, wat)?;
+    let countdown = lines("report_to_log_countdown");
+    assert_eq!(countdown.len(), 2, "{countdown:?}");
+    assert_eq!(countdown[1].0, log::Level::Debug);
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(
+            countdown[1]
+                .1
+                .contains(&TailCallDeoptReason::FunctionTooLarge.to_string()),
+            "{countdown:?}"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_calls_after_stack_overflow() -> Result<()> {
+    let wat = format!(
+        r#"
+        (module
+            {COUNTDOWN}
+            (export "countdown" (func $countdown))
+            ;; Recurses regularly until the stack overflows, tail calling
+            ;; `$countdown` only if it ever reaches zero.
+            (func $overflow (export "overflow") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    local.get 0
+                    return_call $countdown
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    call $overflow
+                    i32.const 1
+                    i32.add
+                end)
+        )
+    "#
+    );
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, &wat)?;
+        assert_eq!(
+            call_classes(&module),
+            [CallClass::TailCallOnly, CallClass::Mixed]
+        );
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+        let overflow = instance.get_typed_func::<i32, i32>(&mut store, "overflow")?;
+
+        // The same store keeps working after each overflow, both for deep
+        // tail calls and for regular recursion that fits in the stack.
+        for _ in 0..3 {
+            let err = overflow.call(&mut store, i32::MAX).unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<Trap>(),
+                Some(&Trap::StackOverflow),
+                "relaxed_stack_check = {relaxed_stack_check}: {err:?}"
+            );
+            assert_eq!(countdown.call(&mut store, 1_000_000)?, 0);
+            assert_eq!(overflow.call(&mut store, 100)?, 100);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn return_call_ref_from_table_get() -> Result<()> {
+    // Each state reads the next state from a typed funcref table by a
+    // computed index and tail calls it through `return_call_ref`.
+    let wat = r#"
+        (module
+            (type $state (func (param i32 i32) (result i32)))
+            (table $states 4 (ref null $state))
+            (elem (table $states) (i32.const 0) (ref null $state)
+                (ref.func $add) (ref.func $double) (ref.func $sub))
+            (func $next (param $n i32) (param $acc i32) (result i32)
+                local.get $n
+                i32.eqz
+                if (result i32)
+                    local.get $acc
+                else
+                    local.get $n
+                    i32.const 1
+                    i32.sub
+                    local.get $acc
+                    local.get $n
+                    i32.const 3
+                    i32.rem_u
+                    table.get $states
+                    return_call_ref $state
+                end)
+            (func $add (type $state)
+                local.get 0
+                local.get 1
+                i32.const 3
+                i32.add
+                return_call $next)
+            (func $double (type $state)
+                local.get 0
+                local.get 1
+                i32.const 1
+                i32.shl
+                return_call $next)
+            (func $sub (type $state)
+                local.get 0
+                local.get 1
+                i32.const 5
+                i32.sub
+                return_call $next)
+            (func (export "run") (param i32 i32) (result i32)
+                local.get 0
+                local.get 1
+                return_call $next)
+            (func (export "dispatch-at") (param i32 i32 i32) (result i32)
+                local.get 1
+                local.get 2
+                local.get 0
+                table.get $states
+                return_call_ref $state)
+        )
+    "#;
+    let expected = |n: i32, acc: i32| {
+        let (mut n, mut acc) = (n, acc);
+        while n != 0 {
+            let index = n % 3;
+            n -= 1;
+            acc = match index {
+                0 => acc.wrapping_add(3),
+                1 => acc.wrapping_shl(1),
+                _ => acc.wrapping_sub(5),
+            };
+        }
+        acc
+    };
+
+    for relaxed_stack_check in [true, false] {
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        let mut config = config();
+        // As in `indirect_tail_calls_multiple_tables`, lazy table
+        // initialization would add a regular call to `table.get`.
+        config
+            .wasm_function_references(true)
+            .table_lazy_init(false)
+            .tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        assert!(module.is_tail_call_pure());
+        if relaxed_stack_check && cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+            assert!(
+                module
+                    .function_tail_call_frame_savings()
+                    .iter()
+                    .all(|(_, savings)| savings.is_some()),
+                "{:?}",
+                module.function_tail_call_frame_savings()
+            );
+        }
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let run = instance.get_typed_func::<(i32, i32), i32>(&mut store, "run")?;
+        for n in [0, 1, 2, 3, 10, 1_000_000] {
+            assert_eq!(run.call(&mut store, (n, 1))?, expected(n, 1), "n = {n}");
+        }
+
+        // Each entry dispatches to its own state, and the null entry traps.
+        let dispatch_at =
+            instance.get_typed_func::<(i32, i32, i32), i32>(&mut store, "dispatch-at")?;
+        assert_eq!(dispatch_at.call(&mut store, (0, 0, 1))?, 4);
+        assert_eq!(dispatch_at.call(&mut store, (1, 0, 1))?, 2);
+        assert_eq!(dispatch_at.call(&mut store, (2, 0, 1))?, -4);
+        let err = dispatch_at.call(&mut store, (3, 0, 1)).unwrap_err();
+        assert_eq!(err.downcast_ref::<Trap>(), Some(&Trap::NullReference));
+
+        assert_constant_stack(
+            &config,
+            &module,
+            "run",
+            &[Val::I32(10), Val::I32(1)],
+            &[Val::I32(1_000_000), Val::I32(1)],
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn unconditional_tail_loop_warnings() -> Result<()> {
+    let engine = Engine::default();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (import "" "host" (func $host (param i32)))
+                (func $spin (param i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                    return_call $spin)
+                (func $ping (param i32)
+                    local.get 0
+                    return_call $pong)
+                (func $pong (param i32)
+                    local.get 0
+                    return_call $ping)
+                (func $into_spin (param i32)
+                    local.get 0
+                    return_call $spin)
+                (func $countdown (param i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $countdown)
+                (func $even (param i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        return
+                    end
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $odd)
+                (func $odd (param i32)
+                    local.get 0
+                    return_call $even)
+                (func $to_host (param i32)
+                    local.get 0
+                    return_call $host)
+            )
+        "#,
+    )?;
+    let loops = |module: &Module| {
+        module
+            .compile_warnings()
+            .into_iter()
+            .map(|warning| match warning {
+                CompileWarning::UnconditionalTailLoop { func_index } => func_index.as_u32(),
+                other => panic!("unexpected warning {other:?}"),
+            })
+            .collect::<Vec<_>>()
+    };
+
+    // Only the functions in cycles without a base case are reported: not
+    // `$into_spin`, which only leads into one, nor `$countdown` and the
+    // `$even`/`$odd` pair, which have conditional base cases, nor the tail
+    // call to an import.
+    assert_eq!(loops(&module), [1, 2, 3]);
+    assert!(
+        CompileWarning::UnconditionalTailLoop {
+            func_index: module.function_call_classes()[0].0
+        }
+        .to_string()
+        .contains("function 1")
+    );
+
+    let deserialized = unsafe { Module::deserialize(&engine, &module.serialize()?)? };
+    assert_eq!(loops(&deserialized), [1, 2, 3]);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn function_frame_layout() -> Result<()> {
+    let wat = format!(
+        r#"
+        (module
+            (import "" "f" (func $f))
+            {COUNTDOWN}
+            (func (export "regular") (param i32) (result i32)
+                call $f
+                local.get 0
+                call $countdown)
+        )
+    "#
+    );
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, &wat)?;
+        let classes = module.function_call_classes();
+        let countdown = module.function_frame_layout(classes[0].0).unwrap();
+        let regular = module.function_frame_layout(classes[1].0).unwrap();
+
+        // Only the function which only makes tail calls hands its return
+        // address on to its callees.
+        assert!(countdown.reuses_return_address);
+        assert!(!regular.reuses_return_address);
+        assert!(!regular.tail_call_optimized);
+        if !relaxed_stack_check {
+            assert!(!countdown.tail_call_optimized);
+        }
+
+        // The layout agrees with the frame savings reported for each function.
+        for (index, savings) in module.function_tail_call_frame_savings() {
+            let layout = module.function_frame_layout(index).unwrap();
+            assert_eq!(layout.tail_call_optimized, savings.is_some(), "{layout:?}");
+            if let Some(savings) = savings {
+                assert_eq!(savings, layout.setup_area_size, "{layout:?}");
+            }
+            assert!(layout.total_size() >= layout.setup_area_size);
+            assert!(layout.callee_saves_size >= layout.callee_saved_registers);
+        }
+
+        // Imported functions have no frame of their own.
+        let imported = module
+            .call_graph()
+            .into_iter()
+            .find_map(|edge| match edge.to {
+                CallTarget::Direct(to) if to.as_u32() == 0 => Some(to),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(module.function_frame_layout(imported), None);
+
+        // Frame layouts are recorded in the compiled artifact.
+        let deserialized = unsafe { Module::deserialize(&engine, &module.serialize()?)? };
+        assert_eq!(
+            deserialized.function_frame_layout(classes[0].0),
+            Some(countdown)
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn backtrace_tail_call_sites() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (type $t (func (param i32) (result i32)))
+                (table 2 funcref)
+                (elem (i32.const 0) $target)
+                (func $target (type $t)
+                    unreachable)
+                (func $dispatch (type $t)
+                    local.get 0
+                    local.get 0
+                    return_call_indirect (type $t))
+                (func (export "outer") (type $t)
+                    local.get 0
+                    call $dispatch
+                    i32.const 1
+                    i32.add)
+            )
+        "#,
+    )?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let outer = instance.get_typed_func::<i32, i32>(&mut store, "outer")?;
+    let frames = |store: &mut Store<()>, arg: i32| {
+        let err = outer.call(&mut *store, arg).unwrap_err();
+        let trap = *err.downcast_ref::<Trap>().unwrap();
+        let frames = err
+            .downcast_ref::<WasmBacktrace>()
+            .unwrap()
+            .frames()
+            .iter()
+            .map(|frame| (frame.func_index(), frame.is_tail_call_site()))
+            .collect::<Vec<_>>();
+        (trap, frames)
+    };
+
+    // A tail call that traps before transferring control leaves its frame
+    // in the backtrace, stopped at the tail call.
+    assert_eq!(
+        frames(&mut store, 1),
+        (Trap::IndirectCallToNull, vec![(1, true), (2, false)])
+    );
+    assert_eq!(
+        frames(&mut store, 5),
+        (Trap::TableOutOfBounds, vec![(1, true), (2, false)])
+    );
+
+    // Once the tail call completes, the callee has replaced the caller's
+    // frame.
+    assert_eq!(
+        frames(&mut store, 0),
+        (Trap::UnreachableCodeReached, vec![(0, false), (2, false)])
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn start_function_tail_calls() -> Result<()> {
+    let wat = r#"
+        (module
+            (global $counter (export "counter") (mut i32) (i32.const 0))
+            (func $init
+                i32.const 100000
+                return_call $bump)
+            ;; Counts up `$counter` once per tail call.
+            (func $bump (param i32)
+                global.get $counter
+                i32.const 1
+                i32.add
+                global.set $counter
+                local.get 0
+                i32.eqz
+                if
+                    return
+                end
+                local.get 0
+                i32.const 1
+                i32.sub
+                return_call $bump)
+            (start $init)
+        )
+    "#;
+    for relaxed_stack_check in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(relaxed_stack_check);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        assert_eq!(
+            call_classes(&module),
+            [CallClass::TailCallOnly, CallClass::TailCallOnly]
+        );
+
+        // The start function's tail calls run to completion during
+        // instantiation, both directly and through a linker.
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let counter = instance.get_global(&mut store, "counter").unwrap();
+        assert_eq!(counter.get(&mut store).unwrap_i32(), 100_001);
+
+        let linker = Linker::new(&engine);
+        let instance = linker.instantiate(&mut store, &module)?;
+        let counter = instance.get_global(&mut store, "counter").unwrap();
+        assert_eq!(counter.get(&mut store).unwrap_i32(), 100_001);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn min_stack_bytes() -> Result<()> {
+    let wat = r#"
+        (module
+            (import "" "f" (func $f))
+            (table 1 funcref)
+            (func $even (export "even") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 1
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $odd
+                end)
+            (func $odd (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $even
+                end)
+            (func (export "leaf") (result i32)
+                i32.const 42)
+            (func $recurse (export "recurse") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    call $recurse
+                end)
+            (func (export "indirect")
+                i32.const 0
+                return_call_indirect)
+            (func (export "to-import")
+                return_call $f)
+            (global (export "g") i32 (i32.const 0))
+        )
+    "#;
+    let engine = engine();
+    let module = Module::new(&engine, wat)?;
+    let classes = module.function_call_classes();
+    let even = module.function_frame_layout(classes[0].0).unwrap();
+    let odd = module.function_frame_layout(classes[1].0).unwrap();
+    let leaf = module.function_frame_layout(classes[2].0).unwrap();
+
+    // Exports which only reach tail calls are bounded by their largest frame.
+    let expected = u64::from(even.total_size().max(odd.total_size()));
+    assert_eq!(module.min_stack_bytes("even"), Some(expected));
+    assert_eq!(
+        module.min_stack_bytes("leaf"),
+        Some(u64::from(leaf.total_size()))
+    );
+
+    // Regular calls, indirect calls, and calls to imports aren't bounded.
+    assert_eq!(module.min_stack_bytes("recurse"), None);
+    assert_eq!(module.min_stack_bytes("indirect"), None);
+    assert_eq!(module.min_stack_bytes("to-import"), None);
+
+    // Neither are things that aren't exported functions.
+    assert_eq!(module.min_stack_bytes("g"), None);
+    assert_eq!(module.min_stack_bytes("missing"), None);
+
+    // The bound is recorded in the compiled artifact.
+    let deserialized = unsafe { Module::deserialize(&engine, &module.serialize()?)? };
+    assert_eq!(deserialized.min_stack_bytes("even"), Some(expected));
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_calls_with_v128() -> Result<()> {
+    let mut config = config();
+    config.wasm_simd(true);
+    let mut options = TailCallOptions::new();
+    let deopts = collect_deopts(&mut options);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        r#"
+            (module
+                (func $a (export "run") (param $n i32) (param $acc v128) (param $step v128)
+                    (result v128)
+                    local.get $n
+                    i32.eqz
+                    if (result v128)
+                        local.get $acc
+                    else
+                        local.get $n
+                        i32.const 1
+                        i32.sub
+                        local.get $acc
+                        local.get $step
+                        i32x4.add
+                        local.get $step
+                        return_call $b
+                    end)
+                (func $b (param i32 v128 v128) (result v128)
+                    local.get 0
+                    local.get 1
+                    local.get 2
+                    return_call $a)
+                (func $few (export "few") (param v128) (result v128)
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    local.get 0
+                    return_call $many)
+                (func $many (param v128 v128 v128 v128 v128 v128 v128 v128 v128 v128)
+                    (result v128)
+                    local.get 0
+                    local.get 9
+                    i32x4.add)
+            )
+        "#,
+    )?;
+
+    // Vector arguments which fit in registers don't prevent the optimization,
+    // but ones spilled to the stack are reported like any other stack
+    // arguments.
+    let deopts = deopts.lock().unwrap().clone();
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert_eq!(deopts.len(), 1, "{deopts:?}");
+        assert_eq!(deopts[0].func_index.as_u32(), 2);
+        assert_eq!(deopts[0].reason, TailCallDeoptReason::StackArgsPresent);
+        assert!(deopts[0].outgoing_args_size > 0);
+    } else {
+        assert_eq!(deopts.len(), 3, "{deopts:?}");
+        for deopt in deopts.iter() {
+            assert_eq!(deopt.reason, TailCallDeoptReason::UnsupportedCallConv);
+        }
+    }
+
+    fn lanes(lanes: [u32; 4]) -> V128 {
+        let mut bytes = [0; 16];
+        for (chunk, lane) in bytes.chunks_mut(4).zip(lanes) {
+            chunk.copy_from_slice(&lane.to_le_bytes());
+        }
+        V128::from(u128::from_le_bytes(bytes))
+    }
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run = instance.get_typed_func::<(i32, V128, V128), V128>(&mut store, "run")?;
+    let result = run.call(
+        &mut store,
+        (100_000, lanes([1, 2, 3, 4]), lanes([1, 1, 1, u32::MAX])),
+    )?;
+    assert_eq!(
+        result.as_u128(),
+        lanes([100_001, 100_002, 100_003, 4u32.wrapping_sub(100_000)]).as_u128()
+    );
+
+    let few = instance.get_typed_func::<V128, V128>(&mut store, "few")?;
+    let result = few.call(&mut store, lanes([1, 2, 3, 0x8000_0000]))?;
+    assert_eq!(result.as_u128(), lanes([2, 4, 6, 0]).as_u128());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn max_tail_call_chain_length() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.max_chain_length(Some(1000));
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                (import "" "tick" (func $tick))
+                {COUNTDOWN}
+                (export "countdown" (func $countdown))
+                (func (export "repeat") (param i32)
+                    loop
+                        i32.const 900
+                        call $countdown
+                        drop
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        local.tee 0
+                        br_if 0
+                    end)
+                (func $ticking (export "ticking") (param i32)
+                    local.get 0
+                    i32.eqz
+                    if
+                        return
+                    end
+                    call $tick
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $ticking)
+                (func $forever (export "forever")
+                    return_call $forever)
+            )
+        "#
+        ),
+    )?;
+    let mut store = Store::new(&engine, 0);
+    let tick = Func::wrap(&mut store, |mut caller: Caller<'_, u32>| {
+        *caller.data_mut() += 1;
+    });
+    let instance = Instance::new(&mut store, &module, &[tick.into()])?;
+
+    // Up to 1000 consecutive tail calls are allowed.
+    let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+    assert_eq!(countdown.call(&mut store, 1000)?, 0);
+    let err = countdown.call(&mut store, 1001).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TailCallChainTooLong);
+
+    // A trapping chain doesn't count against the next call from the host.
+    assert_eq!(countdown.call(&mut store, 1000)?, 0);
+
+    // Regular calls break up chains, so many short chains are fine, including
+    // those broken up by calls to the host.
+    let repeat = instance.get_typed_func::<i32, ()>(&mut store, "repeat")?;
+    repeat.call(&mut store, 100)?;
+    let ticking = instance.get_typed_func::<i32, ()>(&mut store, "ticking")?;
+    ticking.call(&mut store, 5000)?;
+    assert_eq!(*store.data(), 5000);
+
+    // An infinite tail loop traps rather than hanging.
+    let forever = instance.get_typed_func::<(), ()>(&mut store, "forever")?;
+    let err = forever.call(&mut store, ()).unwrap_err();
+    assert_eq!(err.downcast::<Trap>()?, Trap::TailCallChainTooLong);
+
+    // The limit is compiled into the code, so modules must be loaded with
+    // the same limit.
+    let bytes = module.serialize()?;
+    let mut config = self::config();
+    let mut options = TailCallOptions::new();
+    options.max_chain_length(Some(10));
+    config.tail_call_options(options);
+    let err = unsafe { Module::deserialize(&Engine::new(&config)?, &bytes) }.unwrap_err();
+    assert!(
+        format!("{err:?}").contains("maximum tail call chain length of 1000"),
+        "bad error: {err:?}"
+    );
+    assert!(unsafe { Module::deserialize(&self::engine(), &bytes) }.is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn take_tail_call_stats() -> Result<()> {
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.profiling(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let wat = r#"
+            (module
+                (func $even (export "even") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $odd
+                    end)
+                (func $odd (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $even
+                    end)
+            )
+        "#;
+    let module = Module::new(&engine, wat)?;
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let even = instance.get_typed_func::<i32, i32>(&mut store, "even")?;
+    assert_eq!(even.call(&mut store, 11)?, 0);
+    let stats = instance.take_tail_call_stats(&mut store);
+
+    // Taking the stats resets the counts, so the next snapshot only covers
+    // tail calls made since.
+    assert!(instance.tail_call_counts(&store).is_empty());
+    assert_eq!(even.call(&mut store, 2)?, 1);
+    let more = instance.take_tail_call_stats(&mut store);
+    assert_eq!(
+        instance.take_tail_call_stats(&mut store),
+        TailCallStats::default()
+    );
+
+    // The snapshots outlive the store they were taken from.
+    drop(store);
+    let counts = |stats: &TailCallStats| {
+        stats
+            .counts
+            .iter()
+            .map(|(index, count)| (index.as_u32(), *count))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(counts(&stats), [(0, 6), (1, 5)]);
+    assert_eq!(counts(&more), [(0, 1), (1, 1)]);
+
+    // Without profiling there's nothing to take.
+    let engine = self::engine();
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    assert_eq!(
+        instance.take_tail_call_stats(&mut store),
+        TailCallStats::default()
+    );
+    Ok(())
+}
+
+#[test]
+fn supports_tail_call_optimization() -> Result<()> {
+    let native = if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        TailCallSupport::FullyOptimized
+    } else {
+        TailCallSupport::SemanticsOnly
+    };
+    assert_eq!(Config::new().supports_tail_call_optimization(), native);
+    assert_eq!(config().supports_tail_call_optimization(), native);
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.relaxed_stack_check(false);
+    config.tail_call_options(options);
+    assert_eq!(
+        config.supports_tail_call_optimization(),
+        TailCallSupport::SemanticsOnly
+    );
+
+    // The answer depends on the configured target rather than the host.
+    let mut config = self::config();
+    config.target("aarch64-unknown-linux-gnu")?;
+    assert_eq!(
+        config.supports_tail_call_optimization(),
+        TailCallSupport::FullyOptimized
+    );
+    config.target("s390x-unknown-linux-gnu")?;
+    assert_eq!(
+        config.supports_tail_call_optimization(),
+        TailCallSupport::SemanticsOnly
+    );
+
+    let mut config = Config::new();
+    config.wasm_tail_call(false);
+    assert_eq!(
+        config.supports_tail_call_optimization(),
+        TailCallSupport::Unsupported
+    );
+
+    let mut config = Config::new();
+    config.strategy(Strategy::Winch);
+    assert_eq!(
+        config.supports_tail_call_optimization(),
+        TailCallSupport::Unsupported
+    );
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn return_call_with_loop_carried_values() -> Result<()> {
+    let wat = r#"
+            (module
+                (func $step (export "run") (param $n i32) (param $acc i64) (result i64)
+                    (local $k i32) (local $a i64) (local $b i64)
+                    local.get $n
+                    i32.eqz
+                    if
+                        local.get $acc
+                        return
+                    end
+                    loop $l
+                        ;; `$a` and `$b` are carried around the loop.
+                        local.get $a
+                        local.get $k
+                        i64.extend_i32_u
+                        i64.add
+                        local.set $a
+                        local.get $b
+                        local.get $a
+                        local.get $n
+                        i64.extend_i32_u
+                        i64.mul
+                        i64.xor
+                        local.set $b
+
+                        ;; Every third step leaves from the middle of the loop
+                        ;; with the values accumulated so far.
+                        local.get $k
+                        i32.const 3
+                        i32.eq
+                        local.get $n
+                        i32.const 3
+                        i32.rem_u
+                        i32.eqz
+                        i32.and
+                        if
+                            local.get $n
+                            i32.const 1
+                            i32.sub
+                            local.get $acc
+                            local.get $a
+                            i64.add
+                            local.get $b
+                            i64.add
+                            return_call $step
+                        end
+
+                        local.get $k
+                        i32.const 1
+                        i32.add
+                        local.tee $k
+                        i32.const 8
+                        i32.lt_u
+                        br_if $l
+                    end
+                    local.get $n
+                    i32.const 1
+                    i32.sub
+                    local.get $acc
+                    local.get $a
+                    i64.add
+                    local.get $b
+                    i64.add
+                    return_call $step)
+            )
+        "#;
+
+    fn expected(mut n: u32) -> i64 {
+        let mut acc = 0i64;
+        while n != 0 {
+            let (mut a, mut b) = (0i64, 0i64);
+            for k in 0..8u32 {
+                a = a.wrapping_add(i64::from(k));
+                b ^= a.wrapping_mul(i64::from(n));
+                if k == 3 && n % 3 == 0 {
+                    break;
+                }
+            }
+            acc = acc.wrapping_add(a).wrapping_add(b);
+            n -= 1;
+        }
+        acc
+    }
+
+    // The values must survive both real tail calls and tail calls compiled
+    // as a jump back to the start of the function.
+    for (self_recursion_loop, class) in [
+        (false, CallClass::TailCallOnly),
+        (true, CallClass::SelfTailLoop),
+    ] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.self_recursion_loop(self_recursion_loop);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        assert_eq!(call_classes(&module), [class]);
+
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let run = instance.get_typed_func::<(i32, i64), i64>(&mut store, "run")?;
+        for n in [0, 1, 2, 3, 10, 1000, 200_000] {
+            assert_eq!(run.call(&mut store, (n, 0))?, expected(n as u32), "n = {n}");
+        }
+        assert_constant_stack(
+            &config,
+            &module,
+            "run",
+            &[Val::I32(10), Val::I64(0)],
+            &[Val::I32(200_000), Val::I64(0)],
+        );
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_recursion_with_i64_params() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $fact (export "fact") (param $n i64) (param $acc i64) (result i64)
+                local.get $n
+                i64.eqz
+                if (result i64)
+                    local.get $acc
+                else
+                    local.get $n
+                    i64.const 1
+                    i64.sub
+                    local.get $acc
+                    local.get $n
+                    i64.mul
+                    return_call $fact
+                end)
+            (func $sum (export "sum") (param $n i64) (param $acc i64) (result i64)
+                local.get $n
+                i64.eqz
+                if (result i64)
+                    local.get $acc
+                else
+                    local.get $n
+                    i64.const 1
+                    i64.sub
+                    local.get $acc
+                    local.get $n
+                    i64.const 0x100000001
+                    i64.mul
+                    i64.add
+                    return_call $sum
+                end)
+        )
+    "#;
+    let fact = |n: u64| (1..=n).fold(1u64, |acc, i| acc.wrapping_mul(i)) as i64;
+    let sum = |n: u64| {
+        (1..=n).fold(0u64, |acc, i| {
+            acc.wrapping_add(i.wrapping_mul(0x1_0000_0001))
+        }) as i64
+    };
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    let deopts = collect_deopts(&mut options);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    assert_eq!(
+        call_classes(&module),
+        [CallClass::TailCallOnly, CallClass::TailCallOnly]
+    );
+
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let run_fact = instance.get_typed_func::<(i64, i64), i64>(&mut store, "fact")?;
+    let run_sum = instance.get_typed_func::<(i64, i64), i64>(&mut store, "sum")?;
+    for n in [0, 1, 20, 25, 100] {
+        assert_eq!(
+            run_fact.call(&mut store, (n, 1))?,
+            fact(n as u64),
+            "fact({n})"
+        );
+    }
+    assert_eq!(run_fact.call(&mut store, (20, 1))?, 2432902008176640000);
+    for n in [0, 1, 100, 1_000_000] {
+        assert_eq!(run_sum.call(&mut store, (n, 0))?, sum(n as u64), "sum({n})");
+    }
+    assert_constant_stack(
+        &config,
+        &module,
+        "sum",
+        &[Val::I64(10), Val::I64(0)],
+        &[Val::I64(1_000_000), Val::I64(0)],
+    );
+
+    // On 64-bit native targets the `i64` parameters are passed in registers
+    // and both frames are optimized. Elsewhere, including on 32-bit hosts,
+    // which run Pulley, the optimization is unsupported and is reported as
+    // such.
+    let reported = mem::take(&mut *deopts.lock().unwrap());
+    if cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        assert!(reported.is_empty(), "{reported:?}");
+    } else {
+        assert_eq!(reported.len(), 2, "{reported:?}");
+        for deopt in reported.iter() {
+            assert_eq!(deopt.reason, TailCallDeoptReason::UnsupportedCallConv);
+        }
+    }
+
+    // The same holds when cross-compiling for 32-bit Pulley.
+    let mut config = config.clone();
+    config.target("pulley32")?;
+    Engine::new(&config)?.precompile_module(wat.as_bytes())?;
+    let reported = mem::take(&mut *deopts.lock().unwrap());
+    assert_eq!(reported.len(), 2, "{reported:?}");
+    for deopt in reported.iter() {
+        assert_eq!(deopt.reason, TailCallDeoptReason::UnsupportedCallConv);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn engine_tail_calls_executed() -> Result<()> {
+    let wat = r#"
+        (module
+            (func $even (export "even") (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 1
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $odd
+                end)
+            (func $odd (param i32) (result i32)
+                local.get 0
+                i32.eqz
+                if (result i32)
+                    i32.const 0
+                else
+                    local.get 0
+                    i32.const 1
+                    i32.sub
+                    return_call $even
+                end)
+        )
+    "#;
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.profiling(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    assert_eq!(engine.tail_calls_executed(), 0);
+
+    // Tail calls made in every store, on every thread, are counted.
+    std::thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|| {
+                let mut store = Store::new(&engine, ());
+                let instance = Instance::new(&mut store, &module, &[]).unwrap();
+                let even = instance
+                    .get_typed_func::<i32, i32>(&mut store, "even")
+                    .unwrap();
+                assert_eq!(even.call(&mut store, 1000).unwrap(), 1);
+            });
+        }
+    });
+    assert_eq!(engine.tail_calls_executed(), 4000);
+
+    // Taking an instance's stats doesn't reset the engine's total.
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let even = instance.get_typed_func::<i32, i32>(&mut store, "even")?;
+    even.call(&mut store, 10)?;
+    assert_eq!(instance.take_tail_call_stats(&mut store).counts.len(), 2);
+    assert_eq!(engine.tail_calls_executed(), 4010);
+
+    // Without profiling nothing is counted.
+    let engine = self::engine();
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, ());
+    let instance = Instance::new(&mut store, &module, &[])?;
+    let even = instance.get_typed_func::<i32, i32>(&mut store, "even")?;
+    even.call(&mut store, 1000)?;
+    assert_eq!(engine.tail_calls_executed(), 0);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_calls_between_functions_with_different_locals() -> Result<()> {
+    // `$many` fills all of its locals with non-zero values before tail calling
+    // `$few`, and `$few` does the same before tail calling `$many`, so stale
+    // values left behind in a reused frame would show up in the callee's
+    // locals. Each function returns a non-zero sum if any local it hasn't
+    // written is non-zero.
+    let wat = r#"
+        (module
+            (func $many (export "many") (param $n i32) (result i64)
+                (local $a i64) (local $b i64) (local $c i64) (local $d i64)
+                (local $e i64) (local $f i64) (local $g i64) (local $h i64)
+                (local $x f64) (local $y i32) (local $v v128)
+                (local $sum i64)
+
+                ;; Check that every local starts out as zero.
+                (local.set $sum
+                    (i64.or
+                        (i64.or
+                            (i64.or (local.get $a) (local.get $b))
+                            (i64.or (local.get $c) (local.get $d)))
+                        (i64.or
+                            (i64.or (local.get $e) (local.get $f))
+                            (i64.or (local.get $g) (local.get $h)))))
+                (local.set $sum
+                    (i64.or (local.get $sum) (i64.reinterpret_f64 (local.get $x))))
+                (local.set $sum
+                    (i64.or (local.get $sum) (i64.extend_i32_u (local.get $y))))
+                (local.set $sum
+                    (i64.or (local.get $sum) (i64x2.extract_lane 0 (local.get $v))))
+                (local.set $sum
+                    (i64.or (local.get $sum) (i64x2.extract_lane 1 (local.get $v))))
+                (if (i64.ne (local.get $sum) (i64.const 0))
+                    (then (return (local.get $sum))))
+                (if (i32.eqz (local.get $n))
+                    (then (return (i64.const 0))))
+
+                ;; Then dirty them all before tail calling `$few`.
+                (local.set $a (i64.const -1))
+                (local.set $b (i64.const -2))
+                (local.set $c (i64.const -3))
+                (local.set $d (i64.const -4))
+                (local.set $e (i64.const -5))
+                (local.set $f (i64.const -6))
+                (local.set $g (i64.const -7))
+                (local.set $h (i64.const -8))
+                (local.set $x (f64.const 1.5))
+                (local.set $y (i32.const -9))
+                (local.set $v (v128.const i64x2 -10 -11))
+                (call $sink (local.get $a) (local.get $b) (local.get $c) (local.get $d)
+                    (local.get $e) (local.get $f) (local.get $g) (local.get $h)
+                    (local.get $x) (local.get $y) (local.get $v))
+                (return_call $few (i32.sub (local.get $n) (i32.const 1))))
+
+            (func $few (export "few") (param $n i32) (result i64)
+                (local $a i64) (local $b i32)
+                (if (i64.ne (i64.or (local.get $a) (i64.extend_i32_u (local.get $b)))
+                        (i64.const 0))
+                    (then (return (i64.or (local.get $a) (i64.extend_i32_u (local.get $b))))))
+                (if (i32.eqz (local.get $n))
+                    (then (return (i64.const 0))))
+                (local.set $a (i64.const -12))
+                (local.set $b (i32.const -13))
+                (drop (i64.add (local.get $a) (i64.extend_i32_u (local.get $b))))
+                (return_call $many (i32.sub (local.get $n) (i32.const 1))))
+
+            ;; Keeps the locals live across a call so they're spilled to the
+            ;; caller's frame.
+            (func $sink (param i64 i64 i64 i64 i64 i64 i64 i64 f64 i32 v128))
+        )
+    "#;
+
+    for optimize in [true, false] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.relaxed_stack_check(optimize);
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let many = instance.get_typed_func::<i32, i64>(&mut store, "many")?;
+        let few = instance.get_typed_func::<i32, i64>(&mut store, "few")?;
+        for n in [0, 1, 2, 3, 10, 1001] {
+            assert_eq!(
+                many.call(&mut store, n)?,
+                0,
+                "many({n}), optimize={optimize}"
+            );
+            assert_eq!(few.call(&mut store, n)?, 0, "few({n}), optimize={optimize}");
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn reject_tail_call_modules() -> Result<()> {
+    let wat = format!(
+        r#"
+        (module
+            (func $leaf (param i32) (result i32)
+                local.get 0)
+            {COUNTDOWN}
+            (export "countdown" (func $countdown))
+            (func $mixed (param i32) (result i32)
+                local.get 0
+                call $leaf
+                return_call $countdown)
+        )
+    "#
+    );
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.reject_modules(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let err = Module::new(&engine, &wat).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "function 1 (`countdown`) makes tail calls, and modules which make tail calls are \
+         rejected by `TailCallOptions::reject_modules`"
+    );
+
+    // Artifacts compiled elsewhere are rejected too.
+    let compiled = self::engine().precompile_module(wat.as_bytes())?;
+    let err = unsafe { Module::deserialize(&engine, &compiled) }.unwrap_err();
+    assert!(
+        err.to_string().contains("function 1 (`countdown`)"),
+        "{err}"
+    );
+
+    // Modules which don't make tail calls are unaffected.
+    Module::new(
+        &engine,
+        r#"
+            (module
+                (func $leaf (param i32) (result i32)
+                    local.get 0)
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    call $leaf)
+            )
+        "#,
+    )?;
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_fuel_cost() -> Result<()> {
+    let fuel_consumed = |cost: Option<u64>, self_loop: bool, n: i32| -> Result<u64> {
+        let mut options = TailCallOptions::new();
+        options.self_recursion_loop(self_loop);
+        if let Some(cost) = cost {
+            options.fuel_cost(cost);
+        }
+        let mut config = config();
+        config.consume_fuel(true).tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = countdown_module(&engine)?;
+        let mut store = Store::new(&engine, ());
+        store.set_fuel(u64::MAX)?;
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let countdown = instance.get_typed_func::<i32, i32>(&mut store, "countdown")?;
+        let before = store.get_fuel()?;
+        assert_eq!(countdown.call(&mut store, n)?, 0);
+        Ok(before - store.get_fuel()?)
+    };
+
+    let n = 100;
+    for self_loop in [false, true] {
+        // By default a tail call costs the same as a regular call.
+        let default = fuel_consumed(None, self_loop, n)?;
+        assert_eq!(fuel_consumed(Some(1), self_loop, n)?, default);
+
+        // Otherwise each of the `n` tail calls costs the configured amount.
+        let free = fuel_consumed(Some(0), self_loop, n)?;
+        let expensive = fuel_consumed(Some(10), self_loop, n)?;
+        assert_eq!(default - free, n as u64);
+        assert_eq!(expensive - free, 10 * n as u64);
+    }
+
+    let mut config = self::config();
+    let mut options = TailCallOptions::new();
+    options.fuel_cost(u64::MAX);
+    config.tail_call_options(options);
+    assert!(Engine::new(&config).is_err());
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn max_static_tail_chain() -> Result<()> {
+    let engine = engine();
+    let module = Module::new(
+        &engine,
+        format!(
+            r#"
+            (module
+                (import "" "host" (func $host (param i32) (result i32)))
+                (type $t (func (param i32) (result i32)))
+                (table 1 funcref)
+                (memory (export "memory") 1)
+
+                ;; A linear pipeline of tail calls.
+                (func $a (export "pipeline") (param i32) (result i32)
+                    local.get 0
+                    return_call $b)
+                (func $b (param i32) (result i32)
+                    local.get 0
+                    return_call $c)
+                (func $c (param i32) (result i32)
+                    local.get 0
+                    return_call $d)
+                (func $d (param i32) (result i32)
+                    local.get 0)
+
+                ;; A regular call starts a new chain, so this is only as long
+                ;; as the pipeline it calls.
+                (func (export "calls-pipeline") (param i32) (result i32)
+                    local.get 0
+                    call $a
+                    return_call $d)
+
+                ;; A tail call to an import ends the chain.
+                (func (export "to-host") (param i32) (result i32)
+                    local.get 0
+                    return_call $host)
+
+                ;; A state machine's states tail call each other forever.
+                (func $even (export "state-machine") (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 1
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $odd
+                    end)
+                (func $odd (param i32) (result i32)
+                    local.get 0
+                    i32.eqz
+                    if (result i32)
+                        i32.const 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        return_call $even
+                    end)
+
+                ;; So do self-recursive functions.
+                {COUNTDOWN}
+                (export "countdown" (func $countdown))
+
+                ;; Regular recursion doesn't make chains of tail calls.
+                (func $fib (export "fib") (param i32) (result i32)
+                    local.get 0
+                    i32.const 2
+                    i32.lt_u
+                    if (result i32)
+                        local.get 0
+                    else
+                        local.get 0
+                        i32.const 1
+                        i32.sub
+                        call $fib
+                        local.get 0
+                        i32.const 2
+                        i32.sub
+                        call $fib
+                        i32.add
+                    end)
+
+                ;; The callee of an indirect call isn't known.
+                (func (export "indirect") (param i32) (result i32)
+                    local.get 0
+                    i32.const 0
+                    return_call_indirect (type $t))
+            )
+        "#
+        ),
+    )?;
+    assert_eq!(module.max_static_tail_chain("pipeline"), Some(3));
+    assert_eq!(module.max_static_tail_chain("calls-pipeline"), Some(3));
+    assert_eq!(module.max_static_tail_chain("to-host"), Some(1));
+    assert_eq!(module.max_static_tail_chain("state-machine"), None);
+    assert_eq!(module.max_static_tail_chain("countdown"), None);
+    assert_eq!(module.max_static_tail_chain("fib"), Some(0));
+    assert_eq!(module.max_static_tail_chain("indirect"), None);
+    assert_eq!(module.max_static_tail_chain("memory"), None);
+    assert_eq!(module.max_static_tail_chain("missing"), None);
+
+    // The static bound is exactly the runtime limit the pipeline needs.
+    for (limit, ok) in [(3, true), (2, false)] {
+        let mut config = config();
+        let mut options = TailCallOptions::new();
+        options.max_chain_length(Some(limit));
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(
+            &engine,
+            r#"
+                (module
+                    (func $a (export "pipeline") (param i32) (result i32)
+                        local.get 0
+                        return_call $b)
+                    (func $b (param i32) (result i32)
+                        local.get 0
+                        return_call $c)
+                    (func $c (param i32) (result i32)
+                        local.get 0
+                        return_call $d)
+                    (func $d (param i32) (result i32)
+                        local.get 0)
+                )
+            "#,
+        )?;
+        assert_eq!(module.max_static_tail_chain("pipeline"), Some(3));
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let pipeline = instance.get_typed_func::<i32, i32>(&mut store, "pipeline")?;
+        match pipeline.call(&mut store, 7) {
+            Ok(result) => {
+                assert!(ok, "limit {limit}");
+                assert_eq!(result, 7);
+            }
+            Err(e) => {
+                assert!(!ok, "limit {limit}: {e:?}");
+                assert_eq!(e.downcast_ref::<Trap>(), Some(&Trap::TailCallChainTooLong));
+            }
+        }
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn stores_before_tail_calls_are_visible() -> Result<()> {
+    // `$write` updates a global and memory immediately before tail calling
+    // `$check`, which reads them back. `$check` returns -1 if it doesn't see
+    // the values just written, and otherwise keeps going until `$n` is zero,
+    // returning the sum of what it read.
+    let wat = r#"
+        (module
+            (global $g (export "g") (mut i32) (i32.const -1))
+            (memory (export "memory") 1)
+
+            (func $write (export "run") (param $n i32) (param $sum i32) (result i32)
+                (global.set $g (local.get $n))
+                (i32.store
+                    (i32.shl (i32.and (local.get $n) (i32.const 15)) (i32.const 2))
+                    (i32.mul (local.get $n) (i32.const 3)))
+                (return_call $check (local.get $n) (local.get $sum)))
+
+            (func $check (param $n i32) (param $sum i32) (result i32)
+                (local $value i32)
+                (if (i32.ne (global.get $g) (local.get $n))
+                    (then (return (i32.const -1))))
+                (local.set $value
+                    (i32.load
+                        (i32.shl (i32.and (local.get $n) (i32.const 15)) (i32.const 2))))
+                (if (i32.ne (local.get $value) (i32.mul (local.get $n) (i32.const 3)))
+                    (then (return (i32.const -1))))
+                (local.set $sum (i32.add (local.get $sum) (local.get $value)))
+                (if (i32.eqz (local.get $n))
+                    (then (return (local.get $sum))))
+                (return_call $write
+                    (i32.sub (local.get $n) (i32.const 1))
+                    (local.get $sum)))
+
+            ;; Writes to the global and memory and tail calls itself, only
+            ;; reading them back at the end.
+            (func $self (export "self") (param $n i32) (result i32)
+                (global.set $g (i32.add (global.get $g) (i32.const 1)))
+                (i32.store offset=64
+                    (i32.const 0)
+                    (i32.add (i32.load offset=64 (i32.const 0)) (local.get $n)))
+                (if (i32.eqz (local.get $n))
+                    (then (return (i32.load offset=64 (i32.const 0)))))
+                (return_call $self (i32.sub (local.get $n) (i32.const 1))))
+        )
+    "#;
+
+    for (relaxed_stack_check, self_loop) in [(true, true), (true, false), (false, false)] {
+        let mut options = TailCallOptions::new();
+        options
+            .relaxed_stack_check(relaxed_stack_check)
+            .self_recursion_loop(self_loop);
+        let mut config = config();
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, wat)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let run = instance.get_typed_func::<(i32, i32), i32>(&mut store, "run")?;
+        let self_ = instance.get_typed_func::<i32, i32>(&mut store, "self")?;
+        let global = instance.get_global(&mut store, "g").unwrap();
+        let memory = instance.get_memory(&mut store, "memory").unwrap();
+
+        let n = 100_000;
+        let expected = (0..=n).map(|i| i * 3).fold(0i32, i32::wrapping_add);
+        assert_eq!(run.call(&mut store, (n, 0))?, expected);
+
+        // The last writes are visible after returning to the host. The last
+        // write to each slot of memory was made when `$n` was its index.
+        assert_eq!(global.get(&mut store).unwrap_i32(), 0);
+        let mut bytes = [0; 4];
+        for i in 0..16 {
+            memory.read(&store, i * 4, &mut bytes)?;
+            assert_eq!(i32::from_le_bytes(bytes), i as i32 * 3, "memory[{i}]");
+        }
+
+        global.set(&mut store, Val::I32(0))?;
+        let expected = (0..=n).fold(0i32, i32::wrapping_add);
+        assert_eq!(self_.call(&mut store, n)?, expected);
+        assert_eq!(global.get(&mut store).unwrap_i32(), n + 1);
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn caller_is_tail_called() -> Result<()> {
+    let wat = r#"
+        (module
+            (import "" "record" (func $record))
+            (import "" "nested" (func $nested))
+            (table $t 1 1 funcref)
+            (elem (table $t) (i32.const 0) func $record)
+
+            (func (export "call") (call $record))
+            (func (export "tail") (return_call $record))
+            (func (export "call-indirect") (call_indirect (i32.const 0)))
+            (func (export "tail-indirect") (return_call_indirect (i32.const 0)))
+
+            ;; Reaches the host through a function defined in this module,
+            ;; which tail calls it.
+            (func $forward (return_call $record))
+            (func (export "call-forward") (call $forward))
+
+            (func (export "tail-nested") (return_call $nested))
+        )
+    "#;
+
+    let mut config = config();
+    let mut options = TailCallOptions::new();
+    options.host_tracking(true);
+    config.tail_call_options(options);
+    let engine = Engine::new(&config)?;
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, Vec::new());
+    let record = Func::wrap(&mut store, |mut caller: Caller<'_, Vec<bool>>| {
+        let is_tail = caller.is_tail_called();
+        caller.data_mut().push(is_tail);
+    });
+    // Records how it was called both before and after calling back into
+    // wasm, which makes a regular call to `record` in between.
+    let nested = Func::wrap(&mut store, |mut caller: Caller<'_, Vec<bool>>| {
+        let is_tail = caller.is_tail_called();
+        caller.data_mut().push(is_tail);
+        let call = caller.get_export("call").unwrap().into_func().unwrap();
+        call.call(&mut caller, &[], &mut [])?;
+        let is_tail = caller.is_tail_called();
+        caller.data_mut().push(is_tail);
+        Ok(())
+    });
+    let instance = Instance::new(&mut store, &module, &[record.into(), nested.into()])?;
+
+    for (name, expected) in [
+        ("call", &[false][..]),
+        ("tail", &[true]),
+        ("call-indirect", &[false]),
+        ("tail-indirect", &[true]),
+        ("call-forward", &[true]),
+        ("tail-nested", &[true, false, true]),
+        ("call", &[false]),
+    ] {
+        store.data_mut().clear();
+        let func = instance.get_typed_func::<(), ()>(&mut store, name)?;
+        func.call(&mut store, ())?;
+        assert_eq!(store.data(), expected, "{name}");
+    }
+
+    // Calling the host function directly isn't a tail call, even right after
+    // wasm tail called it.
+    instance
+        .get_typed_func::<(), ()>(&mut store, "tail")?
+        .call(&mut store, ())?;
+    store.data_mut().clear();
+    record.typed::<(), ()>(&store)?.call(&mut store, ())?;
+    assert_eq!(store.data(), &[false]);
+
+    // Without tracking, host functions are never reported as tail called.
+    let engine = self::engine();
+    let module = Module::new(&engine, wat)?;
+    let mut store = Store::new(&engine, Vec::new());
+    let record = Func::wrap(&mut store, |mut caller: Caller<'_, Vec<bool>>| {
+        let is_tail = caller.is_tail_called();
+        caller.data_mut().push(is_tail);
+    });
+    let nested = Func::wrap(&mut store, |_: Caller<'_, Vec<bool>>| {});
+    let instance = Instance::new(&mut store, &module, &[record.into(), nested.into()])?;
+    for name in ["call", "tail", "tail-indirect"] {
+        let func = instance.get_typed_func::<(), ()>(&mut store, name)?;
+        func.call(&mut store, ())?;
+    }
+    assert_eq!(store.data(), &[false, false, false]);
+    Ok(())
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_zero_unused_frame() -> Result<()> {
+    // A function with enough live values to need spill slots, and enough
+    // parameters to pass some on the stack, which tail calls itself both
+    // directly and indirectly, mixing up its parameters each time.
+    const PARAMS: usize = 16;
+    let params = (0..PARAMS)
+        .map(|i| format!("(param $p{i} i64)"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let next = (0..PARAMS)
+        .map(|i| match i + 1 {
+            PARAMS => "(i64.xor (local.get $p0) (i64.extend_i32_u (local.get $n)))".to_string(),
+            j => format!("(i64.add (local.get $p{j}) (i64.mul (local.get $p{i}) (i64.const 3)))"),
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    let sum = (1..PARAMS).fold("(local.get $p0)".to_string(), |sum, i| {
+        format!("(i64.add {sum} (local.get $p{i}))")
+    });
+    let wat = format!(
+        r#"
+            (module
+                (type $t (func (param i32) {params} (result i64)))
+                (table 1 funcref)
+                (elem (i32.const 0) $mix)
+                (func $mix (export "mix") (type $t) (param $n i32) {params} (result i64)
+                    (if (i32.eqz (local.get $n))
+                        (then (return {sum})))
+                    (if (i32.and (local.get $n) (i32.const 1))
+                        (then
+                            (return_call_indirect (type $t)
+                                (i32.sub (local.get $n) (i32.const 1))
+                                {next}
+                                (i32.const 0))))
+                    (return_call $mix (i32.sub (local.get $n) (i32.const 1)) {next}))
+            )
+        "#
+    );
+
+    let reference = |n: u32, mut p: [i64; PARAMS]| {
+        for n in (1..=n).rev() {
+            let mut next = [0; PARAMS];
+            for i in 0..PARAMS - 1 {
+                next[i] = p[i + 1].wrapping_add(p[i].wrapping_mul(3));
+            }
+            next[PARAMS - 1] = p[0] ^ i64::from(n);
+            p = next;
+        }
+        p.iter().fold(0, |sum: i64, p| sum.wrapping_add(*p))
+    };
+
+    // Zeroing is only implemented for targets where frames are optimized.
+    let mut options = TailCallOptions::new();
+    options.zero_unused_frame(true);
+    let mut config = self::config();
+    config.target("pulley64")?.tail_call_options(options);
+    let err = Engine::new(&config).err().unwrap().to_string();
+    assert!(
+        err.contains("only supported on x86_64 and aarch64"),
+        "{err}"
+    );
+    if !cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        let mut config = self::config();
+        let mut options = TailCallOptions::new();
+        options.zero_unused_frame(true);
+        config.tail_call_options(options);
+        assert!(Engine::new(&config).is_err());
+        return Ok(());
+    }
+
+    // Zeroing happens after the frame's canary is checked and its registers
+    // are restored, so no tail call traps and every result matches.
+    for relaxed_stack_check in [true, false] {
+        let mut options = TailCallOptions::new();
+        options
+            .zero_unused_frame(true)
+            .debug_assertions(true)
+            .relaxed_stack_check(relaxed_stack_check);
+        let mut config = self::config();
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, &wat)?;
+        let mut store = Store::new(&engine, ());
+        let instance = Instance::new(&mut store, &module, &[])?;
+        let mix = instance.get_func(&mut store, "mix").unwrap();
+
+        for n in [0, 1, 2, 7, 1000, 100_000] {
+            let p: [i64; PARAMS] =
+                std::array::from_fn(|i| (i as i64 + 1).wrapping_mul(0x0123_4567_89ab_cdef));
+            let args = std::iter::once(Val::I32(n as i32))
+                .chain(p.iter().map(|p| Val::I64(*p)))
+                .collect::<Vec<_>>();
+            let mut results = [Val::I64(0)];
+            mix.call(&mut store, &args, &mut results)?;
+            assert_eq!(results[0].unwrap_i64(), reference(n, p), "mix({n})");
+        }
+    }
+    Ok(())
+}
+
+/// The number of values `tail_call_zero_unused_frame_scrubs_stack` spills.
+const SPILLED_SECRETS: i64 = 24;
+
+/// The base of the values `tail_call_zero_unused_frame_scrubs_stack` spills.
+const SECRET_BASE: i64 = 0x1ec2_e75e_c2e7_5e00;
+
+/// Returns whether any word of this thread's stack from `bottom` up to `top`
+/// is one of the values spilled by `tail_call_zero_unused_frame_scrubs_stack`,
+/// which are `3 * (SECRET_BASE + i)`.
+///
+/// This is never inlined, so its own frame, which is the only place other than
+/// wasm that computes those values, is always below `bottom`.
+#[inline(never)]
+fn stack_has_secret(bottom: usize, top: usize) -> bool {
+    (bottom.next_multiple_of(8)..top).step_by(8).any(|addr| {
+        // SAFETY: everything from `bottom` up to `top` is part of the stack of
+        // the current thread.
+        let word = unsafe { (addr as *const i64).read_volatile() };
+        (0..SPILLED_SECRETS).any(|i| word == SECRET_BASE.wrapping_add(i).wrapping_mul(3))
+    })
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn tail_call_zero_unused_frame_scrubs_stack() -> Result<()> {
+    if !cfg!(any(target_arch = "x86_64", target_arch = "aarch64")) {
+        return Ok(());
+    }
+
+    // `run` multiplies its parameter by each of the constants below and keeps
+    // the products live across a call so they're spilled into its frame, then
+    // tail calls `$big`. That has a bigger frame, all of which is built over
+    // `run`'s old one, and calls the host probe before writing to its spill
+    // slots. Anything left over from `run` is then still on the stack between
+    // the probe and the test, and the host never computes the products itself.
+    let locals = |name: &str, n: i64| {
+        (0..n)
+            .map(|i| format!("(local ${name}{i} i64)"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let sets = |name: &str, n: i64, value: &dyn Fn(i64) -> String| {
+        (0..n)
+            .map(|i| format!("(local.set ${name}{i} {})", value(i)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let fold = |op: &str, name: &str, n: i64| {
+        (1..n).fold(format!("(local.get ${name}0)"), |acc, i| {
+            format!("({op} {acc} (local.get ${name}{i}))")
+        })
+    };
+    const TEMPS: i64 = 64;
+    let wat = format!(
+        r#"
+            (module
+                (import "" "probe" (func $probe))
+                (func $keep (param i64))
+                (func (export "run") (param $x i64) (result i64)
+                    {secrets}
+                    {set_secrets}
+                    (call $keep {sum_secrets})
+                    (return_call $big {xor_secrets}))
+                (func $big (param $v i64) (result i64)
+                    {temps}
+                    (call $probe)
+                    {set_temps}
+                    (call $keep {sum_temps})
+                    {xor_temps})
+            )
+        "#,
+        secrets = locals("s", SPILLED_SECRETS),
+        set_secrets = sets("s", SPILLED_SECRETS, &|i| format!(
+            "(i64.mul (local.get $x) (i64.const {}))",
+            SECRET_BASE + i
+        )),
+        sum_secrets = fold("i64.add", "s", SPILLED_SECRETS),
+        xor_secrets = fold("i64.xor", "s", SPILLED_SECRETS),
+        temps = locals("t", TEMPS),
+        set_temps = sets("t", TEMPS, &|i| format!(
+            "(i64.mul (local.get $v) (i64.const {}))",
+            i + 5
+        )),
+        sum_temps = fold("i64.add", "t", TEMPS),
+        xor_temps = fold("i64.xor", "t", TEMPS),
+    );
+
+    struct Probe {
+        top: usize,
+        found: bool,
+    }
+
+    // Without zeroing the probe does find `run`'s leftovers, which shows that
+    // it would notice them. That's checked last so they're not left behind on
+    // the stack for the zeroing run to find.
+    for zero in [true, false] {
+        let mut options = TailCallOptions::new();
+        options.zero_unused_frame(zero);
+        let mut config = self::config();
+        config.tail_call_options(options);
+        let engine = Engine::new(&config)?;
+        let module = Module::new(&engine, &wat)?;
+        let mut store = Store::new(
+            &engine,
+            Probe {
+                top: 0,
+                found: false,
+            },
+        );
+        let probe = Func::wrap(&mut store, |mut caller: Caller<'_, Probe>| {
+            let marker = 0u64;
+            let bottom = std::ptr::addr_of!(marker) as usize;
+            let top = caller.data().top;
+            caller.data_mut().found = stack_has_secret(bottom, top);
+        });
+        let instance = Instance::new(&mut store, &module, &[probe.into()])?;
+        let run = instance.get_typed_func::<i64, i64>(&mut store, "run")?;
+
+        let marker = 0u64;
+        store.data_mut().top = std::ptr::addr_of!(marker) as usize;
+        run.call(&mut store, 3)?;
+        assert_eq!(store.data().found, !zero, "zero_unused_frame({zero})");
+    }
+    Ok(())
+}
@@ -1833,3 +1833,23 @@ fn return_call_to_aborting_wasm_function_with_stack_adjustments() -> Result<()>
 
     Ok(())
 }
+
+#[test]
+fn tail_call_trap_codes() {
+    // These codes are exposed through the C API as `wasmtime_trap_code_t`, so
+    // they must not change.
+    for (trap, code, message) in [
+        (Trap::TailCallFuelExhausted, 24, "tail-call fuel"),
+        (Trap::RegularCallDepthExceeded, 25, "regular call depth"),
+        (
+            Trap::TailCallFrameCorruption,
+            26,
+            "tail call frame corruption",
+        ),
+        (Trap::TailCallChainTooLong, 27, "tail call chain length"),
+    ] {
+        assert_eq!(trap as u8, code, "{trap:?}");
+        assert_eq!(Trap::from_u8(code), Some(trap));
+        assert!(trap.to_string().contains(message), "{trap}");
+    }
+}